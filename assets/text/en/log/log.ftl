@@ -20,16 +20,23 @@ tile-information-no-info = No infomation about this tile.
 exit-to-outside = {$player} exited to outside.
 enter-site = {$player} entered {$site}.
 change-floor = {$player} moved to the next floor.
+auto_travel-hostile-found = {$chara} stopped because {$target} came into view.
+auto_travel-damaged = {$chara} stopped because of damage.
+auto_travel-item-found = {$chara} found {$item}.
 
 # Messages about character status
 
 skill-level-up = {$chara}'s {$skill} level increased.
 skill-learned = {$chara} learned {$skill} skill.
 skill-already-learned = {$chara} have already learned {$skill} skill.
+party-join = {$chara} joined your party.
+party-full = {$chara} cannot join your party because it is full.
+party-leave = {$chara} left your party.
 
 # Messages about combat
 
 attack = {$attacker} attacked {$target}.
+sneak-attack = {$attacker} made a sneak attack on {$target}!
 shot-target = {$attacker} shot {$target}.
 no-ranged-weapon-equipped = No ranged weapon equipped!
 no-target = {$chara} could not find any target.
@@ -50,6 +57,13 @@ item-equip = {$chara} equipped {$item}.
 item-pickup = {$chara} picked up {$item}.
 item-drop = {$chara} dropped {$item}.
 item-owned-by-others = {$item} is owned by others.
+item-equip-cursed = {$item} is cursed!
+item-cursed-cannot-remove = {$item} is cursed and cannot be removed.
+item-kind-identified = It was {$item}.
+item-appraised = You appraised {$item}.
+item-identified-all = All items of {$chara} are identified.
+item-identified-nothing = There is nothing to identify.
+curse-removed = The curses on the items of {$chara} are removed.
 drink-item = {$chara} drank a {$item}.
 eat-item = {$chara} ate a {$item}.
 
@@ -61,6 +75,15 @@ chopping-no-tree = There is no tree to chop.
 chopping-not-adjacent-tile = Need to specify an adjacent tile to chop.
 mining-not-adjacent-tile = Need to specify an adjacent tile to mine.
 
+# Messages about dungeon features
+trap-activated = {$chara} stepped on a trap!
+door-unlock-by-key = {$chara} unlocked the door with a key.
+door-unlock-by-lockpicking = {$chara} picked the lock.
+door-lockpicking-failed = {$chara} failed to pick the lock.
+search-found-trap = You found a trap.
+search-found-hidden-passage = You found a hidden passage.
+search-found-nothing = You found nothing.
+
 # Messages about using items
 use_item-deed-invalid-map = You can not use deeds in this area.
 use_item-deed-occupied = You can not use deeds at this occupied area.
@@ -76,11 +99,16 @@ not-scanned = {$chara} isn't scanned.
 asleep = {$chara} is asleep.
 poison-damage = {$chara} was damaged by poison ({$damage}).
 shop-lack-of-money = {$chara} do not have enough money to buy it.
+shop-lack-of-money-shopkeeper = {$chara} does not have enough money to buy it.
 
 # Messages about quest
 
 quest-complete-slay_monsters = Quest completed! Slayed {$monster} x {$n}.
 quest-reward-receive-money = Recieved {$money} gold as the quest reward.
+quest-complete = Quest completed! ({$quest})
+quest-failed = Quest failed... ({$quest})
+quest-updated = Quest updated. ({$quest})
+quest-deliver-receive-item = Received {$item} to deliver.
 
 # Messages about creation
 
@@ -0,0 +1,22 @@
+# Appearances of unidentified items
+
+item_appearance-potion-red = red potion
+item_appearance-potion-blue = blue potion
+item_appearance-potion-green = green potion
+item_appearance-potion-yellow = yellow potion
+item_appearance-potion-purple = purple potion
+item_appearance-potion-black = black potion
+item_appearance-potion-white = white potion
+item_appearance-potion-cloudy = cloudy potion
+item_appearance-potion-bubbly = bubbly potion
+item_appearance-potion-glowing = glowing potion
+item_appearance-potion-murky = murky potion
+item_appearance-potion-golden = golden potion
+item_appearance-device-oak = oak wand
+item_appearance-device-bone = bone wand
+item_appearance-device-iron = iron wand
+item_appearance-device-crystal = crystal wand
+item_appearance-device-ivory = ivory wand
+item_appearance-device-copper = copper wand
+item_appearance-device-obsidian = obsidian wand
+item_appearance-device-jade = jade wand
@@ -5,3 +5,9 @@ item_info_text-nutrition = Nutrition: {$nutrition}
 item_info_text-melee_weapon = Melee attack: {$eff}
 item_info_text-ranged_weapon = Ranged attack: {$eff}
 item_info_text-defence = Defence: Physical {$physical}, Fire {$fire}, Cold {$cold}, Shock {$shock}, Poison {$poison}, Spirit {$spirit}
+item_info_text-unidentified = This item is unidentified
+item_info_text-bless_state = This item is {$bless}
+item_info_text-affix-attr = {$attr} +{$value}
+item_info_text-affix-defence = {$element} defence +{$value}
+item_info_text-affix-elemental_damage = Additional {$element} damage {$value}%
+item_info_text-affix-on_hit_status = {$value}% chance to cause {$status} on hit
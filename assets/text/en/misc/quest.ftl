@@ -1,2 +1,18 @@
 quest-slay_monsters = Slay monsters ({$monster})
 desc-quest-slay_monsters = Slay "{$monster}" x {$n}.
+quest-deliver = Delivery ({$item})
+desc-quest-deliver = Deliver "{$item}" to {$site}.
+quest-fetch = Gathering ({$item})
+desc-quest-fetch = Bring "{$item}" x {$n} to the client.
+quest-escort = Escort ({$chara})
+desc-quest-escort = Take "{$chara}" to {$site} safely.
+quest-explore_floor = Exploration (floor {$floor})
+desc-quest-explore_floor = Reach floor {$floor} of {$site}.
+quest-craft = Crafting ({$item})
+desc-quest-craft = Create "{$item}" x {$n}.
+desc-quest-reward = Reward: {$money} gold.
+desc-quest-deadline = Deadline: {$year}/{$month}/{$day}.
+story-quest-completed = {$quest} (Completed)
+story-quest-failed = {$quest} (Failed)
+desc-story-quest-stage = {$year}/{$month}/{$day} {$text}
+desc-story-quest-objectives = Objectives:
@@ -47,6 +47,11 @@ skill_kind-healing = Healing
 skill_kind-throwing = Throwing
 skill_kind-magic_device = Magic Device
 skill_kind-mining = Mining
+skill_kind-lockpicking = Lockpicking
+skill_kind-searching = Searching
+skill_kind-stealth = Stealth
+skill_kind-trading = Trading
+skill_kind-appraisal = Appraisal
 
 # WeaponKind
 
@@ -72,6 +77,38 @@ creation_kind-craft = Craft
 creation_kind-pharmacy = Pharmacy
 creation_kind-smith = Smith
 
+# Element
+
+element-none = None
+element-physical = Physical
+element-fire = Fire
+element-cold = Cold
+element-shock = Shock
+element-poison = Poison
+element-spirit = Spirit
+
+# CharaAttrKind
+
+chara_attr_kind-str = STR
+chara_attr_kind-vit = VIT
+chara_attr_kind-dex = DEX
+chara_attr_kind-int = INT
+chara_attr_kind-wil = WIL
+chara_attr_kind-cha = CHA
+chara_attr_kind-spd = SPD
+
+# StatusEffect
+
+status_effect-asleep = Sleep
+status_effect-poison = Poison
+status_effect-scanned = Scan
+
+# BlessState
+
+bless_state-cursed = cursed
+bless_state-normal = uncursed
+bless_state-blessed = blessed
+
 # MedicalEffect
 
 medical_effect-none = No effect
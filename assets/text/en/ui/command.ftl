@@ -4,6 +4,7 @@ command-open_equip_win = Equipment
 command-open_exit_win = Open Exit Window
 command-open_game_info_win = Game Information
 command-open_help_win = Help
+command-open_quest_win = Quests
 command-open_status_win = Status
 command-open_item_menu = Item Menu
 command-pick_up_item = Pick Up Item
@@ -12,3 +13,4 @@ command-drink_item = Drink Item
 command-eat_item = Eat Item
 command-release_item = Release Item
 command-shot = Shot
+command-search = Search
@@ -23,3 +23,5 @@ label_text-creation-enough-ingredients = Enough ingredients
 label_text-creation-not-enough-ingredients = Not enough ingredients
 label_text-creation-required_skill = Required Skill
 list_item_text-creation-no_ingredient = No available {$group}
+label_text-save-autosave = Autosave
+label_text-shop-money = {$player} G (Shop: {$shop} G)
@@ -8,14 +8,21 @@ start = Rusted Ruins へようこそ! (version : {$version})
 exit-to-outside = {$player}は外へ出た。
 enter-site = {$player}は{$site}に足を踏み入れた。
 change-floor = {$player}は別の階へ移動した。
+auto_travel-hostile-found = {$target}が視界に入ったため{$chara}は立ち止まった。
+auto_travel-damaged = ダメージを受けたため{$chara}は立ち止まった。
+auto_travel-item-found = {$chara}は{$item}を見つけた。
 
 # Messages about character status
 
 skill-level-up = {$chara}の{$skill}スキルが上昇した。
+party-join = {$chara}が仲間に加わった。
+party-full = 仲間がいっぱいのため{$chara}は加われない。
+party-leave = {$chara}が仲間から離れた。
 
 # Messages about combat
 
 attack = {$attacker}は{$target}を攻撃した。
+sneak-attack = {$attacker}は{$target}に不意打ちした！
 shot-target = {$attacker}は{$target}を撃った。
 no-ranged-weapon-equipped = 遠隔武器を装備していない。
 no-target = {$chara}はターゲットを見つけられなかった。
@@ -35,9 +42,25 @@ killed-by-starve-damage = {$chara}は餓死した。
 item-equip = {$chara}は{$item}を装備した。
 item-pickup = {$chara}は{$item}を拾った。
 item-drop = {$chara}は{$item}を床に置いた。
+item-equip-cursed = {$item}は呪われている！
+item-cursed-cannot-remove = {$item}は呪われていて外せない。
+item-kind-identified = それは{$item}だった。
+item-appraised = {$item}を鑑定した。
+item-identified-all = {$chara}の持ち物はすべて鑑定された。
+item-identified-nothing = 鑑定するものがない。
+curse-removed = {$chara}の持ち物の呪いが解けた。
 drink-item = {$chara}は{$item}を飲み干した。
 eat-item = {$chara}は{$item}を食べた。
 
+# Messages about dungeon features
+trap-activated = {$chara}は罠を踏んだ！
+door-unlock-by-key = {$chara}は鍵で扉を開けた。
+door-unlock-by-lockpicking = {$chara}は錠を外した。
+door-lockpicking-failed = {$chara}は錠を外せなかった。
+search-found-trap = 罠を見つけた。
+search-found-hidden-passage = 隠し通路を見つけた。
+search-found-nothing = 何も見つからなかった。
+
 # Messages when a character is affected
 
 heal-hp = {$chara}は回復した({$value})。
@@ -48,11 +71,16 @@ not-scanned = {$chara}はまだスキャンされていない。
 asleep = {$chara}は眠っている。
 poison-damage = {$chara}は毒のダメージを受けた({$damage})。
 shop-lack-of-money = {$chara}はそれを買うのに十分なお金を持っていない。
+shop-lack-of-money-shopkeeper = {$chara}はそれを買い取るのに十分なお金を持っていない。
 
 # Messages about quest
 
 quest-complete-slay_monsters = {$monster}を{$n}体始末した。クエストを達成した。
 quest-reward-receive-money = {$money}ゴールドを報酬として受け取った。
+quest-complete = クエストを達成した。({$quest})
+quest-failed = クエストに失敗した…({$quest})
+quest-updated = クエストが進展した。({$quest})
+quest-deliver-receive-item = 届け物の{$item}を受け取った。
 
 # Messages about creation
 
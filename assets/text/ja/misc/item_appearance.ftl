@@ -0,0 +1,22 @@
+# Appearances of unidentified items
+
+item_appearance-potion-red = 赤い薬
+item_appearance-potion-blue = 青い薬
+item_appearance-potion-green = 緑の薬
+item_appearance-potion-yellow = 黄色い薬
+item_appearance-potion-purple = 紫の薬
+item_appearance-potion-black = 黒い薬
+item_appearance-potion-white = 白い薬
+item_appearance-potion-cloudy = 濁った薬
+item_appearance-potion-bubbly = 泡立つ薬
+item_appearance-potion-glowing = 光る薬
+item_appearance-potion-murky = どろどろした薬
+item_appearance-potion-golden = 金色の薬
+item_appearance-device-oak = 樫の杖
+item_appearance-device-bone = 骨の杖
+item_appearance-device-iron = 鉄の杖
+item_appearance-device-crystal = 水晶の杖
+item_appearance-device-ivory = 象牙の杖
+item_appearance-device-copper = 銅の杖
+item_appearance-device-obsidian = 黒曜石の杖
+item_appearance-device-jade = 翡翠の杖
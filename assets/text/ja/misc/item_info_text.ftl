@@ -5,3 +5,9 @@ item_info_text-nutrition = 栄養価: {$nutrition}
 item_info_text-melee_weapon = 近接攻撃力: {$eff}
 item_info_text-ranged_weapon = 遠隔攻撃力: {$eff}
 item_info_text-defence = 守備力: 物理 {$physical}, 火炎 {$fire}, 冷気 {$cold}, 電撃 {$shock}, 毒 {$poison}, 精神 {$spirit}
+item_info_text-unidentified = このアイテムは未鑑定だ
+item_info_text-bless_state = このアイテムは{$bless}ものだ
+item_info_text-affix-attr = {$attr} +{$value}
+item_info_text-affix-defence = {$element}守備力 +{$value}
+item_info_text-affix-elemental_damage = {$element}追加ダメージ {$value}%
+item_info_text-affix-on_hit_status = 命中時 {$value}% の確率で{$status}
@@ -1,2 +1,18 @@
 quest-slay_monsters = モンスター討伐 ({$monster})
 desc-quest-slay_monsters = {$monster}を{$n}体倒す。
+quest-deliver = 配達 ({$item})
+desc-quest-deliver = {$item}を{$site}へ届ける。
+quest-fetch = 収集 ({$item})
+desc-quest-fetch = {$item}を{$n}個依頼者に届ける。
+quest-escort = 護衛 ({$chara})
+desc-quest-escort = {$chara}を{$site}まで無事に送り届ける。
+quest-explore_floor = 探索 ({$floor}階)
+desc-quest-explore_floor = {$site}の{$floor}階に到達する。
+quest-craft = 製作 ({$item})
+desc-quest-craft = {$item}を{$n}個作る。
+desc-quest-reward = 報酬: {$money}ゴールド。
+desc-quest-deadline = 期限: {$year}/{$month}/{$day}。
+story-quest-completed = {$quest} (達成)
+story-quest-failed = {$quest} (失敗)
+desc-story-quest-stage = {$year}/{$month}/{$day} {$text}
+desc-story-quest-objectives = 目標:
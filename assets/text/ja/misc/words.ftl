@@ -41,6 +41,11 @@ skill_kind-healing = 回復力
 skill_kind-throwing = 投擲
 skill_kind-magic_device = 魔道具
 skill_kind-mining = 採掘
+skill_kind-lockpicking = 開錠
+skill_kind-searching = 探索
+skill_kind-stealth = 隠密
+skill_kind-trading = 交易
+skill_kind-appraisal = 鑑定
 
 # WeaponKind
 
@@ -57,6 +62,38 @@ weapon_kind-whip = 鞭
 armor_kind-body = 体防具
 armor_kind-shield = 盾
 
+# Element
+
+element-none = なし
+element-physical = 物理
+element-fire = 火炎
+element-cold = 冷気
+element-shock = 電撃
+element-poison = 毒
+element-spirit = 精神
+
+# CharaAttrKind
+
+chara_attr_kind-str = 筋力
+chara_attr_kind-vit = 耐久
+chara_attr_kind-dex = 器用
+chara_attr_kind-int = 知力
+chara_attr_kind-wil = 意志
+chara_attr_kind-cha = 魅力
+chara_attr_kind-spd = 速度
+
+# StatusEffect
+
+status_effect-asleep = 睡眠
+status_effect-poison = 毒
+status_effect-scanned = 走査
+
+# BlessState
+
+bless_state-cursed = 呪われた
+bless_state-normal = 呪われていない
+bless_state-blessed = 祝福された
+
 # MedicalEffect
 
 medical_effect-none = 効果なし
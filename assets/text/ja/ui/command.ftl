@@ -4,6 +4,7 @@ command-open_equip_win = 装備画面
 command-open_exit_win = 終了画面
 command-open_game_info_win = ゲーム情報
 command-open_help_win = ヘルプ画面
+command-open_quest_win = クエスト
 command-open_status_win = ステータス画面
 command-open_item_menu = アイテム画面
 command-pick_up_item = アイテムを拾う
@@ -12,3 +13,4 @@ command-drink_item = アイテムを飲む
 command-eat_item = アイテムを食べる
 command-release_item = 魔道具を使う
 command-shot = 撃つ
+command-search = 探索する
@@ -7,3 +7,5 @@ tab_text-item_eat = 食べる
 tab_text-item_use = 使う
 tab_text-item_release = 解放
 tab_text-item_read = 読む
+label_text-save-autosave = オートセーブ
+label_text-shop-money = {$player} G (店: {$shop} G)
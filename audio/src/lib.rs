@@ -35,10 +35,12 @@ pub fn init<P: AsRef<Path>>(data_dirs: &[P], music_volume: i32) -> AudioContext
     }
 }
 
+/// Call f with AudioPlayer. Does nothing if not initialized, e.g. running without window.
 pub fn with_audio_player<F: FnOnce(&AudioPlayer)>(f: F) {
     AUDIO_PLAYER.with(|a| {
-        assert!(a.borrow().is_some());
-        f(a.borrow().as_ref().unwrap());
+        if let Some(a) = a.borrow().as_ref() {
+            f(a);
+        }
     });
 }
 
@@ -41,6 +41,9 @@ pub const MISC_TXT_DIR: &'static str = "misc";
 pub const READABLE_TXT_DIR: &'static str = "readable";
 pub const SAVE_DIR_NAME: &'static str = "save";
 pub const SAVE_EXTENSION: &'static str = "rrsve";
+pub const AUTOSAVE_DIR_NAME: &'static str = "autosave";
+/// Version of save data format. Increment it when a migration is added.
+pub const SAVE_FORMAT_VERSION: u32 = 1;
 
 /// Id table
 pub const ID_TABLE_SECTION_TAG: &'static str = "§";
@@ -7,9 +7,10 @@ use super::site::SiteId;
 use super::skill::{SkillKind, SkillList};
 use super::traits::*;
 use super::unknown_id_err;
+use crate::hashmap::HashMap;
 use crate::objholder::{CharaTemplateIdx, ItemIdx};
+use crate::script::ScriptHook;
 use geom::Vec2d;
-use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize)]
 pub struct CharaTemplateObject {
@@ -26,6 +27,9 @@ pub struct CharaTemplateObject {
     pub default_ai_kind: NpcAIKind,
     pub base_attr: CharaBaseAttr,
     pub skill_bonus: HashMap<SkillKind, SkillBonus>,
+    /// Scripts triggered by characters of this template
+    #[serde(default)]
+    pub hooks: Vec<ScriptHook>,
 }
 
 /// Character classes
@@ -71,6 +75,9 @@ pub struct Chara {
     pub rel: Relationship,
     /// When talked, execute this script
     pub trigger_talk: Option<String>,
+    /// Attribute changes by scripts and events
+    #[serde(default)]
+    pub attr_revision: CharaAttrRevision,
 }
 
 /// Character attributes
@@ -98,6 +105,50 @@ pub struct CharaAttributes {
     pub view_range: i32,
 }
 
+impl CharaAttributes {
+    pub fn get(&self, kind: CharaAttrKind) -> u16 {
+        match kind {
+            CharaAttrKind::Str => self.str,
+            CharaAttrKind::Vit => self.vit,
+            CharaAttrKind::Dex => self.dex,
+            CharaAttrKind::Int => self.int,
+            CharaAttrKind::Wil => self.wil,
+            CharaAttrKind::Cha => self.cha,
+            CharaAttrKind::Spd => self.spd,
+        }
+    }
+}
+
+/// Kinds of character attributes except HP
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CharaAttrKind {
+    Str,
+    Vit,
+    Dex,
+    Int,
+    Wil,
+    Cha,
+    Spd,
+}
+
+impl std::str::FromStr for CharaAttrKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "str" => CharaAttrKind::Str,
+            "vit" => CharaAttrKind::Vit,
+            "dex" => CharaAttrKind::Dex,
+            "int" => CharaAttrKind::Int,
+            "wil" => CharaAttrKind::Wil,
+            "cha" => CharaAttrKind::Cha,
+            "spd" => CharaAttrKind::Spd,
+            _ => return Err(()),
+        })
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct CharaBaseAttr {
     pub base_hp: i32,
@@ -110,7 +161,7 @@ pub struct CharaBaseAttr {
     pub spd: i16,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct CharaAttrRevision {
     pub hp: i32,
     pub str: i16,
@@ -137,6 +188,21 @@ impl CharaBaseAttr {
     }
 }
 
+impl CharaAttrRevision {
+    pub fn add(&mut self, kind: CharaAttrKind, value: i16) {
+        let a = match kind {
+            CharaAttrKind::Str => &mut self.str,
+            CharaAttrKind::Vit => &mut self.vit,
+            CharaAttrKind::Dex => &mut self.dex,
+            CharaAttrKind::Int => &mut self.int,
+            CharaAttrKind::Wil => &mut self.wil,
+            CharaAttrKind::Cha => &mut self.cha,
+            CharaAttrKind::Spd => &mut self.spd,
+        };
+        *a = a.saturating_add(value);
+    }
+}
+
 /// Represents chara status
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum CharaStatus {
@@ -202,6 +268,7 @@ impl Default for Chara {
             skills: SkillList::default(),
             rel: Relationship::NEUTRAL,
             trigger_talk: None,
+            attr_revision: CharaAttrRevision::default(),
         }
     }
 }
@@ -216,6 +283,8 @@ pub enum CharaKind {
     /// Indexed for a map. This character don't appear on other maps.
     /// Randomly generated characters use this id.
     OnMap,
+    /// Player's party member. This character travels with the player.
+    Party,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
@@ -228,6 +297,15 @@ pub enum CharaId {
     /// Indexed for a map. This character don't appear on other maps.
     /// Randomly generated characters use this id.
     OnMap { mid: MapId, n: u32 },
+    /// Player's party member. This character travels with the player.
+    Party { n: u32 },
+}
+
+/// Data to generate a NPC.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub struct NpcGen {
+    /// Chara template id
+    pub id: String,
 }
 
 /// Data to determine NPC character's actions
@@ -235,6 +313,35 @@ pub enum CharaId {
 pub struct CharaAI {
     pub kind: NpcAIKind,
     pub initial_pos: Vec2d,
+    /// The character this NPC follows
+    #[serde(default)]
+    pub leader: Option<CharaId>,
+    /// Awareness of enemies
+    #[serde(default)]
+    pub awareness: Awareness,
+}
+
+/// NPC's awareness state of enemies
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Awareness {
+    /// Not aware of any enemy
+    #[default]
+    Unaware,
+    /// Heard or glimpsed something at the position, and will investigate it
+    Suspicious { pos: Vec2d, turns: u32 },
+    /// Chasing the target. The position is where the target was seen last
+    Hunting {
+        target: CharaId,
+        pos: Vec2d,
+        turns: u32,
+    },
+}
+
+impl Awareness {
+    pub fn is_hunting(&self) -> bool {
+        matches!(self, Awareness::Hunting { .. })
+    }
 }
 
 /// Rough kind of NPC AI
@@ -253,6 +360,8 @@ impl Default for CharaAI {
         CharaAI {
             kind: NpcAIKind::default(),
             initial_pos: Vec2d::new(0, 0),
+            leader: None,
+            awareness: Awareness::default(),
         }
     }
 }
@@ -266,8 +375,8 @@ pub struct CharaHolder {
 impl CharaHolder {
     pub(crate) fn new() -> CharaHolder {
         CharaHolder {
-            c: HashMap::new(),
-            on_map: HashMap::new(),
+            c: HashMap::default(),
+            on_map: HashMap::default(),
         }
     }
 
@@ -297,12 +406,26 @@ impl CharaHolder {
         .unwrap_or_else(|| unknown_id_err(cid))
     }
 
-    pub(crate) fn remove_chara(&mut self, cid: CharaId) {
+    pub(crate) fn remove_chara(&mut self, cid: CharaId) -> Option<Chara> {
         match cid {
             CharaId::OnMap { .. } => &mut self.on_map,
             _ => &mut self.c,
         }
-        .remove(&cid);
+        .remove(&cid)
+    }
+
+    /// Returns party member ids in order
+    pub fn party(&self) -> Vec<CharaId> {
+        let mut party: Vec<u32> = self
+            .c
+            .keys()
+            .filter_map(|cid| match cid {
+                CharaId::Party { n } => Some(*n),
+                _ => None,
+            })
+            .collect();
+        party.sort();
+        party.into_iter().map(|n| CharaId::Party { n }).collect()
     }
 
     pub(crate) fn replace_on_map_chara(
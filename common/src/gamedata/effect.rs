@@ -50,6 +50,10 @@ pub enum EffectKind {
     CharaScan,
     SkillLearning { skills: Vec<SkillKind> },
     Deed,
+    /// Identify all items of the user
+    Identify,
+    /// Remove curses of all items of the user
+    RemoveCurse,
 }
 
 impl Default for EffectKind {
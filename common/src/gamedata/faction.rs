@@ -25,6 +25,13 @@ impl Faction {
     pub fn set(&mut self, faction: FactionId, relation: FactionRelation) {
         self.relation_table.insert(faction, relation);
     }
+
+    /// Change the relation by given difference
+    pub fn change(&mut self, faction: FactionId, diff: i32) {
+        let relation = (i32::from(self.get(faction)) + diff)
+            .clamp(FACTION_RELATION_MIN.into(), FACTION_RELATION_MAX.into());
+        self.set(faction, FactionRelation(relation as i8));
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
@@ -58,19 +65,27 @@ const FACTION_RELATION_MIN: i8 = -100;
 impl std::ops::Add<i8> for FactionRelation {
     type Output = Self;
     fn add(self, other: i8) -> Self {
-        FactionRelation(std::cmp::min(
-            self.0.wrapping_add(other),
-            FACTION_RELATION_MAX,
-        ))
+        FactionRelation(
+            self.0
+                .saturating_add(other)
+                .clamp(FACTION_RELATION_MIN, FACTION_RELATION_MAX),
+        )
     }
 }
 
 impl std::ops::Sub<i8> for FactionRelation {
     type Output = Self;
     fn sub(self, other: i8) -> Self {
-        FactionRelation(std::cmp::max(
-            self.0.wrapping_sub(other),
-            FACTION_RELATION_MIN,
-        ))
+        FactionRelation(
+            self.0
+                .saturating_sub(other)
+                .clamp(FACTION_RELATION_MIN, FACTION_RELATION_MAX),
+        )
+    }
+}
+
+impl From<FactionRelation> for i32 {
+    fn from(relation: FactionRelation) -> i32 {
+        relation.0 as i32
     }
 }
@@ -1,9 +1,11 @@
+use super::chara::CharaAttrKind;
 use super::defs::*;
-use super::effect::Effect;
+use super::effect::{Effect, StatusEffect};
 use crate::objholder::ItemIdx;
 use bitflags::bitflags;
 use geom::Vec2d;
 use std::cmp::{Ord, Ordering, PartialOrd};
+use std::collections::BTreeSet;
 
 /// Game item
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -13,6 +15,8 @@ pub struct Item {
     pub flags: ItemFlags,
     pub quality: ItemQuality,
     pub attributes: Vec<ItemAttribute>,
+    #[serde(default)]
+    pub bless: BlessState,
 }
 
 /// ItemObject has detail data for one item
@@ -50,6 +54,9 @@ pub struct ItemObject {
     pub medical_effect: Option<Effect>,
     pub use_effect: Option<Effect>,
     pub tool_effect: ToolEffect,
+    /// Light radius when equipped as a tool
+    #[serde(default)]
+    pub light_radius: u16,
     /// (additional sp) = (nutriton) * (sp_nutrition_factor)
     pub nutrition: u16,
     /// Range of charges
@@ -84,7 +91,18 @@ impl Ord for Item {
         if order != Ordering::Equal {
             return order;
         }
-        self.attributes.cmp(&other.attributes)
+        let order = self.attributes.cmp(&other.attributes);
+        if order != Ordering::Equal {
+            return order;
+        }
+        let order = self.bless.cmp(&other.bless);
+        if order != Ordering::Equal {
+            return order;
+        }
+        // Unidentified items are not stacked with identified ones
+        self.flags
+            .contains(ItemFlags::UNIDENTIFIED)
+            .cmp(&other.flags.contains(ItemFlags::UNIDENTIFIED))
     }
 }
 
@@ -182,6 +200,50 @@ bitflags! {
     pub struct ItemFlags: u64 {
         const FIXED = 1 << 0;
         const OWNED = 1 << 1;
+        /// Quality and blessed state of this item are unknown to the player
+        const UNIDENTIFIED = 1 << 2;
+    }
+}
+
+/// Blessed or cursed state of an item
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlessState {
+    Cursed,
+    Normal,
+    Blessed,
+}
+
+impl Default for BlessState {
+    fn default() -> Self {
+        BlessState::Normal
+    }
+}
+
+/// Item kinds identified by the player.
+/// Kinds that have random appearances are shown by their appearances until identified.
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct ItemKnowledge {
+    /// Seed to shuffle appearances for each game
+    pub appearance_seed: u64,
+    identified: BTreeSet<ItemIdx>,
+}
+
+impl ItemKnowledge {
+    pub fn new(appearance_seed: u64) -> ItemKnowledge {
+        ItemKnowledge {
+            appearance_seed,
+            identified: BTreeSet::new(),
+        }
+    }
+
+    pub fn is_identified(&self, idx: ItemIdx) -> bool {
+        self.identified.contains(&idx)
+    }
+
+    /// Returns false if the kind is already identified
+    pub fn identify(&mut self, idx: ItemIdx) -> bool {
+        self.identified.insert(idx)
     }
 }
 
@@ -245,6 +307,23 @@ pub enum ItemAttribute {
     SkillLearning(super::skill::SkillKind),
     /// Title for readable item.
     Title(String),
+    /// Random affix of generated equipment
+    Affix { kind: AffixKind, value: u16 },
+}
+
+/// Kinds of random affixes of equipment.
+/// Ordered because items are sorted by their attributes.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AffixKind {
+    /// Bonus to a character attribute
+    Attr(CharaAttrKind),
+    /// Additional defence against an element
+    Defence(Element),
+    /// Additional damage of an element in percent of the weapon attack power
+    ElementalDamage(Element),
+    /// Probability in percent to cause a status effect on hit
+    OnHitStatus(StatusEffect),
 }
 
 pub type MaterialName = arrayvec::ArrayString<[u8; crate::basic::ARRAY_STR_ID_LEN]>;
@@ -481,7 +560,7 @@ impl ItemList {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum ItemMoveNum {
     All,
     Partial(u32),
@@ -708,6 +787,11 @@ impl EquipItemList {
         &self.item_list
     }
 
+    /// Mutable iterator of equipped items
+    pub fn items_mut(&mut self) -> impl Iterator<Item = &mut Item> {
+        self.item_list.items.iter_mut().map(|(item, _)| item)
+    }
+
     pub fn n_slots(&self) -> u32 {
         self.slots.len() as u32
     }
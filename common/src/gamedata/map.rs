@@ -3,10 +3,10 @@ use crate::gamedata::chara::{Chara, CharaId};
 use crate::gamedata::item::{Item, ItemList};
 use crate::gamedata::region::RegionId;
 use crate::gamedata::site::SiteId;
+use crate::hashmap::HashMap;
 use crate::objholder::*;
 use arrayvec::{ArrayString, ArrayVec};
 use geom::*;
-use std::collections::HashMap;
 use std::ops::{Index, IndexMut};
 
 pub use crate::piece_pattern::*;
@@ -29,6 +29,9 @@ pub struct Map {
     pub outside_tile: Option<OutsideTileInfo>,
     pub boundary: MapBoundary,
     pub music: String,
+    /// Id of the template this map is created from
+    #[serde(default)]
+    pub template_id: Option<String>,
 }
 
 pub type TileArray = ArrayVec<[TileIdxPP; N_TILE_IMG_LAYER]>;
@@ -99,6 +102,22 @@ pub enum SpecialTileKind {
     SiteSymbol {
         kind: SiteSymbolKind,
     },
+    /// Trap. Its effect is defined in SpecialTileObject of given id
+    Trap {
+        id: SpecialTileId,
+        /// Hidden traps are not drawn until found by searching
+        hidden: bool,
+    },
+    /// Door. Locked doors are not passable
+    Door {
+        /// Zero if the door is not locked
+        lock_level: u32,
+    },
+    /// Wall that becomes a passage after found by searching
+    HiddenPassage {
+        /// Difficulty to find this by searching
+        difficulty: u32,
+    },
 }
 
 impl SpecialTileKind {
@@ -108,6 +127,23 @@ impl SpecialTileKind {
             _ => false,
         }
     }
+
+    /// Characters cannot enter this tile
+    pub fn is_blocking(&self) -> bool {
+        match *self {
+            SpecialTileKind::Door { lock_level } => lock_level > 0,
+            _ => false,
+        }
+    }
+
+    /// This is not observable until found by searching
+    pub fn is_hidden(&self) -> bool {
+        match *self {
+            SpecialTileKind::Trap { hidden, .. } => hidden,
+            SpecialTileKind::HiddenPassage { .. } => true,
+            _ => false,
+        }
+    }
 }
 
 impl Default for SpecialTileKind {
@@ -131,6 +167,21 @@ impl From<&str> for SiteSymbolKind {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct SpecialTileId(ArrayString<[u8; crate::basic::ARRAY_STR_ID_LEN]>);
+
+impl From<&str> for SpecialTileId {
+    fn from(id: &str) -> SpecialTileId {
+        SpecialTileId(ArrayString::from(id).expect("too long special tile id"))
+    }
+}
+
+impl SpecialTileId {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
 impl SpecialTileKind {
     /// Convert to id of SpecialTileObject
     pub fn obj_id(&self) -> Option<&str> {
@@ -143,6 +194,17 @@ impl SpecialTileKind {
                 StairsKind::UpStairs => "!upstairs",
             },
             SpecialTileKind::SiteSymbol { ref kind } => kind.0.as_str(),
+            SpecialTileKind::Trap { ref id, .. } => id.0.as_str(),
+            SpecialTileKind::Door { lock_level } => {
+                if lock_level > 0 {
+                    "!locked-door"
+                } else {
+                    "!door"
+                }
+            }
+            SpecialTileKind::HiddenPassage { .. } => {
+                return None;
+            }
         })
     }
 }
@@ -164,6 +226,9 @@ pub struct TileInfo {
     pub item_list: ItemList,
     pub chara: Option<CharaId>,
     pub special: SpecialTileKind,
+    /// This tile is lit regardless of ambient light
+    #[serde(default)]
+    pub lit: bool,
 }
 
 /// The data for map drawing
@@ -217,6 +282,7 @@ impl Default for TileInfo {
             item_list: ItemList::new(),
             chara: None,
             special: SpecialTileKind::None,
+            lit: false,
         }
     }
 }
@@ -242,10 +308,11 @@ impl Map {
             player_pos: Vec2d(0, 0),
             entrance: ArrayVec::new(),
             charaid: Vec::new(),
-            charas: Some(HashMap::new()),
+            charas: Some(HashMap::default()),
             outside_tile: None,
             boundary: MapBoundary::default(),
             music: String::default(),
+            template_id: None,
         }
     }
 
@@ -3,6 +3,21 @@
 pub struct MetaData {
     /// Save directory name
     save_name: String,
+    /// Version of save data format. Save data without this field are version 0.
+    #[serde(default)]
+    format_version: u32,
+    /// Summary of the game shown in the save file list
+    #[serde(default)]
+    summary: SaveSummary,
+}
+
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct SaveSummary {
+    pub chara_name: String,
+    pub level: u32,
+    pub location: String,
+    /// Total play time in seconds
+    pub play_time: u64,
 }
 
 impl MetaData {
@@ -13,12 +28,30 @@ impl MetaData {
     pub fn set_save_name(&mut self, s: &str) {
         self.save_name = s.to_owned();
     }
+
+    pub fn format_version(&self) -> u32 {
+        self.format_version
+    }
+
+    pub fn set_format_version(&mut self, version: u32) {
+        self.format_version = version;
+    }
+
+    pub fn summary(&self) -> &SaveSummary {
+        &self.summary
+    }
+
+    pub fn summary_mut(&mut self) -> &mut SaveSummary {
+        &mut self.summary
+    }
 }
 
 impl Default for MetaData {
     fn default() -> MetaData {
         MetaData {
             save_name: "uninit".to_owned(),
+            format_version: crate::basic::SAVE_FORMAT_VERSION,
+            summary: SaveSummary::default(),
         }
     }
 }
@@ -52,7 +52,13 @@ pub struct GameData {
     pub vars: Variables,
     pub faction: Faction,
     pub learned_recipes: LearnedRecipes,
+    /// Random number generator states to reproduce the game after loading
+    #[serde(default)]
+    pub rng: rng::RngState,
     current_mapid: MapId,
+    /// Snapshot of item kinds identified by the player
+    #[serde(default)]
+    pub item_knowledge: ItemKnowledge,
 }
 
 impl GameData {
@@ -67,7 +73,9 @@ impl GameData {
             vars: Variables::new(),
             faction: Faction::new(),
             learned_recipes: LearnedRecipes::new(),
+            rng: rng::RngState::default(),
             current_mapid: MapId::default(),
+            item_knowledge: ItemKnowledge::default(),
         }
     }
 
@@ -107,6 +115,15 @@ impl GameData {
                 self.chara.add(CharaId::Player, chara);
                 CharaId::Player
             }
+            CharaKind::Party => {
+                let party = self.chara.party();
+                let n = (0..)
+                    .find(|n| !party.contains(&CharaId::Party { n: *n }))
+                    .unwrap();
+                let cid = CharaId::Party { n };
+                self.chara.add(cid, chara);
+                cid
+            }
             CharaKind::OnSite => panic!("Adding OnSite chara without id is unavailable"),
             CharaKind::OnMap => panic!("Adding OnMap chara without mapid is unavailable"),
         }
@@ -142,7 +159,7 @@ impl GameData {
             CharaId::Player => {
                 panic!();
             }
-            CharaId::OnMap { .. } => {
+            CharaId::OnMap { .. } | CharaId::Party { .. } => {
                 self.get_current_map_mut().remove_chara(cid);
                 self.chara.remove_chara(cid);
             }
@@ -170,6 +187,20 @@ impl GameData {
         self.chara.remove_chara(cid);
     }
 
+    /// Make the character on the current map a party member.
+    /// The character is given a new id, and the new id is returned.
+    pub fn add_party_member(&mut self, cid: CharaId) -> CharaId {
+        let pos = self
+            .get_current_map()
+            .chara_pos(cid)
+            .expect("party member must be on the current map");
+        let chara = self.chara.get(cid).clone();
+        self.remove_chara(cid);
+        let new_cid = self.add_chara(chara, CharaKind::Party);
+        self.get_current_map_mut().locate_chara(new_cid, pos);
+        new_cid
+    }
+
     pub fn add_site(
         &mut self,
         site: Site,
@@ -187,7 +218,16 @@ impl GameData {
         MapId::SiteMap { sid, floor }
     }
 
+    /// Switch the current map.
+    /// Party members are removed from the previous map, and need to be located on the next map.
     pub fn set_current_mapid(&mut self, mid: MapId) {
+        for cid in self.chara.party() {
+            let map = self.get_current_map_mut();
+            if map.chara_pos(cid).is_some() {
+                map.remove_chara(cid);
+            }
+        }
+
         // OnMap characters on the next map
         let next_charas = self
             .region
@@ -1,5 +1,8 @@
+use super::chara::CharaId;
 use super::defs::Reward;
-use crate::objholder::CharaTemplateIdx;
+use super::site::SiteId;
+use super::time::Time;
+use crate::objholder::{CharaTemplateIdx, ItemIdx};
 use std::slice::{Iter, IterMut};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -7,16 +10,22 @@ pub enum QuestState {
     Active,
     Completed,
     RewardReceived,
+    Failed,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QuestHolder {
     quests: Vec<(QuestState, Quest)>,
+    #[serde(default)]
+    story_quests: Vec<StoryQuest>,
 }
 
 impl QuestHolder {
     pub fn new() -> QuestHolder {
-        QuestHolder { quests: Vec::new() }
+        QuestHolder {
+            quests: Vec::new(),
+            story_quests: Vec::new(),
+        }
     }
 
     pub fn iter(&self) -> Iter<(QuestState, Quest)> {
@@ -31,26 +40,177 @@ impl QuestHolder {
         self.quests.push((QuestState::Active, quest));
     }
 
+    /// Remove quests that rewards are received or failed
     pub fn remove_reward_received(&mut self) {
-        self.quests
-            .retain(|&(state, _)| state != QuestState::RewardReceived);
+        self.quests.retain(|&(state, _)| {
+            state != QuestState::RewardReceived && state != QuestState::Failed
+        });
+    }
+
+    pub fn story_quests(&self) -> Iter<StoryQuest> {
+        self.story_quests.iter()
+    }
+
+    pub fn story_quest(&self, id: &str) -> Option<&StoryQuest> {
+        self.story_quests.iter().find(|quest| quest.id == id)
+    }
+
+    /// Move the story quest to given stage. The quest is started if it is not started yet.
+    /// Returns false if the quest is already finished or at the stage.
+    pub fn set_story_quest_stage(&mut self, id: &str, stage: &str, time: Time) -> bool {
+        let i = if let Some(i) = self.story_quests.iter().position(|quest| quest.id == id) {
+            i
+        } else {
+            self.story_quests.push(StoryQuest {
+                id: id.to_owned(),
+                state: QuestState::Active,
+                stages: Vec::new(),
+            });
+            self.story_quests.len() - 1
+        };
+        let quest = &mut self.story_quests[i];
+
+        if quest.state != QuestState::Active || quest.current_stage() == Some(stage) {
+            return false;
+        }
+        quest.stages.push((stage.to_owned(), time));
+        true
+    }
+
+    /// Finish the active story quest with given state.
+    /// Returns false if the quest is not active.
+    pub fn finish_story_quest(&mut self, id: &str, state: QuestState) -> bool {
+        match self.story_quests.iter_mut().find(|quest| quest.id == id) {
+            Some(quest) if quest.state == QuestState::Active => {
+                quest.state = state;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Progress of a story quest defined by StoryQuestObject
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct StoryQuest {
+    pub id: String,
+    pub state: QuestState,
+    /// Reached stage ids and the time. The last one is the current stage.
+    pub stages: Vec<(String, Time)>,
+}
+
+impl StoryQuest {
+    pub fn current_stage(&self) -> Option<&str> {
+        self.stages.last().map(|(stage, _)| stage.as_str())
     }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Quest {
+    /// Slay the given number of monsters
     SlayMonsters {
         reward: Reward,
         idx: CharaTemplateIdx,
         goal: u32,
         killed: u32,
+        #[serde(default)]
+        deadline: Option<Time>,
+    },
+    /// Carry an item to the destination town
+    Deliver {
+        reward: Reward,
+        deadline: Option<Time>,
+        idx: ItemIdx,
+        destination: SiteId,
+    },
+    /// Bring items to the client
+    Fetch {
+        reward: Reward,
+        deadline: Option<Time>,
+        idx: ItemIdx,
+        goal: u32,
+    },
+    /// Take a character to the destination town as a party member.
+    /// cid is given when the quest is undertaken.
+    Escort {
+        reward: Reward,
+        deadline: Option<Time>,
+        idx: CharaTemplateIdx,
+        cid: Option<CharaId>,
+        destination: SiteId,
+    },
+    /// Reach the floor of the dungeon
+    ExploreFloor {
+        reward: Reward,
+        deadline: Option<Time>,
+        sid: SiteId,
+        floor: u32,
+    },
+    /// Create items
+    Craft {
+        reward: Reward,
+        deadline: Option<Time>,
+        idx: ItemIdx,
+        goal: u32,
+        crafted: u32,
     },
 }
 
 impl Quest {
     pub fn reward(&self) -> &Reward {
         match self {
-            Quest::SlayMonsters { reward, .. } => reward,
+            Quest::SlayMonsters { reward, .. }
+            | Quest::Deliver { reward, .. }
+            | Quest::Fetch { reward, .. }
+            | Quest::Escort { reward, .. }
+            | Quest::ExploreFloor { reward, .. }
+            | Quest::Craft { reward, .. } => reward,
         }
     }
+
+    /// The quest fails if it is not completed until the deadline
+    pub fn deadline(&self) -> Option<Time> {
+        match self {
+            Quest::SlayMonsters { deadline, .. }
+            | Quest::Deliver { deadline, .. }
+            | Quest::Fetch { deadline, .. }
+            | Quest::Escort { deadline, .. }
+            | Quest::ExploreFloor { deadline, .. }
+            | Quest::Craft { deadline, .. } => *deadline,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn story_quest_stages() {
+        let mut quests = QuestHolder::new();
+        let t = Time::default();
+        assert!(quests.story_quest("main").is_none());
+        assert!(quests.set_story_quest_stage("main", "start", t));
+        assert!(!quests.set_story_quest_stage("main", "start", t));
+        assert!(quests.set_story_quest_stage("main", "cave", t));
+
+        let quest = quests.story_quest("main").unwrap();
+        assert_eq!(quest.state, QuestState::Active);
+        assert_eq!(quest.current_stage(), Some("cave"));
+        assert_eq!(quest.stages.len(), 2);
+
+        assert!(!quests.finish_story_quest("other", QuestState::Completed));
+        assert!(quests.finish_story_quest("main", QuestState::Completed));
+        assert!(!quests.finish_story_quest("main", QuestState::Failed));
+        // Finished quests do not move to other stages
+        assert!(!quests.set_story_quest_stage("main", "end", t));
+        assert_eq!(
+            quests.story_quest("main").unwrap().state,
+            QuestState::Completed
+        );
+
+        // Story quests are not removed with town quests
+        quests.remove_reward_received();
+        assert_eq!(quests.story_quests().count(), 1);
+    }
 }
@@ -1,9 +1,9 @@
 use super::map::*;
 use super::site::*;
 use super::unknown_id_err;
+use crate::hashmap::HashMap;
 use filebox::FileBox;
 use geom::*;
-use std::collections::HashMap;
 use std::path::Path;
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
@@ -37,7 +37,7 @@ impl Default for RegionId {
 
 impl RegionHolder {
     pub fn new() -> RegionHolder {
-        RegionHolder(HashMap::new())
+        RegionHolder(HashMap::default())
     }
 
     pub fn get(&self, rid: RegionId) -> &Region {
@@ -275,7 +275,7 @@ impl Region {
         Region {
             name: name.to_owned(),
             id: RegionId(0),
-            sites: HashMap::new(),
+            sites: HashMap::default(),
             map: FileBox::new(map_random_id, map),
         }
     }
@@ -300,6 +300,18 @@ impl Region {
         self.sites.keys().filter(|&sid| sid.kind == kind).count() as u32
     }
 
+    /// Get ids of sites of given kind, in ascending order
+    pub fn get_site_ids(&self, kind: SiteKind) -> Vec<SiteId> {
+        let mut sids: Vec<SiteId> = self
+            .sites
+            .keys()
+            .filter(|sid| sid.kind == kind)
+            .copied()
+            .collect();
+        sids.sort_by_key(|sid| sid.n);
+        sids
+    }
+
     /// Get site by position on the region
     pub fn get_id_by_pos(&self, pos: Vec2d) -> Option<SiteId> {
         for (sid, sinfo) in self.sites.iter() {
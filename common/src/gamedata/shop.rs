@@ -19,4 +19,7 @@ pub struct Shop {
     pub items: ItemList,
     /// Shop level is used to choose shop items
     pub level: u32,
+    /// Money the shopkeeper can pay for items the player sells
+    #[serde(default)]
+    pub money: i64,
 }
@@ -18,6 +18,11 @@ pub enum SkillKind {
     Mining,
     Weapon(WeaponKind),
     Creation(CreationKind),
+    Lockpicking,
+    Searching,
+    Stealth,
+    Trading,
+    Appraisal,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Error)]
@@ -37,6 +42,11 @@ impl FromStr for SkillKind {
             "healing" => SkillKind::Healing,
             "magic_device" => SkillKind::MagicDevice,
             "mining" => SkillKind::Mining,
+            "lockpicking" => SkillKind::Lockpicking,
+            "searching" => SkillKind::Searching,
+            "stealth" => SkillKind::Stealth,
+            "trading" => SkillKind::Trading,
+            "appraisal" => SkillKind::Appraisal,
             "sword" => SkillKind::Weapon(WeaponKind::Sword),
             "spear" => SkillKind::Weapon(WeaponKind::Spear),
             "axe" => SkillKind::Weapon(WeaponKind::Axe),
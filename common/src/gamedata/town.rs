@@ -1,13 +1,24 @@
+use crate::gamedata::item::ItemKindRough;
 use crate::gamedata::quest::Quest;
 use crate::gamedata::shop::*;
+use crate::gamedata::time::Time;
 use fnv::FnvHashMap;
-use std::collections::hash_map::{Values, ValuesMut};
+use std::collections::hash_map::{IterMut, Values};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Town {
     id: String,
     shops: FnvHashMap<u32, Shop>,
     pub quests: Vec<Quest>,
+    /// Grows by trades and quests, and raises shop levels
+    #[serde(default)]
+    pub development: u32,
+    /// Items the player sold recently by kind. Higher supply lowers prices.
+    #[serde(default)]
+    pub supply: FnvHashMap<ItemKindRough, u32>,
+    /// The last time shops are restocked
+    #[serde(default)]
+    pub last_restock: Time,
 }
 
 impl Town {
@@ -16,6 +27,9 @@ impl Town {
             id: id.to_owned(),
             shops: FnvHashMap::default(),
             quests: Vec::new(),
+            development: 0,
+            supply: FnvHashMap::default(),
+            last_restock: Time::default(),
         }
     }
 
@@ -35,8 +49,13 @@ impl Town {
         self.shops.values()
     }
 
-    pub fn iter_shops_mut(&mut self) -> ValuesMut<u32, Shop> {
-        self.shops.values_mut()
+    /// Iterate shops with their shopkeeper's number
+    pub fn iter_shops_mut(&mut self) -> IterMut<u32, Shop> {
+        self.shops.iter_mut()
+    }
+
+    pub fn supply(&self, kind: ItemKindRough) -> u32 {
+        self.supply.get(&kind).copied().unwrap_or(0)
     }
 
     pub fn add_shop(&mut self, shop: Shop, n: u32) {
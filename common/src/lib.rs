@@ -21,6 +21,7 @@ pub mod gamedata;
 pub mod gobj;
 pub mod impl_filebox;
 pub mod maptemplate;
+pub mod migration;
 pub mod objholder;
 pub mod pakutil;
 pub mod piece_pattern;
@@ -28,3 +29,4 @@ pub mod regiongen;
 pub mod saveload;
 pub mod script;
 pub mod sitegen;
+pub mod storyquest;
@@ -1,10 +1,11 @@
 use crate::basic::N_TILE_IMG_LAYER;
 #[cfg(feature = "global_state_obj")]
 use crate::gamedata::map::TileLayers;
-use crate::gamedata::ItemGen;
+use crate::gamedata::{ItemGen, NpcGen};
 #[cfg(feature = "global_state_obj")]
 use crate::objholder::ObjectIndex;
 use crate::piece_pattern::*;
+use crate::script::ScriptHook;
 use arrayvec::ArrayVec;
 use geom::*;
 use std::ops::{Index, IndexMut};
@@ -29,6 +30,12 @@ pub struct MapTemplateObject {
     pub items: Vec<(Vec2d, ItemGen)>,
     #[serde(default)]
     pub music: String,
+    /// NPCs spawned when this template is used as a prefab of dungeon floors
+    #[serde(default)]
+    pub npcs: Vec<(Vec2d, NpcGen)>,
+    /// Scripts triggered on maps created from this template
+    #[serde(default)]
+    pub hooks: Vec<ScriptHook>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
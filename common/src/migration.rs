@@ -0,0 +1,255 @@
+//! Migration of save data written by older versions.
+//!
+//! Save files are decoded into an intermediate CBOR value tree, and registered migrations
+//! are applied in order until the data reaches `SAVE_FORMAT_VERSION`.
+//! Object id reordering is handled separately by `IdxConvTable` when loading.
+
+use crate::basic::SAVE_FORMAT_VERSION;
+use crate::gamedata::MetaData;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_cbor::Value;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Conversion of save data from version `from` to `from + 1`
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    /// Converts `gamedata` file
+    pub gamedata: fn(&mut Value) -> Result<(), MigrationError>,
+    /// Converts each map file in `maps` directory
+    pub map: fn(&mut Value) -> Result<(), MigrationError>,
+}
+
+/// Registered migrations. Must be sorted by `from`, and cover all versions
+/// older than `SAVE_FORMAT_VERSION`.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "insert rng state before current_mapid in GameData",
+    gamedata: v0_gamedata,
+    map: no_change,
+}];
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+    #[error("cbor error")]
+    Cbor(#[from] serde_cbor::Error),
+    #[error("json error")]
+    Json(#[from] serde_json::Error),
+    #[error("unsupported save format version {0}")]
+    UnsupportedVersion(u32),
+    #[error("invalid save data: {0}")]
+    InvalidData(&'static str),
+}
+
+/// Save data loaded as value trees
+pub struct SaveValues {
+    pub gamedata: Value,
+    pub maps: Vec<(PathBuf, Value)>,
+}
+
+/// Apply migrations to save data of the given version.
+pub fn migrate(values: &mut SaveValues, version: u32) -> Result<(), MigrationError> {
+    if version > SAVE_FORMAT_VERSION {
+        return Err(MigrationError::UnsupportedVersion(version));
+    }
+
+    for v in version..SAVE_FORMAT_VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.from == v)
+            .ok_or(MigrationError::UnsupportedVersion(v))?;
+        info!(
+            "Migrating save data from version {}: {}",
+            v, migration.description
+        );
+        (migration.gamedata)(&mut values.gamedata)?;
+        for (_, map) in values.maps.iter_mut() {
+            (migration.map)(map)?;
+        }
+    }
+    Ok(())
+}
+
+/// Upgrade the save directory to the current format version.
+/// Returns false if the save data is already up to date.
+pub fn upgrade_save_dir<P: AsRef<Path>>(path: P) -> Result<bool, MigrationError> {
+    let save_dir = path.as_ref();
+
+    let mut meta: MetaData =
+        serde_json::from_reader(BufReader::new(File::open(save_dir.join("metadata"))?))?;
+    let version = meta.format_version();
+    if version == SAVE_FORMAT_VERSION {
+        return Ok(false);
+    }
+
+    let gamedata = serde_cbor::from_reader(BufReader::new(File::open(save_dir.join("gamedata"))?))?;
+    let mut maps = Vec::new();
+    let map_dir = save_dir.join("maps");
+    if map_dir.exists() {
+        for entry in fs::read_dir(&map_dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let file = GzDecoder::new(BufReader::new(File::open(&path)?));
+            let map = serde_cbor::from_reader(file)?;
+            maps.push((path, map));
+        }
+    }
+
+    let mut values = SaveValues { gamedata, maps };
+    migrate(&mut values, version)?;
+
+    // Write all files to temporary files first, and replace metadata at last
+    let mut written = Vec::new();
+    for (path, map) in &values.maps {
+        let tmp = tmp_path(path);
+        let mut file = GzEncoder::new(BufWriter::new(File::create(&tmp)?), Compression::fast());
+        serde_cbor::to_writer(&mut file, map)?;
+        file.finish()?.flush()?;
+        written.push((tmp, path.clone()));
+    }
+
+    let path = save_dir.join("gamedata");
+    let tmp = tmp_path(&path);
+    let mut file = BufWriter::new(File::create(&tmp)?);
+    serde_cbor::to_writer(&mut file, &values.gamedata)?;
+    file.flush()?;
+    written.push((tmp, path));
+
+    let path = save_dir.join("metadata");
+    let tmp = tmp_path(&path);
+    meta.set_format_version(SAVE_FORMAT_VERSION);
+    let mut file = BufWriter::new(File::create(&tmp)?);
+    serde_json::to_writer_pretty(&mut file, &meta)?;
+    file.flush()?;
+    written.push((tmp, path));
+
+    for (tmp, path) in &written {
+        fs::rename(tmp, path)?;
+    }
+
+    Ok(true)
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut s = path.as_os_str().to_owned();
+    s.push(".tmp");
+    PathBuf::from(s)
+}
+
+fn no_change(_: &mut Value) -> Result<(), MigrationError> {
+    Ok(())
+}
+
+/// `GameData::rng` was inserted before `current_mapid`.
+/// In packed format, fields are keyed by their indices, so the index of `current_mapid` is shifted.
+fn v0_gamedata(gamedata: &mut Value) -> Result<(), MigrationError> {
+    let fields = if let Value::Map(fields) = gamedata {
+        fields
+    } else {
+        return Err(MigrationError::InvalidData("gamedata is not a map"));
+    };
+
+    // Data saved in named field mode does not need conversion
+    if fields.contains_key(&Value::Text("current_mapid".into())) {
+        return Ok(());
+    }
+
+    let current_mapid = fields
+        .remove(&Value::Integer(8))
+        .ok_or(MigrationError::InvalidData("current_mapid not found"))?;
+    fields.insert(Value::Integer(9), current_mapid);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "global_state_obj"))]
+mod tests {
+    use super::*;
+    use crate::gamedata::*;
+
+    const FIXTURE_V0: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/save_v0");
+
+    fn copy_dir(src: &Path, dest: &Path) {
+        fs::create_dir_all(dest).unwrap();
+        for entry in fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            let dest = dest.join(path.file_name().unwrap());
+            if path.is_dir() {
+                copy_dir(&path, &dest);
+            } else {
+                fs::copy(&path, &dest).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn upgrade_v0_save() {
+        let save_dir = std::env::temp_dir().join(format!(
+            "rusted-ruins-migration-test-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&save_dir);
+        copy_dir(Path::new(FIXTURE_V0), &save_dir);
+
+        let read_gamedata = || -> Result<GameData, serde_cbor::Error> {
+            serde_cbor::from_reader(BufReader::new(
+                File::open(save_dir.join("gamedata")).unwrap(),
+            ))
+        };
+        assert!(read_gamedata().is_err());
+
+        assert!(upgrade_save_dir(&save_dir).unwrap());
+
+        let meta: MetaData =
+            serde_json::from_reader(File::open(save_dir.join("metadata")).unwrap()).unwrap();
+        assert_eq!(meta.save_name(), "fixture");
+        assert_eq!(meta.format_version(), SAVE_FORMAT_VERSION);
+
+        let gd = read_gamedata().unwrap();
+        assert_eq!(
+            gd.get_current_mapid(),
+            MapId::RegionMap {
+                rid: RegionId::default()
+            }
+        );
+        assert_eq!(gd.player.money(), 100);
+        // Skill kinds are stored by variant indices
+        let skills = &gd.chara.get(CharaId::Player).skills;
+        assert_eq!(skills.get(SkillKind::Mining), 3);
+        assert_eq!(skills.get(SkillKind::Weapon(WeaponKind::Sword)), 5);
+        assert_eq!(skills.get(SkillKind::Weapon(WeaponKind::Bow)), 2);
+        assert_eq!(skills.get(SkillKind::Creation(CreationKind::Smith)), 4);
+
+        let map_file = save_dir.join("maps/0000000000001234");
+        let map: Map = serde_cbor::from_reader(GzDecoder::new(BufReader::new(
+            File::open(map_file).unwrap(),
+        )))
+        .unwrap();
+        assert_eq!(map.w, 4);
+
+        // Already upgraded
+        assert!(!upgrade_save_dir(&save_dir).unwrap());
+
+        fs::remove_dir_all(&save_dir).unwrap();
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut values = SaveValues {
+            gamedata: Value::Null,
+            maps: Vec::new(),
+        };
+        assert!(matches!(
+            migrate(&mut values, SAVE_FORMAT_VERSION + 1),
+            Err(MigrationError::UnsupportedVersion(_))
+        ));
+    }
+}
@@ -26,6 +26,7 @@ pub enum Object {
     RegionGen(RegionGenObject),
     Script(ScriptObject),
     SiteGen(SiteGenObject),
+    StoryQuest(StoryQuestObject),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -51,6 +52,18 @@ pub struct SpecialTileObject {
     pub id: String,
     pub always_background: bool,
     pub img: Img,
+    /// Parameters for trap tiles
+    #[serde(default)]
+    pub trap: Option<TrapParams>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrapParams {
+    /// Effect to the character stepping on the trap
+    pub effect: gamedata::Effect,
+    pub power: f32,
+    /// Difficulty to find the trap by searching
+    pub difficulty: u32,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -136,6 +149,7 @@ pub use crate::maptemplate::MapTemplateObject;
 pub use crate::regiongen::RegionGenObject;
 pub use crate::script::ScriptObject;
 pub use crate::sitegen::SiteGenObject;
+pub use crate::storyquest::StoryQuestObject;
 
 macro_rules! impl_object {
     ( $($i:ty),* ) => {
@@ -162,7 +176,8 @@ impl_object!(
     MapTemplateObject,
     RegionGenObject,
     SiteGenObject,
-    ScriptObject
+    ScriptObject,
+    StoryQuestObject
 );
 
 impl Object {
@@ -181,6 +196,7 @@ impl Object {
             Object::RegionGen(ref o) => &o.id,
             Object::Script(ref o) => &o.id,
             Object::SiteGen(ref o) => &o.id,
+            Object::StoryQuest(ref o) => &o.id,
         }
     }
 }
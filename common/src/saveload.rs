@@ -1,33 +1,42 @@
-use crate::basic::SAVE_EXTENSION;
+use crate::basic::{SAVE_EXTENSION, SAVE_FORMAT_VERSION};
 use crate::gamedata::*;
-use crate::impl_filebox::MapLoadError;
 use crate::utils::to_writer_with_mode;
 use serde_cbor::from_reader;
+use std::error::Error;
+use std::ffi::OsString;
 use std::fs::{self, create_dir_all, File};
 use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 
 #[cfg(feature = "global_state_obj")]
 impl GameData {
-    /// Save game data to the specified directory
-    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+    /// Save game data to the specified directory.
+    /// Maps not loaded on memory are copied from `map_src_dir`.
+    pub fn save<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        path: P,
+        map_src_dir: Q,
+    ) -> Result<(), Box<dyn Error>> {
         if cfg!(debug_assertions) {
             print_save_data_size(self); // Debug code for save file size optimization
         }
 
-        let save_dir = path.as_ref();
-
-        // Create directory
-        create_dir_all(&save_dir)?;
+        replace_save_dir(path, |save_dir| {
+            self.write_save_files(save_dir, map_src_dir.as_ref())
+        })
+    }
 
+    fn write_save_files(&self, save_dir: &Path, map_src_dir: &Path) -> Result<(), Box<dyn Error>> {
         // Write id table file
         let mut file = BufWriter::new(File::create(save_dir.join("idtable"))?);
         writeln!(file, "{:016x}", *crate::gobj::OBJ_HOLDER_HASH)?;
         crate::gobj::get_objholder().write_table(&mut file)?;
 
         // Write metadata file
+        let mut meta = self.meta.clone();
+        meta.set_format_version(SAVE_FORMAT_VERSION);
         let mut file = BufWriter::new(File::create(save_dir.join("metadata"))?);
-        serde_json::to_writer_pretty(&mut file, &self.meta)?;
+        serde_json::to_writer_pretty(&mut file, &meta)?;
 
         // Write GameData
         let mut file = BufWriter::new(File::create(save_dir.join("gamedata"))?);
@@ -37,23 +46,39 @@ impl GameData {
         let map_dir = save_dir.join("maps");
         create_dir_all(&map_dir)?;
 
-        let mut errors: Vec<MapLoadError> = Vec::new();
-        self.region
-            .visit_all_maps(|_mid, map| match BoxedMap::write(map, &map_dir) {
-                Ok(_) => (),
-                Err(e) => errors.push(e),
-            });
+        let mut errors: Vec<Box<dyn Error>> = Vec::new();
+        self.region.visit_all_maps(|_mid, map| {
+            let result = if map.is_loaded() {
+                BoxedMap::write_force(map, &map_dir).map_err(|e| e.into())
+            } else {
+                fs::copy(map.path(map_src_dir), map.path(&map_dir))
+                    .map(|_| ())
+                    .map_err(|e| e.into())
+            };
+            if let Err(e) = result {
+                errors.push(e);
+            }
+        });
 
         if !errors.is_empty() {
-            return Err(errors.into_iter().next().unwrap().into());
+            return Err(errors.into_iter().next().unwrap());
         }
 
         Ok(())
     }
 
     /// Load game data from specified directory
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<GameData, Box<dyn std::error::Error>> {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GameData, Box<dyn Error>> {
         let save_dir = path.as_ref();
+        recover_save_dir(save_dir)?;
+
+        // Convert save data written by older versions
+        if crate::migration::upgrade_save_dir(save_dir)? {
+            info!(
+                "Save data is upgraded to format version {}",
+                SAVE_FORMAT_VERSION
+            );
+        }
 
         // Read metadata file
         let mut file = BufReader::new(File::open(save_dir.join("metadata"))?);
@@ -93,7 +118,7 @@ impl GameData {
         Ok(gamedata)
     }
 
-    pub fn clean_map_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn clean_map_dir<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
         let map_dir = path.as_ref().join("maps");
 
         let mut map_files: Vec<PathBuf> = Vec::new();
@@ -131,6 +156,116 @@ impl GameData {
     }
 }
 
+impl MetaData {
+    /// Read metadata file in the save directory
+    pub fn load<P: AsRef<Path>>(save_dir: P) -> Result<MetaData, Box<dyn Error>> {
+        let file = BufReader::new(File::open(save_dir.as_ref().join("metadata"))?);
+        Ok(serde_json::from_reader(file)?)
+    }
+}
+
+/// Write save files to a temporary directory by `f`, and then replace the directory at `path`.
+/// If the process is interrupted, the previous directory is left as `*.old`,
+/// and it is recovered by `recover_save_dir()`.
+pub fn replace_save_dir<P, F>(path: P, f: F) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&Path) -> Result<(), Box<dyn Error>>,
+{
+    let path = path.as_ref();
+    let tmp_dir = with_suffix(path, "tmp");
+    let old_dir = with_suffix(path, "old");
+    recover_save_dir(path)?;
+
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+    create_dir_all(&tmp_dir)?;
+    if let Err(e) = f(&tmp_dir) {
+        let _ = fs::remove_dir_all(&tmp_dir);
+        return Err(e);
+    }
+
+    if path.exists() {
+        fs::rename(path, &old_dir)?;
+    }
+    fs::rename(&tmp_dir, path)?;
+    fs::remove_dir_all(&old_dir).or_else(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            Ok(())
+        } else {
+            Err(e)
+        }
+    })?;
+    Ok(())
+}
+
+/// Restore the previous save directory if replacing is interrupted.
+pub fn recover_save_dir<P: AsRef<Path>>(path: P) -> Result<(), std::io::Error> {
+    let path = path.as_ref();
+    let old_dir = with_suffix(path, "old");
+    if !old_dir.exists() {
+        return Ok(());
+    }
+
+    if path.exists() {
+        // Interrupted after the new directory is renamed
+        fs::remove_dir_all(&old_dir)
+    } else {
+        warn!("Recover interrupted save data {:?}", path.to_string_lossy());
+        fs::rename(&old_dir, path)
+    }
+}
+
+/// Copy the save directory. The destination directory is replaced.
+pub fn copy_save_dir<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dest: Q,
+) -> Result<(), Box<dyn Error>> {
+    let src = src.as_ref();
+    replace_save_dir(dest, |tmp_dir| Ok(copy_dir(src, tmp_dir)?))
+}
+
+/// Copy the save directory to `dest`, keeping the existing `dest` as `*.bak`.
+/// Returns the backup path if `dest` existed.
+pub fn restore_save_dir<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dest: Q,
+) -> Result<Option<PathBuf>, Box<dyn Error>> {
+    let dest = dest.as_ref();
+    recover_save_dir(dest)?;
+    let backup = if dest.exists() {
+        let backup = with_suffix(dest, "bak");
+        copy_save_dir(dest, &backup)?;
+        Some(backup)
+    } else {
+        None
+    };
+    copy_save_dir(src, dest)?;
+    Ok(backup)
+}
+
+fn copy_dir(src: &Path, dest: &Path) -> Result<(), std::io::Error> {
+    create_dir_all(dest)?;
+    for entry in fs::read_dir(src)? {
+        let path = entry?.path();
+        let dest = dest.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &dest)?;
+        } else {
+            fs::copy(&path, &dest)?;
+        }
+    }
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut s: OsString = path.as_os_str().to_owned();
+    s.push(".");
+    s.push(suffix);
+    s.into()
+}
+
 /// Print save data size
 #[cfg(debug_assertions)]
 fn print_save_data_size(gd: &GameData) {
@@ -150,3 +285,49 @@ fn print_save_data_size(gd: &GameData) {
 
 #[cfg(not(debug_assertions))]
 fn print_save_data_size(_gd: &GameData) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_and_recover_save_dir() {
+        let dir =
+            std::env::temp_dir().join(format!("rusted-ruins-saveload-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("test.rrsve");
+        let read = |path: &Path| fs::read_to_string(path.join("gamedata")).unwrap();
+
+        replace_save_dir(&path, |tmp| Ok(fs::write(tmp.join("gamedata"), "1")?)).unwrap();
+        assert_eq!(read(&path), "1");
+
+        // Failed writing keeps the previous data
+        assert!(replace_save_dir(&path, |_| Err("error".into())).is_err());
+        assert_eq!(read(&path), "1");
+        assert!(!with_suffix(&path, "tmp").exists());
+
+        replace_save_dir(&path, |tmp| Ok(fs::write(tmp.join("gamedata"), "2")?)).unwrap();
+        assert_eq!(read(&path), "2");
+        assert!(!with_suffix(&path, "old").exists());
+
+        // Interrupted after the previous directory is moved
+        fs::rename(&path, with_suffix(&path, "old")).unwrap();
+        recover_save_dir(&path).unwrap();
+        assert_eq!(read(&path), "2");
+
+        let copied = dir.join("copied.rrsve");
+        copy_save_dir(&path, &copied).unwrap();
+        assert_eq!(read(&copied), "2");
+
+        // Restoring keeps the overwritten directory as a backup
+        replace_save_dir(&path, |tmp| Ok(fs::write(tmp.join("gamedata"), "3")?)).unwrap();
+        let backup = restore_save_dir(&copied, &path).unwrap().unwrap();
+        assert_eq!(read(&path), "2");
+        assert_eq!(read(&backup), "3");
+        let restored = dir.join("restored.rrsve");
+        assert_eq!(restore_save_dir(&copied, &restored).unwrap(), None);
+        assert_eq!(read(&restored), "2");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
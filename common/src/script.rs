@@ -1,5 +1,6 @@
-use crate::gamedata::Time;
+use crate::gamedata::{CharaAttrKind, FactionId, QuestState, SkillKind, Time};
 use crate::hashmap::HashMap;
+use geom::Vec2d;
 use std::fmt;
 use std::ops::Index;
 
@@ -14,16 +15,50 @@ pub enum Instruction {
     Talk(String, Vec<(String, String)>),
     /// Set global variable
     GSet(String, Expr),
+    /// Set local variable
+    Set(String, Expr),
+    /// Call given section as a subroutine with arguments
+    Call(String, Vec<Expr>),
+    /// Return from the subroutine. The value is stored to the last result
+    Return(Option<Expr>),
+    /// Loop while given expr is true. Jump to the index if it is false
+    While(Expr, usize),
+    /// The end of a while loop. Jump back to the index of the while instruction
+    EndWhile(usize),
     /// Player receive item
     ReceiveItem(String, Expr),
     /// Player receive money
     ReceiveMoney(Expr),
     /// Remove item form player's inventory
     RemoveItem(String),
+    /// Change the relation between the player and given faction
+    ChangeFactionRelation(FactionId, Expr),
+    /// Player learns given skill. The last result is false if already learned
+    LearnSkill(SkillKind),
+    /// Player gains exp of given skill
+    GainSkillExp(SkillKind, Expr),
+    /// Change player's attribute
+    AddAttr(CharaAttrKind, Expr),
+    /// Change the character's HP. HP does not become lower than 1
+    AddHp(CharaRef, Expr),
+    /// Spawn a character from given template id and faction near the player
+    SpawnChara(String, FactionId),
+    /// Move the player to given map path "region/site/floor:x,y"
+    Teleport(String),
+    /// Change the talking character's faction, and make it attack the player
+    StartCombat(FactionId),
     /// Special Instruction
     Special(SpecialInstruction),
     /// Debug print
     Print(Expr),
+    /// Move the story quest to given stage. Starts the quest if not started
+    SetQuestStage(String, String),
+    /// Complete the story quest
+    CompleteQuest(String),
+    /// Fail the story quest
+    FailQuest(String),
+    /// Fail all active quests except story quests
+    FailQuests,
 }
 
 /// Special Instructions
@@ -39,6 +74,8 @@ pub enum SpecialInstruction {
     QuestWindow,
     /// Special instruction to receive quest rewards
     ReceiveQuestRewards,
+    /// Special instruction to make the talking character a party member
+    Recruit,
 }
 
 impl std::str::FromStr for SpecialInstruction {
@@ -52,6 +89,7 @@ impl std::str::FromStr for SpecialInstruction {
             "get_dungeon_location" => Ok(GetDungeonLocation),
             "quest_window" => Ok(QuestWindow),
             "receive_quest_rewards" => Ok(ReceiveQuestRewards),
+            "recruit" => Ok(Recruit),
             _ => Err(()),
         }
     }
@@ -64,10 +102,50 @@ pub enum Expr {
     Term(Vec<(Operator, Expr)>),
     /// Reference to global variable
     GVar(String),
+    /// Reference to local variable
+    LVar(String),
     IsGVarEmpty(String),
     CurrentTime,
     DurationHour(Box<Expr>, Box<Expr>),
     HasItem(String),
+    /// Player's attribute
+    PlayerAttr(CharaAttrKind),
+    /// Player's skill level
+    SkillLevel(SkillKind),
+    /// Relation between the player and given faction
+    FactionRelation(FactionId),
+    /// The number of player's quests in given state
+    QuestCount(QuestState),
+    /// Whether the current map belongs to the site of given id
+    InSite(String),
+    /// Floor of the current map. -1 on region maps
+    CurrentFloor,
+    Hp(CharaRef),
+    MaxHp(CharaRef),
+    /// Whether the current stage of given story quest is given stage
+    QuestStage(String, String),
+    /// Whether given story quest is in given state. Not started quests are in no state
+    QuestState(String, QuestState),
+}
+
+/// Character referred in scripts
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum CharaRef {
+    Player,
+    /// The character talking with the player
+    Talker,
+}
+
+impl std::str::FromStr for CharaRef {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "player" => Ok(CharaRef::Player),
+            "talker" => Ok(CharaRef::Talker),
+            _ => Err(()),
+        }
+    }
 }
 
 /// Value is the result of evaluation of Expr.
@@ -108,15 +186,35 @@ pub enum ExprErrorKind {
 /// Script consists of one or more sections.
 /// One section includes one or more instructions.
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
-pub struct Script(HashMap<String, Vec<Instruction>>);
+pub struct Script {
+    sections: HashMap<String, Vec<Instruction>>,
+    /// Parameter names of sections called as subroutines
+    params: HashMap<String, Vec<String>>,
+}
 
 impl Script {
     pub fn from_map(map: HashMap<String, Vec<Instruction>>) -> Script {
-        Script(map)
+        Script {
+            sections: map,
+            params: HashMap::default(),
+        }
+    }
+
+    pub fn set_params(&mut self, section: &str, params: Vec<String>) {
+        self.params.insert(section.to_owned(), params);
+    }
+
+    /// Parameter names of given section
+    pub fn params(&self, section: &str) -> &[String] {
+        self.params.get(section).map(|p| p.as_ref()).unwrap_or(&[])
+    }
+
+    pub fn has_section(&self, section: &str) -> bool {
+        self.sections.contains_key(section)
     }
 
     pub fn get(&self, pos: &ScriptPos) -> Option<&Instruction> {
-        if let Some(v) = self.0.get(&pos.section) {
+        if let Some(v) = self.sections.get(&pos.section) {
             v.get(pos.i)
         } else {
             warn!("script error: unknown section {}", pos.section);
@@ -125,7 +223,11 @@ impl Script {
     }
 
     pub fn section(&self, s: &str) -> &[Instruction] {
-        self.0[s].as_ref()
+        self.sections[s].as_ref()
+    }
+
+    pub fn iter_sections(&self) -> impl Iterator<Item = (&str, &[Instruction])> {
+        self.sections.iter().map(|(s, v)| (s.as_str(), v.as_ref()))
     }
 }
 
@@ -162,6 +264,31 @@ impl<'a> Index<&'a ScriptPos> for Script {
     }
 }
 
+/// Game event that starts scripts
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// The player enters the map or the site
+    Enter,
+    /// The player steps on the tile
+    Step(Vec2d),
+    /// The character dies. Used for character templates
+    Death,
+    /// The player picks up an item of given id
+    PickUp(String),
+    /// The hour of the day begins
+    Hour(u16),
+    /// One of the player's quests is completed
+    QuestCompleted,
+}
+
+/// Script started by a trigger
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ScriptHook {
+    pub trigger: Trigger,
+    pub script_id: String,
+}
+
 /// Object that include script data.
 #[derive(Serialize, Deserialize)]
 pub struct ScriptObject {
@@ -2,6 +2,7 @@ use crate::gamedata::faction::FactionId;
 use crate::gamedata::map::SiteSymbolKind;
 use crate::gamedata::shop::ShopKind;
 use crate::gamedata::site::SiteKind;
+use crate::script::ScriptHook;
 use geom::Vec2d;
 
 /// Hold data for site generation
@@ -14,6 +15,9 @@ pub struct SiteGenObject {
     pub map_template_id: Vec<String>,
     pub unique_citizens: Vec<UniqueCitizenGenData>,
     pub shops: Vec<ShopGenData>,
+    /// Scripts triggered on this site
+    #[serde(default)]
+    pub hooks: Vec<ScriptHook>,
 }
 
 /// Data to generate a unique citizen
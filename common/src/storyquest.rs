@@ -0,0 +1,32 @@
+/// Authored quest for storylines. Its stages are changed by scripts.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoryQuestObject {
+    pub id: String,
+    pub stages: Vec<StoryQuestStage>,
+}
+
+/// Stage of a story quest.
+/// The quest log text of the stage is given by the text id "{quest id}-{stage id}".
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StoryQuestStage {
+    pub id: String,
+    /// Text ids of objectives shown while this stage is the current stage
+    #[serde(default)]
+    pub objectives: Vec<String>,
+}
+
+impl StoryQuestObject {
+    pub fn stage(&self, id: &str) -> Option<&StoryQuestStage> {
+        self.stages.iter().find(|stage| stage.id == id)
+    }
+
+    pub fn has_stage(&self, id: &str) -> bool {
+        self.stage(id).is_some()
+    }
+}
+
+/// Text id of the quest log of given stage
+pub fn stage_text_id(quest_id: &str, stage_id: &str) -> String {
+    format!("{}-{}", quest_id, stage_id)
+}
@@ -0,0 +1,33 @@
+//! Sound and music requests from the game.
+//! The front-end sets functions to play them actually.
+
+use std::sync::RwLock;
+
+lazy_static! {
+    static ref AUDIO_FUNCS: RwLock<Option<AudioFuncs>> = RwLock::new(None);
+}
+
+struct AudioFuncs {
+    play_sound: fn(&str),
+    play_music: fn(&str),
+}
+
+/// Set functions to play sounds and music. If not set, requests are ignored.
+pub fn set_audio_funcs(play_sound: fn(&str), play_music: fn(&str)) {
+    *AUDIO_FUNCS.write().unwrap() = Some(AudioFuncs {
+        play_sound,
+        play_music,
+    });
+}
+
+pub fn play_sound(name: &str) {
+    if let Some(funcs) = AUDIO_FUNCS.read().unwrap().as_ref() {
+        (funcs.play_sound)(name);
+    }
+}
+
+pub fn play_music(name: &str) {
+    if let Some(funcs) = AUDIO_FUNCS.read().unwrap().as_ref() {
+        (funcs.play_music)(name);
+    }
+}
@@ -1,4 +1,3 @@
-use crate::config::UI_CFG;
 use common::gamedata::*;
 use geom::Vec2d;
 use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -18,8 +17,9 @@ pub struct CharaLogDamage {
     pub passed_frame: u32,
 }
 
-pub fn advance_frame() {
-    get_log_mut().advance();
+/// Advance frame counters, and remove entries displayed n_frame frames
+pub fn advance_frame(n_frame: u32) {
+    get_log_mut().advance(n_frame);
 }
 
 pub fn get_log() -> RwLockReadGuard<'static, CharaLog> {
@@ -56,12 +56,12 @@ impl CharaLog {
         }
     }
 
-    fn advance(&mut self) {
+    fn advance(&mut self, n_frame: u32) {
         for damaged_chara in &mut self.damage_list {
             damaged_chara.passed_frame += 1;
         }
 
         self.damage_list
-            .retain(|damaged_chara| damaged_chara.passed_frame < UI_CFG.damage.n_frame);
+            .retain(|damaged_chara| damaged_chara.passed_frame < n_frame);
     }
 }
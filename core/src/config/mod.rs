@@ -0,0 +1,174 @@
+pub mod changeable;
+
+use common::basic;
+use serde::de::DeserializeOwned;
+use std::env;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+use std::process::exit;
+use std::sync::Mutex;
+use toml;
+
+/// Load config file from user or assets config directory.
+/// Exit if the file cannot be loaded.
+pub fn load_config_file<T: DeserializeOwned>(path: &str) -> T {
+    let path = cfg_path(path);
+    info!("Loading config file : \"{}\"", path.to_string_lossy());
+    let s = match read_to_string(&path) {
+        Ok(s) => s,
+        Err(e) => {
+            error!(
+                "Cannot load config file \"{}\"\n{}",
+                path.to_string_lossy(),
+                e
+            );
+            exit(1);
+        }
+    };
+
+    match toml::de::from_str(&s) {
+        Ok(config) => config,
+        Err(e) => {
+            error!(
+                "Cannot load config file \"{}\"\n{}",
+                path.to_string_lossy(),
+                e
+            );
+            exit(1);
+        }
+    }
+}
+
+/// Initialize lazy static
+pub fn init() {
+    use lazy_static::initialize;
+    initialize(&ASSETS_DIR);
+    initialize(&USER_DIR);
+    initialize(&CONFIG);
+    initialize(&PAK_DIRS);
+    changeable::initialize();
+}
+
+lazy_static! {
+    static ref CONFIG_MODIFIER: Mutex<Option<fn(Config) -> Config>> = Mutex::new(None);
+    pub static ref ASSETS_DIR: PathBuf = get_assets_dir().expect("Cannot get data directory path");
+    pub static ref USER_DIR: PathBuf = get_user_dir();
+    pub static ref ADDON_DIR: Option<PathBuf> = get_addon_dir();
+    pub static ref CONFIG: Config = {
+        let config: Config = load_config_file("config.toml");
+        if let Some(modifier) = *CONFIG_MODIFIER.lock().unwrap() {
+            modifier(config)
+        } else {
+            config
+        }
+    };
+    pub static ref PAK_DIRS: Vec<PathBuf> = {
+        let mut v = Vec::new();
+        v.push(abs_path("paks"));
+        v
+    };
+}
+
+/// Set the function to modify config after loading, e.g. by command line arguments.
+/// Must be called before initialization.
+pub fn set_config_modifier(modifier: fn(Config) -> Config) {
+    *CONFIG_MODIFIER.lock().unwrap() = Some(modifier);
+}
+
+/// Get application directory
+fn get_assets_dir() -> Option<PathBuf> {
+    if let Some(e) = env::var_os("RUSTED_RUINS_ASSETS_DIR") {
+        return Some(PathBuf::from(e));
+    }
+
+    if let Ok(mut exe_file) = env::current_exe() {
+        exe_file.pop();
+        exe_file.push("assets");
+        return Some(exe_file);
+    }
+
+    if let Ok(mut cdir) = env::current_dir() {
+        cdir.push("assets");
+        return Some(cdir);
+    }
+    None
+}
+
+fn get_user_dir() -> PathBuf {
+    let mut path = dirs::data_dir().expect("Failed to get user data diractory");
+    path.push(basic::APP_DIR_NAME);
+    path
+}
+
+/// Get addon directory
+fn get_addon_dir() -> Option<PathBuf> {
+    if let Some(e) = env::var_os("RUSTED_RUINS_ADDON_DIR") {
+        return Some(PathBuf::from(e));
+    }
+    None
+}
+
+/// Get application and each addon's directories
+/// They will be the root path for searching pak or text, and other data files.
+pub fn get_data_dirs() -> Vec<PathBuf> {
+    let mut v = Vec::new();
+    v.push(ASSETS_DIR.clone());
+
+    if ADDON_DIR.is_some() {
+        v.push(ADDON_DIR.clone().unwrap());
+    }
+
+    v
+}
+
+/// Create absolute path from relative path which root is application directory
+pub fn abs_path(s: &str) -> PathBuf {
+    let mut path = ASSETS_DIR.clone();
+    path.push(s);
+    path
+}
+
+/// Create absolute path from config directory
+pub fn cfg_path(s: &str) -> PathBuf {
+    let mut path = USER_DIR.clone();
+    path.push(basic::CFG_FILES_DIR);
+    path.push(s);
+    if path.exists() {
+        return path;
+    }
+
+    let mut path = ASSETS_DIR.clone();
+    path.push(basic::CFG_FILES_DIR);
+    path.push(s);
+    if !path.exists() {
+        panic!("Config file {} does not exist", path.to_string_lossy());
+    }
+    path
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub lang: String,
+    pub second_lang: String,
+    pub screen_config: String,
+    pub hardware_acceleration: bool,
+    #[serde(default)]
+    pub double_scale_mode: bool,
+    #[serde(default)]
+    pub fix_rand: bool,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Player inputs in a new game are recorded to this file
+    #[serde(default)]
+    pub record_file: Option<PathBuf>,
+    /// Replay the recorded file without window, and exit
+    #[serde(default)]
+    pub replay_file: Option<PathBuf>,
+    /// Upgrade save data in the directory to the current format version, and exit
+    #[serde(default)]
+    pub upgrade_save_dir: Option<PathBuf>,
+    pub music_volume: i32,
+    /// The number of rotating autosave slots. Autosave is disabled if 0.
+    #[serde(default)]
+    pub autosave_slots: u32,
+}
@@ -1,4 +1,5 @@
 use crate::game::extrait::*;
+use crate::game::item::identify;
 use common::gamedata::*;
 
 pub fn get_item<T: Into<ItemMoveNum>>(
@@ -9,6 +10,7 @@ pub fn get_item<T: Into<ItemMoveNum>>(
 ) {
     let item = gd.get_item(item_location);
     let obj = item.0.obj();
+    let identified = identify::is_identified(item.0);
     let src_list = gd.get_item_list_mut(item_location.0);
     let n = match n.into() {
         ItemMoveNum::Partial(n) => n,
@@ -25,6 +27,12 @@ pub fn get_item<T: Into<ItemMoveNum>>(
     }
 
     let dest = ItemListLocation::Chara { cid };
-    gd.move_item(item_location, dest, n);
+    if cid == CharaId::Player && !identified {
+        let mut item = gd.remove_item_and_get(item_location, n);
+        identify::appraise(gd, &mut item);
+        gd.get_item_list_mut(dest).append(item, n);
+    } else {
+        gd.move_item(item_location, dest, n);
+    }
     gd.chara.get_mut(cid).update();
 }
@@ -1,6 +1,7 @@
 use crate::game::extrait::*;
 use crate::game::Game;
 use crate::game::InfoGetter;
+use crate::text::ItemName;
 use common::gamedata::*;
 use common::gobj;
 use common::objholder::ItemIdx;
@@ -98,8 +99,8 @@ pub fn finish_harvest(gd: &mut GameData, chara_id: CharaId, item_idx: ItemIdx, i
 
     match harvest.harvest_type {
         HarvestType::Chop => {
-            game_log_i!("harvest-chop"; chara=gd.chara.get(chara_id), item=&target_item, n=n_yield);
-            audio::play_sound("chop-tree");
+            game_log_i!("harvest-chop"; chara=gd.chara.get(chara_id), item=ItemName::new(gd, &target_item), n=n_yield);
+            crate::audio::play_sound("chop-tree");
         }
         _ => (),
     }
@@ -2,6 +2,9 @@
 
 pub mod get_item;
 pub mod harvest;
+pub mod search;
+pub mod trap;
+pub mod unlock;
 pub mod use_item;
 
 use super::extrait::*;
@@ -9,8 +12,13 @@ use super::target::Target;
 use super::{Game, InfoGetter};
 use crate::game::damage::*;
 use crate::game::effect::{do_effect, weapon_to_effect};
+use crate::game::hook;
+use crate::game::item::identify;
+use crate::game::stealth;
+use crate::text::ItemName;
 use common::gamedata::*;
 use common::gobj;
+use common::script::Trigger;
 use geom::*;
 use rules::RULES;
 
@@ -20,6 +28,12 @@ pub fn try_move(game: &mut Game, chara_id: CharaId, dir: Direction) -> bool {
     } // Move to current tile always success
     let dest_tile = game.gd.get_current_map().chara_pos(chara_id).unwrap() + dir.as_vec();
 
+    if game.gd.get_current_map().is_inside(dest_tile)
+        && unlock::try_unlock(game, chara_id, dest_tile)
+    {
+        return true;
+    }
+
     if !game
         .gd
         .get_current_map()
@@ -34,6 +48,11 @@ pub fn try_move(game: &mut Game, chara_id: CharaId, dir: Direction) -> bool {
         if chara_id == CharaId::Player {
             game.anim_queue.push_player_move(dir);
         }
+        stealth::walk(&mut game.gd, chara_id, dest_tile);
+        trap::trigger_trap(game, chara_id, dest_tile);
+        if chara_id == CharaId::Player {
+            hook::trigger(game, &Trigger::Step(dest_tile));
+        }
     } else {
         let relation = game.gd.chara_relation(chara_id, other_chara.unwrap());
 
@@ -45,6 +64,7 @@ pub fn try_move(game: &mut Game, chara_id: CharaId, dir: Direction) -> bool {
                 }
                 if chara_id == CharaId::Player {
                     game.anim_queue.push_player_move(dir);
+                    hook::trigger(game, &Trigger::Step(dest_tile));
                 }
             }
             Relationship::HOSTILE => {
@@ -147,7 +167,7 @@ pub fn throw_item(game: &mut Game, il: ItemLocation, cid: CharaId, target: Targe
         * chara.attr.str as f32
         * chara.attr.dex as f32
         * (chara.skills.get(SkillKind::Throwing) as f32 + RULES.combat.skill_base);
-    game_log!("throw-item"; chara=chara, item=item);
+    game_log!("throw-item"; chara=chara, item=ItemName::new(gd, &item));
     super::effect::do_effect(game, &effect, Some(cid), target, power, 1.0);
 
     // Exp processing
@@ -164,11 +184,12 @@ pub fn drink_item(game: &mut Game, il: ItemLocation, cid: CharaId) {
     let gd = &mut game.gd;
     let item = gd.remove_item_and_get(il, 1); // Decrease the number of item by 1
 
-    let chara = gd.chara.get_mut(cid);
-    game_log!("drink-item"; chara=chara, item=item);
+    let chara = gd.chara.get(cid);
+    game_log!("drink-item"; chara=chara, item=ItemName::new(gd, &item));
 
     let power = item.calc_eff() as f32 * RULES.effect.item_drink_power_factor;
     apply_medical_effect(game, cid, &item.obj().medical_effect, power);
+    identify::identify_by_use(&mut game.gd, &item, cid);
 }
 
 /// Eat one item
@@ -177,8 +198,8 @@ pub fn eat_item(game: &mut Game, il: ItemLocation, cid: CharaId) {
     let item = gd.remove_item_and_get(il, 1); // Decrease the number of item by 1
     let item_obj = item.obj();
 
+    game_log!("eat-item"; chara=gd.chara.get(cid), item=ItemName::new(gd, &item));
     let chara = gd.chara.get_mut(cid);
-    game_log!("eat-item"; chara=chara, item=item);
     let nutrition: f32 = item_obj.nutrition.into();
     if let Some(damage) = chara.add_sp(nutrition * RULES.chara.sp_nutrition_factor, cid) {
         do_damage(game, cid, damage, CharaDamageKind::Starve);
@@ -199,6 +220,7 @@ pub fn release_item(game: &mut Game, il: ItemLocation, cid: CharaId, target: Tar
             let power = (skill_level / 10.0 + 1.0) * item_eff * RULES.magic.magic_device_base_power;
             if let Some(effect) = item_obj.magical_effect.as_ref() {
                 super::effect::do_effect(game, effect, Some(cid), target, power, 1.0);
+                identify::identify_by_use(&mut game.gd, &item, cid);
             } else {
                 return;
             }
@@ -210,6 +232,18 @@ pub fn release_item(game: &mut Game, il: ItemLocation, cid: CharaId, target: Tar
     game.gd.get_item_list_mut(il.0).append(item, 1);
 }
 
+/// Use active skill
+pub fn use_active_skill(game: &mut Game, cid: CharaId, id: &str, target: Target) {
+    let active_skill = if let Some(active_skill) = RULES.active_skills.get(id) {
+        active_skill
+    } else {
+        warn!("unknown active skill \"{}\"", id);
+        return;
+    };
+    let power = game.gd.chara.get(cid).attr.int as f32 * RULES.magic.active_skill_base_power;
+    super::effect::do_effect(game, &active_skill.effect, Some(cid), target, power, 1.0);
+}
+
 fn apply_medical_effect(game: &mut Game, cid: CharaId, effect: &Option<Effect>, eff: f32) {
     if effect.is_none() {
         return;
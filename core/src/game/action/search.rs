@@ -0,0 +1,109 @@
+use crate::game::extrait::*;
+use crate::game::Game;
+use common::gamedata::*;
+use common::gobj;
+use common::obj::SpecialTileObject;
+use geom::*;
+use rules::RULES;
+
+/// Search hidden traps and passages around the character
+pub fn search(game: &mut Game, cid: CharaId) {
+    let center = if let Some(pos) = game.gd.get_current_map().chara_pos(cid) {
+        pos
+    } else {
+        return;
+    };
+    let floor_level = game.gd.get_current_mapid().floor();
+    let skill_level = game.gd.chara.get(cid).skills.get(SkillKind::Searching) as f32;
+    let range = RULES.effect.search_range;
+    let map = game.gd.get_current_map_mut();
+    let mut n_found = 0;
+
+    for pos in RectIter::new(center - (range, range), center + (range, range)) {
+        if !map.is_inside(pos) {
+            continue;
+        }
+        let difficulty = match map.tile[pos].special {
+            SpecialTileKind::Trap { id, hidden: true } => {
+                gobj::get_by_id_checked::<SpecialTileObject>(id.as_str())
+                    .and_then(|obj| obj.trap.as_ref())
+                    .map(|trap| trap.difficulty)
+                    .unwrap_or(0)
+            }
+            SpecialTileKind::HiddenPassage { difficulty } => difficulty,
+            _ => {
+                continue;
+            }
+        };
+        if rng::gen_range(0.0, skill_level + RULES.effect.searching_base) < difficulty as f32 {
+            continue;
+        }
+
+        n_found += 1;
+        match map.tile[pos].special {
+            SpecialTileKind::Trap { id, .. } => {
+                map.tile[pos].special = SpecialTileKind::Trap { id, hidden: false };
+                game_log_i!("search-found-trap");
+            }
+            _ => {
+                map.tile[pos].special = SpecialTileKind::None;
+                map.erase_wall(pos);
+                game_log_i!("search-found-hidden-passage");
+            }
+        }
+    }
+
+    if n_found > 0 {
+        game.gd.chara.get_mut(cid).skills.add_exp(
+            SkillKind::Searching,
+            RULES.exp.searching * n_found,
+            floor_level,
+        );
+    } else {
+        game_log_i!("search-found-nothing");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{self, TRAP_ID, WALL_ID};
+
+    #[test]
+    fn search_reveals_traps_and_passages() {
+        let _lock = test_util::lock();
+        let (gd, _) = test_util::fixture(Vec2d(14, 14));
+        let mut game = test_util::game(gd);
+        game.gd
+            .chara
+            .get_mut(CharaId::Player)
+            .skills
+            .learn_new_skill(SkillKind::Searching);
+        let trap = SpecialTileKind::Trap {
+            id: TRAP_ID.into(),
+            hidden: true,
+        };
+        let map = game.gd.get_current_map_mut();
+        map.tile[Vec2d(3, 2)].special = trap;
+        // Out of range
+        map.tile[Vec2d(10, 10)].special = trap;
+        map.set_wall(Vec2d(2, 3), gobj::id_to_idx(WALL_ID));
+        map.tile[Vec2d(2, 3)].special = SpecialTileKind::HiddenPassage { difficulty: 0 };
+
+        search(&mut game, CharaId::Player);
+
+        let map = game.gd.get_current_map();
+        assert_eq!(
+            map.tile[Vec2d(3, 2)].special,
+            SpecialTileKind::Trap {
+                id: TRAP_ID.into(),
+                hidden: false
+            }
+        );
+        assert_eq!(map.tile[Vec2d(10, 10)].special, trap);
+        assert!(map.tile[Vec2d(2, 3)].wall.is_empty());
+        assert!(map.tile[Vec2d(2, 3)].special.is_none());
+        let skills = &game.gd.chara.get(CharaId::Player).skills;
+        assert_ne!(skills.get_level_exp(SkillKind::Searching), (1, 0));
+    }
+}
@@ -0,0 +1,62 @@
+use crate::game::effect::do_effect;
+use crate::game::Game;
+use common::gamedata::*;
+use common::gobj;
+use common::obj::SpecialTileObject;
+use geom::*;
+
+/// Activate the trap on given tile by the character stepping on it
+pub fn trigger_trap(game: &mut Game, cid: CharaId, pos: Vec2d) {
+    let map = game.gd.get_current_map_mut();
+    let id = if let SpecialTileKind::Trap { id, .. } = map.tile[pos].special {
+        id
+    } else {
+        return;
+    };
+    map.tile[pos].special = SpecialTileKind::Trap { id, hidden: false };
+
+    let trap = if let Some(trap) =
+        gobj::get_by_id_checked::<SpecialTileObject>(id.as_str()).and_then(|obj| obj.trap.as_ref())
+    {
+        trap
+    } else {
+        warn!("special tile \"{}\" is not a trap", id.as_str());
+        return;
+    };
+
+    game_log!("trap-activated"; chara=game.gd.chara.get(cid));
+    do_effect(game, &trap.effect, None, pos, trap.power, 0.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{self, TRAP_ID};
+    use crate::game::InfoGetter;
+
+    #[test]
+    fn stepping_on_trap() {
+        let _lock = test_util::lock();
+        let (gd, monster) = test_util::fixture(Vec2d(6, 6));
+        let mut game = test_util::game(gd);
+        let pos = Vec2d(6, 7);
+        game.gd.get_current_map_mut().tile[pos].special = SpecialTileKind::Trap {
+            id: TRAP_ID.into(),
+            hidden: true,
+        };
+
+        assert!(super::super::try_move(&mut game, monster, Direction::S));
+        assert_eq!(game.gd.chara_pos(monster), Some(pos));
+        assert_eq!(
+            game.gd.get_current_map().tile[pos].special,
+            SpecialTileKind::Trap {
+                id: TRAP_ID.into(),
+                hidden: false
+            }
+        );
+        let status = &game.gd.chara.get(monster).status;
+        assert!(status
+            .iter()
+            .any(|s| matches!(s, CharaStatus::Asleep { .. })));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::game::extrait::*;
+use crate::game::stealth;
+use crate::game::Game;
+use common::gamedata::*;
+use common::gobj;
+use common::objholder::ItemIdx;
+use geom::*;
+use rules::RULES;
+
+/// Try to open the locked door by a key or lockpicking.
+/// Returns false if there is no locked door.
+pub fn try_unlock(game: &mut Game, cid: CharaId, pos: Vec2d) -> bool {
+    let lock_level = match game.gd.get_current_map().tile[pos].special {
+        SpecialTileKind::Door { lock_level } if lock_level > 0 => lock_level,
+        _ => {
+            return false;
+        }
+    };
+    stealth::make_noise(&mut game.gd, Some(cid), pos, RULES.stealth.noise_door);
+    // NPCs' attempts are logged only if the player can see the door
+    let visible = cid == CharaId::Player || game.view_map.get_tile_visible(pos);
+
+    // Use a key if the character has
+    if let Some(key_idx) = gobj::id_to_idx_checked::<ItemIdx>(&RULES.item.key_item_id) {
+        let item_list = game.gd.get_item_list_mut(ItemListLocation::Chara { cid });
+        if item_list.count(key_idx) > 0 {
+            item_list.consume(key_idx, 1, |_, _| {}, false);
+            unlock(game, pos);
+            if visible {
+                game_log!("door-unlock-by-key"; chara=game.gd.chara.get(cid));
+            }
+            return true;
+        }
+    }
+
+    let floor_level = game.gd.get_current_mapid().floor();
+    let chara = game.gd.chara.get_mut(cid);
+    let skill_level = chara.skills.get(SkillKind::Lockpicking) as f32;
+    let power = rng::gen_range(0.0, skill_level + RULES.effect.lockpicking_base);
+    chara
+        .skills
+        .add_exp(SkillKind::Lockpicking, RULES.exp.lockpicking, floor_level);
+
+    if power >= lock_level as f32 {
+        unlock(game, pos);
+        if visible {
+            game_log!("door-unlock-by-lockpicking"; chara=game.gd.chara.get(cid));
+        }
+    } else if visible {
+        game_log!("door-lockpicking-failed"; chara=game.gd.chara.get(cid));
+    }
+    true
+}
+
+fn unlock(game: &mut Game, pos: Vec2d) {
+    game.gd.get_current_map_mut().tile[pos].special = SpecialTileKind::Door { lock_level: 0 };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util;
+
+    #[test]
+    fn unlock_by_key_and_lockpicking() {
+        let _lock = test_util::lock();
+        let (gd, _) = test_util::fixture(Vec2d(14, 14));
+        let mut game = test_util::game(gd);
+        let door = Vec2d(3, 2);
+        let locked = SpecialTileKind::Door { lock_level: 1000 };
+        game.gd.get_current_map_mut().tile[door].special = locked;
+
+        assert!(!try_unlock(&mut game, CharaId::Player, Vec2d(3, 3)));
+        // Too difficult to pick
+        assert!(try_unlock(&mut game, CharaId::Player, door));
+        assert_eq!(game.gd.get_current_map().tile[door].special, locked);
+
+        // A key is consumed
+        let key_idx: ItemIdx = gobj::id_to_idx(&RULES.item.key_item_id);
+        let key = crate::game::item::gen::gen_item_from_idx(key_idx, 1);
+        let il = ItemListLocation::Chara {
+            cid: CharaId::Player,
+        };
+        game.gd.get_item_list_mut(il).append(key, 1);
+        assert!(try_unlock(&mut game, CharaId::Player, door));
+        assert_eq!(
+            game.gd.get_current_map().tile[door].special,
+            SpecialTileKind::Door { lock_level: 0 }
+        );
+        assert_eq!(game.gd.get_item_list(il).count(key_idx), 0);
+
+        // Skilled characters pick easy locks
+        game.gd.get_current_map_mut().tile[door].special = SpecialTileKind::Door { lock_level: 1 };
+        game.gd
+            .chara
+            .get_mut(CharaId::Player)
+            .skills
+            .set_skill_level(SkillKind::Lockpicking, 10000);
+        assert!(try_unlock(&mut game, CharaId::Player, door));
+        assert!(!game.gd.get_current_map().tile[door].special.is_blocking());
+    }
+
+    #[test]
+    fn npcs_move_through_locked_doors() {
+        let _lock = test_util::lock();
+        let (gd, monster) = test_util::fixture(Vec2d(6, 2));
+        let mut game = test_util::game(gd);
+        let wall_idx = gobj::id_to_idx(test_util::WALL_ID);
+        let map = game.gd.get_current_map_mut();
+        for y in 0..16 {
+            map.set_wall(Vec2d(4, y), wall_idx);
+        }
+        map.erase_wall(Vec2d(4, 2));
+        map.tile[Vec2d(4, 2)].special = SpecialTileKind::Door { lock_level: 1 };
+        game.gd
+            .chara
+            .get_mut(monster)
+            .skills
+            .set_skill_level(SkillKind::Lockpicking, 10000);
+
+        // The player does not plan paths through locked doors
+        use crate::game::map::path::find_path;
+        assert!(find_path(&game.gd, CharaId::Player, Vec2d(6, 3)).is_none());
+        let path = find_path(&game.gd, monster, Vec2d(2, 3)).unwrap();
+        assert!(path.contains(&Vec2d(4, 2)));
+
+        game.gd
+            .get_current_map_mut()
+            .locate_chara(monster, Vec2d(5, 2));
+        assert!(super::super::try_move(&mut game, monster, Direction::W));
+        assert!(!game.gd.get_current_map().tile[Vec2d(4, 2)]
+            .special
+            .is_blocking());
+        assert!(super::super::try_move(&mut game, monster, Direction::W));
+        assert_eq!(game.gd.chara_pos(monster), Some(Vec2d(4, 2)));
+    }
+}
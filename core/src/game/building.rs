@@ -44,7 +44,7 @@ pub fn start_build(game: &mut Game, pos: Vec2d, builder: CharaId) {
 pub fn finish_build(game: &mut Game, pos: Vec2d, wall_idx: WallIdx) {
     let map = game.gd.get_current_map_mut();
     map.set_wall(pos, wall_idx);
-    audio::play_sound("finish-build");
+    crate::audio::play_sound("finish-build");
 }
 
 fn is_buildable(gd: &GameData, pos: Vec2d) -> bool {
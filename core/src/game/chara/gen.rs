@@ -38,6 +38,7 @@ pub fn create_chara(
         skills: gen_skill_list(ct, lv, class),
         rel: Relationship::NEUTRAL,
         trigger_talk: None,
+        attr_revision: CharaAttrRevision::default(),
     };
 
     chara.update();
@@ -53,8 +54,12 @@ pub fn create_npc_chara(dungeon: DungeonKind, floor_level: u32) -> Chara {
         .get(&dungeon)
         .expect("No rule for npc generation");
     let idx = choose_npc_chara_template(&dungeon_gen_rule.npc_race_probability, floor_level);
+    create_dungeon_npc(idx, dungeon_gen_rule.default_faction_id)
+}
+
+/// Create hostile npc character in dungeons from the template
+pub fn create_dungeon_npc(idx: CharaTemplateIdx, faction_id: FactionId) -> Chara {
     let ct = gobj::get_obj(idx);
-    let faction_id = dungeon_gen_rule.default_faction_id;
     let mut chara = create_chara(idx, ct.gen_level, faction_id, None);
     set_skill(&mut chara);
     chara.rel = Relationship::HOSTILE;
@@ -132,6 +137,8 @@ pub fn create_ai(ai_kind: NpcAIKind) -> CharaAI {
     CharaAI {
         kind: ai_kind,
         initial_pos: Vec2d::new(0, 0),
+        leader: None,
+        awareness: Awareness::default(),
     }
 }
 
@@ -24,6 +24,8 @@ pub trait CharaEx {
     fn add_damage_exp(&mut self, damage: i32, attacker_level: u32);
     /// Add exp when attacked.
     fn add_evasion_exp(&mut self, attacker_level: u32);
+    /// Add exp after a sneak attack.
+    fn add_stealth_exp(&mut self, target_level: u32);
     /// Add exp when regeneration
     fn add_healing_exp(&mut self);
     /// sp increase/decrease. return damage if sp is lower than 0.
@@ -74,6 +76,10 @@ impl CharaEx for Chara {
         self.add_skill_exp(SkillKind::Evasion, RULES.exp.evasion, attacker_level);
     }
 
+    fn add_stealth_exp(&mut self, target_level: u32) {
+        self.add_skill_exp(SkillKind::Stealth, RULES.exp.stealth, target_level);
+    }
+
     fn add_healing_exp(&mut self) {
         let lv = self.skills.get(SkillKind::Healing);
         if get_rng().gen_bool(RULES.exp.healing_probability.into()) {
@@ -28,7 +28,7 @@ pub fn preturn(game: &mut Game, cid: CharaId) -> bool {
         }
     }
     for s in expired_status.into_iter() {
-        s.expire(&mut game.gd, cid);
+        s.expire(game, cid);
     }
 
     let chara = game.gd.chara.get_mut(cid);
@@ -1,5 +1,6 @@
 //! Functions for character status operation
 
+use crate::game::Game;
 use common::gamedata::*;
 
 pub trait CharaStatusOperation {
@@ -86,7 +87,7 @@ pub trait CharaStatusEx {
     /// If this status is expired, returns true.
     /// Expired status will be removed from character.
     fn is_expired(&self) -> bool;
-    fn expire(self, gd: &mut GameData, cid: CharaId);
+    fn expire(self, game: &mut Game, cid: CharaId);
 }
 
 macro_rules! impl_chara_status_ex {
@@ -131,7 +132,7 @@ impl CharaStatusEx for CharaStatus {
         }
     }
 
-    fn expire(self, gd: &mut GameData, cid: CharaId) {
+    fn expire(self, game: &mut Game, cid: CharaId) {
         match self {
             CharaStatus::Work { work, .. } => match work {
                 Work::Creation {
@@ -142,7 +143,7 @@ impl CharaStatusEx for CharaStatus {
                 } => {
                     assert!(cid == CharaId::Player);
                     crate::game::creation::finish_creation(
-                        gd,
+                        game,
                         kind,
                         &recipe,
                         ingredients,
@@ -150,7 +151,7 @@ impl CharaStatusEx for CharaStatus {
                     );
                 }
                 Work::Harvest { item_idx, il } => {
-                    crate::game::action::harvest::finish_harvest(gd, cid, item_idx, il);
+                    crate::game::action::harvest::finish_harvest(&mut game.gd, cid, item_idx, il);
                 }
             },
             _ => (),
@@ -8,7 +8,11 @@ use rules::RULES;
 pub fn update_attributes(chara: &mut Chara) {
     let ct = gobj::get_obj(chara.template);
 
-    let base_attr = ct.base_attr.revise(RULES.class.get(chara.class).revision);
+    let base_attr = ct
+        .base_attr
+        .revise(RULES.class.get(chara.class).revision)
+        .revise(chara.attr_revision)
+        .revise(equip_attr_revision(chara));
 
     chara.attr.max_hp = calc_max_hp(chara, ct);
     chara.attr.str = base_attr.str as u16;
@@ -43,6 +47,19 @@ pub fn update_attributes(chara: &mut Chara) {
     chara.attr.view_range = RULES.chara.default_view_range;
 }
 
+/// Attribute bonuses by affixes of equipment
+fn equip_attr_revision(chara: &Chara) -> CharaAttrRevision {
+    let mut revision = CharaAttrRevision::default();
+    for (_, _, item) in chara.equip.item_iter() {
+        for (kind, value) in item.affixes() {
+            if let AffixKind::Attr(attr) = kind {
+                revision.add(attr, value as i16);
+            }
+        }
+    }
+    revision
+}
+
 fn calc_max_hp(chara: &mut Chara, ct: &CharaTemplateObject) -> i32 {
     (chara.skills.get(SkillKind::Endurance) as i32 + 8) * ct.base_attr.base_hp / 8
 }
@@ -75,3 +92,43 @@ pub fn calc_carrying_capacity(chara: &Chara) -> f32 {
         * (skill_level + 10.0)
         * RULES.chara.carrying_capacity_factor
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util;
+    use geom::Vec2d;
+
+    #[test]
+    fn attr_bonus_by_affixes() {
+        test_util::init();
+        let mut chara = test_util::chara("monster", Vec2d(0, 0));
+        update_attributes(&mut chara);
+        let (str, dex) = (chara.attr.str, chara.attr.dex);
+
+        chara.equip = EquipItemList::new(&[EquipSlotKind::MeleeWeapon, EquipSlotKind::BodyArmor]);
+        let sword = test_util::equipment(
+            test_util::SWORD_ID,
+            &[(AffixKind::Attr(CharaAttrKind::Str), 3)],
+        );
+        let armor = test_util::equipment(
+            test_util::ARMOR_ID,
+            &[
+                (AffixKind::Attr(CharaAttrKind::Str), 2),
+                (AffixKind::Attr(CharaAttrKind::Dex), 1),
+                (AffixKind::Defence(Element::Fire), 10),
+            ],
+        );
+        chara.equip.equip(EquipSlotKind::MeleeWeapon, 0, sword);
+        chara.equip.equip(EquipSlotKind::BodyArmor, 0, armor);
+
+        let revision = equip_attr_revision(&chara);
+        assert_eq!(revision.str, 5);
+        assert_eq!(revision.dex, 1);
+        assert_eq!(revision.vit, 0);
+
+        update_attributes(&mut chara);
+        assert_eq!(chara.attr.str, str + 5);
+        assert_eq!(chara.attr.dex, dex + 1);
+    }
+}
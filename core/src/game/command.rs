@@ -19,6 +19,7 @@ pub enum Command {
         target: Vec2d,
     },
     Enter,
+    Search,
     Cancel,
     RotateWindowRight,
     RotateWindowLeft,
@@ -29,6 +30,7 @@ pub enum Command {
     OpenExitWin,
     OpenGameInfoWin,
     OpenHelpWin,
+    OpenQuestWin,
     OpenStatusWin,
     OpenItemMenu,
     PickUpItem,
@@ -62,12 +62,13 @@ pub fn start_creation(
 }
 
 pub fn finish_creation(
-    gd: &mut GameData,
+    game: &mut Game,
     kind: CreationKind,
     recipe: &Recipe,
     _ingredients: Vec<(Item, u32)>,
     material: Option<MaterialName>,
 ) {
+    let gd = &mut game.gd;
     let idx: ItemIdx = gobj::id_to_idx(&recipe.product);
     let item_obj = gobj::get_obj(idx);
     let mut item = Item {
@@ -76,6 +77,7 @@ pub fn finish_creation(
         kind: item_obj.kind,
         quality: ItemQuality::default(),
         attributes: vec![],
+        bless: BlessState::Normal,
     };
     if let Some(material) = material {
         item.attributes.push(ItemAttribute::Material(material));
@@ -106,6 +108,7 @@ pub fn finish_creation(
     }
 
     game_log_i!("creation-finish"; chara=player, product=product);
+    super::quest::notify(game, super::quest::QuestEvent::ItemCreated(idx));
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -1,6 +1,6 @@
 use crate::game::extrait::*;
 use crate::game::Game;
-use crate::text::ToText;
+use crate::text::{ItemName, ToText};
 use common::gamedata::*;
 use common::gobj;
 use common::objholder::*;
@@ -122,7 +122,7 @@ fn gen_item(game: &mut Game, arg1: &str, n: u32) {
         return;
     };
 
-    game_log_i!("debug-command-genitem"; item=item);
+    game_log_i!("debug-command-genitem"; item=ItemName::new(&game.gd, &item));
     let pos = game.gd.player_pos();
     game.gd.get_current_map_mut().locate_item(item, pos, n);
 }
@@ -0,0 +1,415 @@
+//! This module provides functions for auto generated dungeons
+
+use crate::game::map::builder::MapBuilder;
+use crate::game::map::MapEx;
+use crate::game::saveload::gen_box_id;
+use common::gamedata::*;
+use common::gobj;
+use common::maptemplate::MapTemplateObject;
+use common::objholder::*;
+use geom::*;
+use map_generator::validator::{validate_by, ValidationError};
+use rng::{self, GameRng, SliceRandom};
+use rules::dungeon_gen::DungeonGenParams;
+use rules::RULES;
+
+/// Maximum number of floor generation when modified floors are invalid
+const MAX_FLOOR_GEN_TRY: usize = 4;
+
+/// Add a new dungeon
+pub fn add_dungeon_site(gd: &mut GameData, dungeon_kind: DungeonKind, pos: Vec2d) -> SiteId {
+    let floor_range = &RULES.dungeon_gen[&dungeon_kind].floor_range;
+    let n_floor = rng::gen_range(floor_range[0], floor_range[1]);
+    let mut site = Site::new(n_floor, None);
+    site.content = SiteContent::AutoGenDungeon { dungeon_kind };
+    gd.add_site(site, SiteKind::AutoGenDungeon, RegionId::default(), pos)
+        .unwrap()
+}
+
+/// Extend dungion site by one floor
+pub fn extend_site_floor(gd: &mut GameData, sid: SiteId) {
+    let floor = gd.region.get_site(sid).floor_num();
+    let is_deepest_floor = floor >= gd.region.get_site(sid).max_floor() - 1;
+    let mut faction_id = FactionId::default();
+    let (map, prefab) = match gd.region.get_site(sid).content {
+        SiteContent::AutoGenDungeon { dungeon_kind } => {
+            let rule = &RULES.dungeon_gen[&dungeon_kind];
+            faction_id = rule.default_faction_id;
+            gen_floor(rule, floor, is_deepest_floor)
+        }
+        _ => (MapBuilder::new(40, 40).floor(floor).build(), None),
+    };
+
+    let map_random_id = gen_box_id(gd);
+    let mid = gd.add_map(map, sid, map_random_id);
+    if let Some((prefab_idx, prefab_pos)) = prefab {
+        gen_prefab_npcs(gd, mid, gobj::get_obj(prefab_idx), prefab_pos, faction_id);
+    }
+    super::map::gen_npcs(gd, mid, 10, mid.floor());
+    super::map::gen_items(gd, mid);
+
+    if is_deepest_floor {
+        add_for_deepest_floor(gd, mid);
+    }
+}
+
+/// Generate a floor map, and returns the prefab and its position if stamped.
+/// If modified floors are invalid, a floor without prefabs and features is generated instead.
+fn gen_floor(
+    rule: &DungeonGenParams,
+    floor: u32,
+    is_deepest_floor: bool,
+) -> (Map, Option<(MapTemplateIdx, Vec2d)>) {
+    let floor_gen_id = &rule
+        .floor_gen
+        .choose_weighted(&mut GameRng, |item| item.1)
+        .unwrap()
+        .0;
+    let tile_idx = gobj::id_to_idx(&rule.terrain[0][0]);
+    let wall_idx = gobj::id_to_idx(&rule.terrain[0][1]);
+    let builder = MapBuilder::new(1, 1)
+        .floor(floor)
+        .tile(tile_idx)
+        .wall(wall_idx)
+        .deepest_floor(is_deepest_floor)
+        .floor_gen_id(floor_gen_id)
+        .music(&rule.music)
+        .lit_room_probability(rule.lit_room_probability);
+
+    for _ in 0..MAX_FLOOR_GEN_TRY {
+        let mut builder = builder.clone();
+        let prefab_idx = choose_prefab(rule);
+        if let Some(prefab_idx) = prefab_idx {
+            builder = builder.prefab(prefab_idx);
+        }
+        let (mut map, prefab_pos) = builder.build_with_prefab();
+        set_sub_walls(&mut map, rule);
+        set_features(&mut map, rule, floor);
+        match check_connectivity(&map, floor) {
+            Ok(()) => return (map, prefab_idx.zip(prefab_pos)),
+            Err(e) => warn!("Generated floor {} is invalid: {}", floor, e),
+        }
+    }
+
+    let mut map = builder.build();
+    set_sub_walls(&mut map, rule);
+    (map, None)
+}
+
+/// Choose a prefab map template by the probability and weight in the rule
+fn choose_prefab(rule: &DungeonGenParams) -> Option<MapTemplateIdx> {
+    if rule.prefabs.is_empty() || !rng::gen_bool(rule.prefab_probability) {
+        return None;
+    }
+
+    let id = &rule
+        .prefabs
+        .choose_weighted(&mut GameRng, |item| item.1)
+        .ok()?
+        .0;
+    let idx = gobj::id_to_idx_checked(id);
+    if idx.is_none() {
+        warn!("unknown prefab map template id {}", id);
+    }
+    idx
+}
+
+/// Locate npcs of the prefab.
+/// NPCs outside the map or on impassable or occupied tiles are skipped.
+fn gen_prefab_npcs(
+    gd: &mut GameData,
+    mid: MapId,
+    t: &MapTemplateObject,
+    offset: Vec2d,
+    faction_id: FactionId,
+) {
+    for (pos, npc_gen) in &t.npcs {
+        let idx: CharaTemplateIdx = if let Some(idx) = gobj::id_to_idx_checked(&npc_gen.id) {
+            idx
+        } else {
+            warn!("unknown chara template id {} in prefab", npc_gen.id);
+            continue;
+        };
+        let chara = crate::game::chara::gen::create_dungeon_npc(idx, faction_id);
+        let pos = offset + *pos;
+        let map = gd.region.get_map(mid);
+        if !map.is_passable(&chara, pos) || map.tile[pos].chara.is_some() {
+            warn!(
+                "cannot locate {} of prefab {} at {:?}",
+                npc_gen.id, t.id, pos
+            );
+            continue;
+        }
+        let cid = gd.add_chara_to_map(chara, mid);
+        gd.region.get_map_mut(mid).locate_chara(cid, pos);
+    }
+}
+
+pub fn set_sub_walls(map: &mut Map, rule: &DungeonGenParams) {
+    // Prefab walls which differ from the default wall are kept
+    let default_wall: WallIdx = gobj::id_to_idx(&rule.terrain[0][1]);
+    for (sub_wall_id, weight) in &rule.sub_walls {
+        let wall_idx: WallIdx = if let Some(wall_idx) = gobj::id_to_idx_checked(sub_wall_id) {
+            wall_idx
+        } else {
+            error!("unknown wall id {}", sub_wall_id);
+            return;
+        };
+
+        let binary_map = map_generator::binary::create_binary_fractal(map.size().into(), *weight);
+
+        for p in binary_map.iter_idx() {
+            let tile = &mut map.tile[p];
+            if tile.wall.idx() == Some(default_wall) && binary_map[p] {
+                tile.wall.set_idx(wall_idx);
+            }
+        }
+    }
+}
+
+/// Lock doors, replace doors with hidden passages, and locate traps
+pub fn set_features(map: &mut Map, rule: &DungeonGenParams, floor: u32) {
+    let wall_idx: WallIdx = gobj::id_to_idx(&rule.terrain[0][1]);
+    let difficulty = floor + 1;
+
+    for p in map.tile.iter_idx() {
+        if let SpecialTileKind::Door { .. } = map.tile[p].special {
+            if rng::gen_bool(rule.hidden_passage_probability) {
+                map.set_wall(p, wall_idx);
+                map.tile[p].special = SpecialTileKind::HiddenPassage { difficulty };
+            } else if rng::gen_bool(rule.locked_door_probability) {
+                map.tile[p].special = SpecialTileKind::Door {
+                    lock_level: difficulty + rng::gen_range(0, 3),
+                };
+            }
+        }
+    }
+
+    // Traps not defined in objects are skipped
+    let traps: Vec<&(String, f32)> = rule
+        .traps
+        .iter()
+        .filter(|(id, _)| {
+            let exists = gobj::id_to_idx_checked::<SpecialTileIdx>(id).is_some();
+            if !exists {
+                warn!("unknown trap id {}", id);
+            }
+            exists
+        })
+        .collect();
+    if traps.is_empty() {
+        return;
+    }
+    for p in map.tile.iter_idx() {
+        let tile = &map.tile[p];
+        if !tile.wall.is_empty() || !tile.special.is_none() {
+            continue;
+        }
+        if !rng::gen_bool(rule.trap_probability) {
+            continue;
+        }
+        let id = &traps
+            .choose_weighted(&mut GameRng, |item| item.1)
+            .unwrap()
+            .0;
+        map.tile[p].special = SpecialTileKind::Trap {
+            id: id.as_str().into(),
+            hidden: true,
+        };
+    }
+}
+
+/// Check that all floor tiles are connected after walls are modified.
+/// Locked doors and hidden passages are regarded as passable.
+fn check_connectivity(map: &Map, floor: u32) -> Result<(), ValidationError> {
+    let exit = map.search_stairs(floor + 1);
+    validate_by(map.size().into(), map.entrance[0], exit, |p| {
+        map.tile[p].wall.is_empty()
+            || matches!(map.tile[p].special, SpecialTileKind::HiddenPassage { .. })
+    })
+}
+
+/// Add items for deepest floor of dungeon
+pub fn add_for_deepest_floor(gd: &mut GameData, mid: MapId) {
+    let map = gd.region.get_map_mut(mid);
+
+    let p = if let Some(p) = crate::game::map::choose_empty_tile(map) {
+        p
+    } else {
+        return;
+    };
+
+    let idx: ItemIdx = gobj::id_to_idx("ancient-box");
+    let item_obj: &ItemObject = gobj::get_obj(idx);
+    let item = Item {
+        idx,
+        flags: item_obj.default_flags,
+        kind: item_obj.kind,
+        quality: ItemQuality::default(),
+        attributes: vec![],
+        bless: BlessState::Normal,
+    };
+
+    let mut item_list = ItemList::new();
+    item_list.append(item, 1);
+    map.tile[p].item_list = item_list;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{self, PREFAB_ID, TRAP_ID, WALL_ID};
+
+    fn rule() -> DungeonGenParams {
+        serde_json::from_str(
+            r#"{
+                "floor_gen": [["rooms", 1.0]],
+                "npc_race_probability": {},
+                "default_faction_id": "monster",
+                "terrain": [["test-tile", "test-wall"]],
+                "sub_walls": [],
+                "item_gen_probability": 0.0,
+                "item_gen_weight_for_kind": {},
+                "floor_range": [1, 2],
+                "music": ""
+            }"#,
+        )
+        .unwrap()
+    }
+
+    /// Map with a wall at (1, 1), and doors on x = 8
+    fn map() -> Map {
+        let mut map = Map::new(16, 16);
+        map.set_wall(Vec2d(1, 1), gobj::id_to_idx(WALL_ID));
+        for y in 0..16 {
+            map.tile[Vec2d(8, y)].special = SpecialTileKind::Door { lock_level: 0 };
+        }
+        map
+    }
+
+    #[test]
+    fn lock_and_hide_doors() {
+        test_util::init();
+        let mut rule = rule();
+        let mut map = map();
+        set_features(&mut map, &rule, 3);
+        assert!((0..16)
+            .all(|y| map.tile[Vec2d(8, y)].special == SpecialTileKind::Door { lock_level: 0 }));
+
+        rule.locked_door_probability = 1.0;
+        set_features(&mut map, &rule, 3);
+        for y in 0..16 {
+            match map.tile[Vec2d(8, y)].special {
+                SpecialTileKind::Door { lock_level } => assert!(lock_level >= 4),
+                other => panic!("unexpected special tile {:?}", other),
+            }
+        }
+
+        rule.hidden_passage_probability = 1.0;
+        let mut map = self::map();
+        set_features(&mut map, &rule, 3);
+        for y in 0..16 {
+            let tile = &map.tile[Vec2d(8, y)];
+            assert_eq!(
+                tile.special,
+                SpecialTileKind::HiddenPassage { difficulty: 4 }
+            );
+            assert!(!tile.wall.is_empty());
+        }
+    }
+
+    #[test]
+    fn locate_hidden_traps() {
+        test_util::init();
+        let mut rule = rule();
+        rule.trap_probability = 1.0;
+        rule.traps = vec![("unknown-trap".into(), 1.0)];
+        let mut map = map();
+        set_features(&mut map, &rule, 3);
+        assert!(map
+            .tile
+            .iter()
+            .all(|tile| !matches!(tile.special, SpecialTileKind::Trap { .. })));
+
+        rule.traps.push((TRAP_ID.into(), 1.0));
+        set_features(&mut map, &rule, 3);
+        let trap = SpecialTileKind::Trap {
+            id: TRAP_ID.into(),
+            hidden: true,
+        };
+        for p in map.tile.iter_idx() {
+            let special = map.tile[p].special;
+            if p == Vec2d(1, 1) {
+                assert!(special.is_none());
+            } else if p.0 == 8 {
+                assert_eq!(special, SpecialTileKind::Door { lock_level: 0 });
+            } else {
+                assert_eq!(special, trap);
+            }
+        }
+    }
+
+    #[test]
+    fn choose_known_prefab() {
+        test_util::init();
+        let mut rule = rule();
+        rule.prefab_probability = 1.0;
+        rule.prefabs = vec![("unknown-prefab".into(), 1.0)];
+        assert_eq!(choose_prefab(&rule), None);
+
+        rule.prefabs = vec![(PREFAB_ID.into(), 1.0)];
+        assert_eq!(choose_prefab(&rule), gobj::id_to_idx_checked(PREFAB_ID));
+    }
+
+    #[test]
+    fn skip_prefab_npcs_on_invalid_tiles() {
+        let (mut gd, monster) = test_util::fixture(Vec2d(12, 12));
+        let mid = gd.get_current_mapid();
+        gd.get_current_map_mut()
+            .set_wall(Vec2d(5, 5), gobj::id_to_idx(WALL_ID));
+        let faction = FactionId::new("monster").unwrap();
+        let t = gobj::get_by_id(PREFAB_ID);
+        gen_prefab_npcs(&mut gd, mid, t, Vec2d(4, 4), faction);
+
+        let map = gd.get_current_map();
+        assert_eq!(map.iter_charaid().count(), 3);
+        let cid = map.tile[Vec2d(4, 4)].chara.unwrap();
+        assert_eq!(gd.chara.get(cid).faction, faction);
+        assert!(map.tile[Vec2d(5, 5)].chara.is_none());
+        assert!(map.tile[Vec2d(4, 5)].chara.is_none());
+        assert_eq!(map.tile[Vec2d(12, 12)].chara, Some(monster));
+    }
+
+    #[test]
+    fn check_separated_floor() {
+        test_util::init();
+        let mut map = map();
+        map.entrance.push(Vec2d(0, 0));
+        assert_eq!(check_connectivity(&map, 0), Ok(()));
+
+        for y in 0..16 {
+            map.set_wall(Vec2d(8, y), gobj::id_to_idx(WALL_ID));
+        }
+        assert_eq!(
+            check_connectivity(&map, 0),
+            Err(ValidationError::Disconnected(2))
+        );
+
+        map.tile[Vec2d(8, 3)].special = SpecialTileKind::HiddenPassage { difficulty: 1 };
+        assert_eq!(check_connectivity(&map, 0), Ok(()));
+    }
+
+    #[test]
+    fn gen_valid_floor() {
+        test_util::init();
+        let mut rule = rule();
+        rule.hidden_passage_probability = 0.5;
+        rule.locked_door_probability = 0.5;
+        for _ in 0..10 {
+            let (map, prefab) = gen_floor(&rule, 2, false);
+            assert_eq!(check_connectivity(&map, 2), Ok(()));
+            assert!(map.search_stairs(3).is_some());
+            assert_eq!(prefab, None);
+        }
+    }
+}
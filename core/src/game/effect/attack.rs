@@ -1,13 +1,14 @@
 use crate::config::changeable::game_log_cfg;
 use crate::game::damage::*;
-use crate::game::extrait::CharaEx;
-use crate::game::Game;
+use crate::game::extrait::{CharaEx, ItemEx};
+use crate::game::stealth;
+use crate::game::{Game, InfoGetter};
 use crate::rng;
 use common::gamedata::*;
 use common::gobj;
 use geom::ShapeKind;
-use rng::Rng;
-// use rules::RULES;
+use rng::{Rng, RngStream};
+use rules::RULES;
 
 #[derive(Clone, Copy)]
 struct AttackParams {
@@ -77,14 +78,14 @@ pub fn ranged_attack(
 // Explosion attack to a chara.
 pub fn explosion_attack(
     game: &mut Game,
-    cid: CharaId,
+    cid: Option<CharaId>,
     target_id: CharaId,
     attack_power: f32,
     hit_power: f32,
     element: Element,
 ) {
     let attack_params = AttackParams {
-        attacker_id: Some(cid),
+        attacker_id: cid,
         kind: CharaDamageKind::Explosion,
         element,
         attack_power,
@@ -96,7 +97,22 @@ pub fn explosion_attack(
 }
 
 /// Routines for targetted character
-fn attack_target(game: &mut Game, attack_params: AttackParams, target_id: CharaId) -> i32 {
+fn attack_target(game: &mut Game, mut attack_params: AttackParams, target_id: CharaId) -> i32 {
+    if let Some(attacker_id) = attack_params.attacker_id {
+        if stealth::is_exposed_to(&game.gd, target_id, attacker_id) {
+            sneak_attack(game, &mut attack_params, attacker_id, target_id);
+        }
+        stealth::alert(&mut game.gd, target_id, attacker_id);
+    }
+    if let Some(pos) = game.gd.chara_pos(target_id) {
+        stealth::make_noise(
+            &mut game.gd,
+            attack_params.attacker_id,
+            pos,
+            RULES.stealth.noise_combat,
+        );
+    }
+
     if !attack_params.always_hit
         && !hit_judge(
             &mut game.gd,
@@ -110,7 +126,6 @@ fn attack_target(game: &mut Game, attack_params: AttackParams, target_id: CharaI
 
     let equip_def = calc_equip_defence(&game.gd, target_id);
     let target = game.gd.chara.get_mut(target_id);
-    let idx = target.template;
     let defence_skill_level = target.skills.get(SkillKind::Defence);
     let defence_power = calc_defence_power(
         equip_def[attack_params.element],
@@ -129,13 +144,79 @@ fn attack_target(game: &mut Game, attack_params: AttackParams, target_id: CharaI
             let target = game.gd.chara.get_mut(target_id);
             target.add_damage_exp(damage, attacker_level);
         }
-    } else {
-        crate::game::quest::count_slayed_monster(&mut game.gd, idx);
+    }
+
+    if hp >= 0 {
+        if let Some(attacker_id) = attack_params.attacker_id {
+            weapon_affix_effects(game, &attack_params, attacker_id, target_id);
+        }
     }
 
     damage
 }
 
+/// Additional elemental damage and status effects by affixes of the attacker's weapon
+fn weapon_affix_effects(
+    game: &mut Game,
+    attack_params: &AttackParams,
+    attacker_id: CharaId,
+    target_id: CharaId,
+) {
+    let slot = match attack_params.kind {
+        CharaDamageKind::MeleeAttack => EquipSlotKind::MeleeWeapon,
+        CharaDamageKind::RangedAttack => EquipSlotKind::RangedWeapon,
+        _ => return,
+    };
+    let affixes = if let Some(weapon) = game.gd.get_equip_list(attacker_id).item(slot, 0) {
+        weapon.affixes()
+    } else {
+        return;
+    };
+
+    for (kind, value) in affixes {
+        match kind {
+            AffixKind::ElementalDamage(element) => {
+                let equip_def = calc_equip_defence(&game.gd, target_id);
+                let target = game.gd.chara.get(target_id);
+                let defence_power = calc_defence_power(
+                    equip_def[element],
+                    target.attr.vit,
+                    target.skills.get(SkillKind::Defence),
+                );
+                let power = attack_params.attack_power * value as f32 / 100.0;
+                let damage = (power / defence_power).floor() as i32;
+                if damage > 0 && do_damage(game, target_id, damage, attack_params.kind) < 0 {
+                    return;
+                }
+            }
+            AffixKind::OnHitStatus(status) => {
+                let p = value as f32 / 100.0;
+                if rng::with_stream(RngStream::Combat, || rng::gen_bool(p)) {
+                    super::cause_status(game, target_id, RULES.item.on_hit_status_power, status);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Attacks to unaware targets get a damage bonus
+fn sneak_attack(
+    game: &mut Game,
+    attack_params: &mut AttackParams,
+    attacker_id: CharaId,
+    target_id: CharaId,
+) {
+    attack_params.attack_power *= RULES.stealth.sneak_attack_factor;
+    game_log!("sneak-attack"; attacker=game.gd.chara.get(attacker_id), target=game.gd.chara.get(target_id));
+
+    let target_level = game.gd.chara.get(target_id).level;
+    game.gd
+        .chara
+        .get_mut(attacker_id)
+        .add_stealth_exp(target_level);
+}
+
 /// Calculate character's defence for each elements
 fn calc_equip_defence(gd: &GameData, cid: CharaId) -> ElementArray<u16> {
     let mut def: ElementArray<u16> = ElementArray::default();
@@ -144,6 +225,7 @@ fn calc_equip_defence(gd: &GameData, cid: CharaId) -> ElementArray<u16> {
         let item_obj: &ItemObject = gobj::get_obj(item.idx);
         for e in &ELEMENTS {
             def[*e] = def[*e].saturating_add(item_obj.def[*e]);
+            def[*e] = def[*e].saturating_add(item.affix_value(AffixKind::Defence(*e)));
         }
     }
 
@@ -194,7 +276,7 @@ fn hit_judge(
 
     let d = accuracy_power - evasion_power;
     let p = 1.0 / (1.0 + (-d * 0.125).exp());
-    let is_hit = rng::get_rng().gen_bool(p.into());
+    let is_hit = rng::with_stream(RngStream::Combat, || rng::get_rng().gen_bool(p.into()));
 
     if !is_hit && game_log_cfg().combat_log.attack() {
         game_log!("attack-evade"; chara=gd.chara.get(target_id));
@@ -245,11 +327,17 @@ pub fn weapon_to_effect(item: &Item) -> Effect {
         EffectAnimKind::Shot
     };
 
+    let range = if weapon_kind.is_melee() {
+        1
+    } else {
+        RULES.combat.ranged_weapon_range
+    };
+
     Effect {
         kind: effect_kind,
         target_mode: TargetMode::Enemy,
         power_adjust: vec![],
-        range: 1,
+        range,
         shape: ShapeKind::OneTile,
         size: 0,
         anim_kind,
@@ -258,3 +346,63 @@ pub fn weapon_to_effect(item: &Item) -> Effect {
         sound,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util;
+    use geom::Vec2d;
+
+    #[test]
+    fn affix_defence() {
+        let (mut gd, cid) = test_util::fixture(Vec2d(3, 2));
+        let chara = gd.chara.get_mut(cid);
+        chara.equip = EquipItemList::new(&[EquipSlotKind::BodyArmor]);
+        let armor = test_util::equipment(
+            test_util::ARMOR_ID,
+            &[
+                (AffixKind::Defence(Element::Fire), 7),
+                (AffixKind::Defence(Element::Fire), 3),
+                (AffixKind::Defence(Element::Cold), 2),
+            ],
+        );
+        chara.equip.equip(EquipSlotKind::BodyArmor, 0, armor);
+
+        let def = calc_equip_defence(&gd, cid);
+        assert_eq!(def[Element::Physical], 5);
+        assert_eq!(def[Element::Fire], 15);
+        assert_eq!(def[Element::Cold], 7);
+    }
+
+    #[test]
+    fn elemental_damage_by_weapon_affix() {
+        let _lock = test_util::lock();
+        let (mut gd, cid) = test_util::fixture(Vec2d(3, 2));
+        gd.chara.get_mut(cid).attr.vit = 10;
+        let player = gd.chara.get_mut(CharaId::Player);
+        player.equip = EquipItemList::new(&[EquipSlotKind::MeleeWeapon]);
+        let sword = test_util::equipment(
+            test_util::SWORD_ID,
+            &[(AffixKind::ElementalDamage(Element::Fire), 50)],
+        );
+        player.equip.equip(EquipSlotKind::MeleeWeapon, 0, sword);
+        let mut game = test_util::game(gd);
+
+        // Defence power is (0 + 16) * 10 * (0 + 8) = 1280
+        let mut attack_params = AttackParams {
+            attacker_id: Some(CharaId::Player),
+            kind: CharaDamageKind::MeleeAttack,
+            element: Element::Physical,
+            attack_power: 25600.0,
+            hit_power: 0.0,
+            always_hit: true,
+        };
+        weapon_affix_effects(&mut game, &attack_params, CharaId::Player, cid);
+        assert_eq!(game.gd.chara.get(cid).hp, 90);
+
+        // The weapon is not used for explosions
+        attack_params.kind = CharaDamageKind::Explosion;
+        weapon_affix_effects(&mut game, &attack_params, CharaId::Player, cid);
+        assert_eq!(game.gd.chara.get(cid).hp, 90);
+    }
+}
@@ -8,6 +8,7 @@ pub use attack::weapon_to_effect;
 pub use range::*;
 
 use crate::game::extrait::CharaStatusOperation;
+use crate::game::item::identify;
 use crate::game::target::Target;
 use crate::game::{Animation, Game, InfoGetter};
 use common::gamedata::*;
@@ -61,14 +62,7 @@ pub fn do_effect<T: Into<Target>>(
             }
             EffectKind::Explosion { element } => {
                 for cid in &cids {
-                    self::attack::explosion_attack(
-                        game,
-                        cause.unwrap(),
-                        *cid,
-                        power,
-                        hit_power,
-                        *element,
-                    );
+                    self::attack::explosion_attack(game, cause, *cid, power, hit_power, *element);
                 }
             }
             EffectKind::Status { status } => {
@@ -89,6 +83,16 @@ pub fn do_effect<T: Into<Target>>(
                     self::skill_learn::skill_learn(game, *cid, skills);
                 }
             }
+            EffectKind::Identify => {
+                if let Some(cause) = cause {
+                    identify::identify_all(&mut game.gd, cause);
+                }
+            }
+            EffectKind::RemoveCurse => {
+                if let Some(cause) = cause {
+                    identify::remove_curse(&mut game.gd, cause);
+                }
+            }
             other => {
                 error!("unimplemented effect: {:?}", other);
             }
@@ -132,7 +136,7 @@ pub fn do_effect<T: Into<Target>>(
     }
     // Sound
     if !effect.sound.is_empty() {
-        audio::play_sound(&effect.sound);
+        crate::audio::play_sound(&effect.sound);
     }
 }
 
@@ -1,21 +1,26 @@
 use common::gamedata::*;
 use common::gobj;
-use common::script::{Expr, ExprErrorKind, Operator, Value};
+use common::hashmap::HashMap;
+use common::script::{CharaRef, Expr, ExprErrorKind, Operator, Value};
+
+/// Local variables in a script scope
+pub type Locals = HashMap<String, Value>;
 
 pub trait EvalExpr {
-    fn eval(&self, gd: &GameData) -> Value;
+    /// Evaluate the expression. `cid` is the character talking with the player.
+    fn eval(&self, gd: &GameData, cid: Option<CharaId>, locals: &Locals) -> Value;
 }
 
 impl EvalExpr for Expr {
-    fn eval(&self, gd: &GameData) -> Value {
+    fn eval(&self, gd: &GameData, cid: Option<CharaId>, locals: &Locals) -> Value {
         match self {
             Expr::Value(value) => value.clone(),
             Expr::Term(v) => {
-                let mut a = v[0].1.eval(gd);
+                let mut a = v[0].1.eval(gd, cid, locals);
                 assert_eq!(v[0].0, Operator::None);
 
                 for (o, b) in v.iter().skip(1) {
-                    let b = b.eval(gd);
+                    let b = b.eval(gd, cid, locals);
                     a = binary_operation(*o, a, b);
                 }
                 a
@@ -27,9 +32,16 @@ impl EvalExpr for Expr {
                     Value::RefUnknownVar
                 }
             }
+            Expr::LVar(var_name) => {
+                if let Some(v) = locals.get(var_name) {
+                    v.clone()
+                } else {
+                    Value::RefUnknownVar
+                }
+            }
             Expr::IsGVarEmpty(var_name) => Value::Bool(gd.vars.global_var(var_name).is_none()),
             Expr::CurrentTime => Value::Time(gd.time.current_time()),
-            Expr::DurationHour(a, b) => match (a.eval(gd), b.eval(gd)) {
+            Expr::DurationHour(a, b) => match (a.eval(gd, cid, locals), b.eval(gd, cid, locals)) {
                 (Value::Time(a), Value::Time(b)) => Value::Int(a.duration_from(b).as_hours()),
                 _ => Value::Error(ExprErrorKind::InvalidType),
             },
@@ -49,6 +61,59 @@ impl EvalExpr for Expr {
                     Value::Error(ExprErrorKind::UnknownIdRef)
                 }
             }
+            Expr::PlayerAttr(kind) => {
+                Value::Int(gd.chara.get(CharaId::Player).attr.get(*kind).into())
+            }
+            Expr::SkillLevel(kind) => {
+                Value::Int(gd.chara.get(CharaId::Player).skills.get(*kind) as i32)
+            }
+            Expr::FactionRelation(faction) => Value::Int(gd.faction.get(*faction).into()),
+            Expr::QuestCount(state) => {
+                Value::Int(gd.quest.iter().filter(|(s, _)| s == state).count() as i32)
+            }
+            Expr::InSite(id) => {
+                let mid = gd.get_current_mapid();
+                if mid.is_region_map() {
+                    return Value::Bool(false);
+                }
+                Value::Bool(gd.region.get_site(mid.sid()).id.as_ref() == Some(id))
+            }
+            Expr::CurrentFloor => {
+                let mid = gd.get_current_mapid();
+                if mid.is_region_map() {
+                    Value::Int(-1)
+                } else {
+                    Value::Int(mid.floor() as i32)
+                }
+            }
+            Expr::Hp(chara) => match chara_ref(*chara, cid) {
+                Some(cid) => Value::Int(gd.chara.get(cid).hp),
+                None => Value::Error(ExprErrorKind::UnknownIdRef),
+            },
+            Expr::MaxHp(chara) => match chara_ref(*chara, cid) {
+                Some(cid) => Value::Int(gd.chara.get(cid).attr.max_hp),
+                None => Value::Error(ExprErrorKind::UnknownIdRef),
+            },
+            Expr::QuestStage(quest_id, stage_id) => {
+                let quest = gd.quest.story_quest(quest_id);
+                Value::Bool(quest.and_then(|q| q.current_stage()) == Some(stage_id.as_str()))
+            }
+            Expr::QuestState(quest_id, state) => {
+                let quest = gd.quest.story_quest(quest_id);
+                Value::Bool(quest.map(|q| q.state) == Some(*state))
+            }
+        }
+    }
+}
+
+pub fn chara_ref(chara: CharaRef, talker: Option<CharaId>) -> Option<CharaId> {
+    match chara {
+        CharaRef::Player => Some(CharaId::Player),
+        CharaRef::Talker => {
+            if talker.is_none() {
+                warn!("script error: no talking character");
+            }
+            talker
         }
     }
 }
@@ -0,0 +1,189 @@
+//! Field of view calculation by symmetric shadowcasting.
+//! A tile is visible from another tile if and only if the reverse is true.
+
+use geom::*;
+
+/// Slope represented by a fraction to avoid rounding errors
+#[derive(Clone, Copy, Debug)]
+struct Slope {
+    num: i32,
+    den: i32,
+}
+
+impl Slope {
+    fn new(num: i32, den: i32) -> Slope {
+        Slope { num, den }
+    }
+
+    /// Slope of the left edge of given tile
+    fn of_tile(depth: i32, col: i32) -> Slope {
+        Slope::new(2 * col - 1, 2 * depth)
+    }
+}
+
+/// Tiles in the same distance from the origin in a quadrant
+#[derive(Clone, Copy, Debug)]
+struct Row {
+    depth: i32,
+    start: Slope,
+    end: Slope,
+}
+
+impl Row {
+    fn min_col(&self) -> i32 {
+        // Round ties up
+        div_floor(
+            2 * self.depth * self.start.num + self.start.den,
+            2 * self.start.den,
+        )
+    }
+
+    fn max_col(&self) -> i32 {
+        // Round ties down
+        div_ceil(
+            2 * self.depth * self.end.num - self.end.den,
+            2 * self.end.den,
+        )
+    }
+
+    fn next(&self) -> Row {
+        Row {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+
+    /// Floor tiles are visible only if the center is in the row sector
+    fn is_symmetric(&self, col: i32) -> bool {
+        col * self.start.den >= self.depth * self.start.num
+            && col * self.end.den <= self.depth * self.end.num
+    }
+}
+
+/// Calculate visible tiles from the origin in given range.
+/// `mark_visible` may be called more than once for the same tile.
+pub fn fov<B, M>(origin: Vec2d, range: i32, is_blocking: B, mut mark_visible: M)
+where
+    B: Fn(Vec2d) -> bool,
+    M: FnMut(Vec2d),
+{
+    mark_visible(origin);
+
+    let quadrants: [fn(Vec2d, i32, i32) -> Vec2d; 4] = [
+        |o, depth, col| Vec2d(o.0 + col, o.1 - depth),
+        |o, depth, col| Vec2d(o.0 + col, o.1 + depth),
+        |o, depth, col| Vec2d(o.0 + depth, o.1 + col),
+        |o, depth, col| Vec2d(o.0 - depth, o.1 + col),
+    ];
+
+    for transform in &quadrants {
+        let is_wall = |depth: i32, col: i32| is_blocking(transform(origin, depth, col));
+
+        let mut rows = vec![Row {
+            depth: 1,
+            start: Slope::new(-1, 1),
+            end: Slope::new(1, 1),
+        }];
+
+        while let Some(mut row) = rows.pop() {
+            if row.depth > range {
+                continue;
+            }
+            let mut prev_is_wall = None;
+
+            for col in row.min_col()..=row.max_col() {
+                let wall = is_wall(row.depth, col);
+                if wall || row.is_symmetric(col) {
+                    mark_visible(transform(origin, row.depth, col));
+                }
+                if prev_is_wall == Some(true) && !wall {
+                    row.start = Slope::of_tile(row.depth, col);
+                }
+                if prev_is_wall == Some(false) && wall {
+                    let mut next_row = row.next();
+                    next_row.end = Slope::of_tile(row.depth, col);
+                    rows.push(next_row);
+                }
+                prev_is_wall = Some(wall);
+            }
+
+            if prev_is_wall == Some(false) {
+                rows.push(row.next());
+            }
+        }
+    }
+}
+
+fn div_floor(a: i32, b: i32) -> i32 {
+    let d = a / b;
+    if a % b != 0 && (a < 0) != (b < 0) {
+        d - 1
+    } else {
+        d
+    }
+}
+
+fn div_ceil(a: i32, b: i32) -> i32 {
+    let d = a / b;
+    if a % b != 0 && (a < 0) == (b < 0) {
+        d + 1
+    } else {
+        d
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Vec2d = Vec2d(16, 16);
+
+    fn visible_tiles(walls: &[Vec2d], origin: Vec2d, range: i32) -> Array2d<bool> {
+        let mut visible = Array2d::new(SIZE.0 as u32, SIZE.1 as u32, false);
+        let is_blocking =
+            |p: Vec2d| p.0 < 0 || p.1 < 0 || p.0 >= SIZE.0 || p.1 >= SIZE.1 || walls.contains(&p);
+        fov(origin, range, is_blocking, |p| {
+            if visible.in_range(p) {
+                visible[p] = true;
+            }
+        });
+        visible
+    }
+
+    #[test]
+    fn blocked_by_wall() {
+        let visible = visible_tiles(&[Vec2d(4, 2)], Vec2d(6, 2), 8);
+        assert!(visible[Vec2d(5, 2)]);
+        assert!(visible[Vec2d(4, 2)]);
+        assert!(!visible[Vec2d(3, 2)]);
+        assert!(!visible[Vec2d(2, 2)]);
+        assert!(visible[Vec2d(2, 5)]);
+    }
+
+    #[test]
+    fn symmetric() {
+        let walls = [
+            Vec2d(3, 3),
+            Vec2d(4, 3),
+            Vec2d(7, 5),
+            Vec2d(8, 8),
+            Vec2d(9, 8),
+            Vec2d(5, 10),
+            Vec2d(11, 4),
+        ];
+        let floor: Vec<Vec2d> = SIZE
+            .iter_from_zero()
+            .filter(|p| !walls.contains(p))
+            .collect();
+        let visible: Vec<Array2d<bool>> = floor
+            .iter()
+            .map(|p| visible_tiles(&walls, *p, 32))
+            .collect();
+
+        for (i, a) in floor.iter().enumerate() {
+            for (j, b) in floor.iter().enumerate() {
+                assert_eq!(visible[i][*b], visible[j][*a], "{:?} and {:?}", a, b);
+            }
+        }
+    }
+}
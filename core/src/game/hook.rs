@@ -0,0 +1,105 @@
+//! Scripts started by game events.
+//! Hooks are declared in site generation objects, map templates and character templates.
+
+use super::Game;
+use common::gamedata::*;
+use common::gobj;
+use common::maptemplate::MapTemplateObject;
+use common::script::{ScriptHook, Trigger};
+use common::sitegen::SiteGenObject;
+
+/// Queue scripts hooked to the trigger on the current map and site
+pub fn trigger(game: &mut Game, trigger: &Trigger) {
+    let scripts: Vec<String> = matched_scripts(map_hooks(&game.gd), trigger)
+        .chain(matched_scripts(site_hooks(&game.gd), trigger))
+        .collect();
+    for script_id in scripts {
+        game.queue_script(script_id, None);
+    }
+}
+
+/// Queue enter scripts after the player moved from the previous map.
+/// Enter scripts of the site are started only when the player comes from outside of the site.
+pub fn enter(game: &mut Game, prev_mid: MapId) {
+    let mid = game.gd.get_current_mapid();
+    let entered_site =
+        !mid.is_region_map() && (prev_mid.is_region_map() || prev_mid.sid() != mid.sid());
+    let site_hooks = if entered_site {
+        site_hooks(&game.gd)
+    } else {
+        &[]
+    };
+    let scripts: Vec<String> = matched_scripts(map_hooks(&game.gd), &Trigger::Enter)
+        .chain(matched_scripts(site_hooks, &Trigger::Enter))
+        .collect();
+    for script_id in scripts {
+        game.queue_script(script_id, None);
+    }
+}
+
+/// Queue scripts hooked to the trigger in the character's template
+pub fn trigger_chara(game: &mut Game, cid: CharaId, trigger: &Trigger) {
+    let ct = gobj::get_obj(game.gd.chara.get(cid).template);
+    let scripts: Vec<String> = matched_scripts(&ct.hooks, trigger).collect();
+    for script_id in scripts {
+        game.queue_script(script_id, None);
+    }
+}
+
+fn map_hooks(gd: &GameData) -> &'static [ScriptHook] {
+    gd.get_current_map()
+        .template_id
+        .as_ref()
+        .and_then(|id| gobj::get_by_id_checked::<MapTemplateObject>(id))
+        .map(|t| t.hooks.as_ref())
+        .unwrap_or(&[])
+}
+
+fn site_hooks(gd: &GameData) -> &'static [ScriptHook] {
+    let mid = gd.get_current_mapid();
+    if mid.is_region_map() {
+        return &[];
+    }
+    gd.region
+        .get_site(mid.sid())
+        .id
+        .as_ref()
+        .and_then(|id| gobj::get_by_id_checked::<SiteGenObject>(id))
+        .map(|sg| sg.hooks.as_ref())
+        .unwrap_or(&[])
+}
+
+fn matched_scripts<'a>(
+    hooks: &'a [ScriptHook],
+    trigger: &'a Trigger,
+) -> impl Iterator<Item = String> + 'a {
+    hooks
+        .iter()
+        .filter(move |hook| hook.trigger == *trigger)
+        .map(|hook| hook.script_id.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geom::Vec2d;
+
+    #[test]
+    fn match_hooks() {
+        let hook = |trigger: Trigger, script_id: &str| ScriptHook {
+            trigger,
+            script_id: script_id.to_owned(),
+        };
+        let hooks = vec![
+            hook(Trigger::Enter, "a"),
+            hook(Trigger::Step(Vec2d(3, 4)), "b"),
+            hook(Trigger::Enter, "c"),
+            hook(Trigger::Hour(6), "d"),
+        ];
+        let matched = |trigger: Trigger| matched_scripts(&hooks, &trigger).collect::<Vec<_>>();
+        assert_eq!(matched(Trigger::Enter), vec!["a", "c"]);
+        assert_eq!(matched(Trigger::Step(Vec2d(3, 4))), vec!["b"]);
+        assert!(matched(Trigger::Step(Vec2d(4, 3))).is_empty());
+        assert!(matched(Trigger::Hour(7)).is_empty());
+    }
+}
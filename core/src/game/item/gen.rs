@@ -2,7 +2,8 @@ use common::gamedata::*;
 use common::gobj;
 use common::obj::ImgVariationRule;
 use common::objholder::ItemIdx;
-use rng::SliceRandom;
+use rng::{RngStream, SliceRandom};
+use rules::item::AffixRule;
 use rules::RULES;
 
 /// Generate new item on dungeon floor
@@ -29,7 +30,9 @@ pub fn gen_item_by_level<F: FnMut(&ItemObject) -> f32>(
     f: F,
     is_shop: bool,
 ) -> Option<Item> {
-    choose_item_by_floor_level(level, f, is_shop).map(|idx| gen_item_from_idx(idx, level))
+    rng::with_stream(RngStream::Loot, || {
+        choose_item_by_floor_level(level, f, is_shop).map(|idx| gen_item_from_idx(idx, level))
+    })
 }
 
 /// Choose item by floor level.
@@ -115,6 +118,10 @@ pub fn gen_item_from_id(id: &str, level: u32) -> Item {
 }
 
 pub fn gen_item_from_idx(idx: ItemIdx, level: u32) -> Item {
+    rng::with_stream(RngStream::Loot, || gen_item(idx, level))
+}
+
+fn gen_item(idx: ItemIdx, level: u32) -> Item {
     let item_obj = gobj::get_obj(idx);
 
     let mut item = Item {
@@ -123,6 +130,7 @@ pub fn gen_item_from_idx(idx: ItemIdx, level: u32) -> Item {
         kind: item_obj.kind,
         quality: ItemQuality::default(),
         attributes: vec![],
+        bless: BlessState::Normal,
     };
 
     // Set image variation.
@@ -154,6 +162,8 @@ pub fn gen_item_from_idx(idx: ItemIdx, level: u32) -> Item {
 
     set_quality(&mut item, item_obj, level);
     set_material(&mut item, item_obj, level);
+    set_affixes(&mut item, level);
+    set_bless(&mut item);
 
     item
 }
@@ -203,6 +213,55 @@ fn set_quality(item: &mut Item, item_obj: &ItemObject, level: u32) {
     }
 }
 
+/// Add random affixes. Affixes with higher values are available on higher levels.
+fn set_affixes(item: &mut Item, level: u32) {
+    let kind = item.kind.rough();
+    let mut candidates: Vec<&AffixRule> = RULES
+        .item
+        .affixes
+        .iter()
+        .filter(|rule| rule.item_kinds.contains(&kind) && rule.min_level <= level)
+        .collect();
+
+    for _ in 0..RULES.item.max_affixes {
+        if !rng::gen_bool(RULES.item.affix_prob) {
+            break;
+        }
+        let rule = if let Some(rule) = rng::choose(&candidates, |rule| rule.gen_weight) {
+            rule
+        } else {
+            break;
+        };
+        let max_value = affix_max_value(rule, level);
+        let value = rng::gen_range_inclusive(max_value.div_ceil(2), max_value);
+        item.attributes.push(ItemAttribute::Affix {
+            kind: rule.kind,
+            value,
+        });
+        candidates.retain(|candidate| candidate.kind != rule.kind);
+    }
+}
+
+fn affix_max_value(rule: &AffixRule, level: u32) -> u16 {
+    let value = rule.base_value + rule.value_per_level * (level - rule.min_level) as f32;
+    (value as u16).max(1).min(rule.max_value)
+}
+
+/// Items that can be unidentified are blessed or cursed randomly
+fn set_bless(item: &mut Item) {
+    if !super::identify::is_generated_unidentified(item.kind) {
+        return;
+    }
+    item.flags |= ItemFlags::UNIDENTIFIED;
+    item.bless = if rng::gen_bool(RULES.item.blessed_prob) {
+        BlessState::Blessed
+    } else if rng::gen_bool(RULES.item.cursed_prob) {
+        BlessState::Cursed
+    } else {
+        BlessState::Normal
+    };
+}
+
 fn set_material(item: &mut Item, item_obj: &ItemObject, level: u32) {
     if item_obj.material_group.is_empty() {
         return;
@@ -219,3 +278,53 @@ fn set_material(item: &mut Item, item_obj: &ItemObject, level: u32) {
     };
     item.attributes.push(ItemAttribute::Material(material_name));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::extrait::ItemEx;
+
+    #[test]
+    fn affix_value_scales_with_level() {
+        let rule = AffixRule {
+            kind: AffixKind::Attr(CharaAttrKind::Str),
+            item_kinds: vec![ItemKindRough::Weapon],
+            min_level: 5,
+            gen_weight: 1.0,
+            base_value: 2.0,
+            value_per_level: 0.5,
+            max_value: 10,
+        };
+        assert_eq!(affix_max_value(&rule, 5), 2);
+        assert_eq!(affix_max_value(&rule, 9), 4);
+        assert_eq!(affix_max_value(&rule, 100), 10);
+    }
+
+    #[test]
+    fn affixes_by_item_kind_and_level() {
+        crate::game::test_util::init();
+        let sword: ItemIdx = gobj::id_to_idx(crate::game::test_util::SWORD_ID);
+        let level = 3;
+
+        for _ in 0..200 {
+            let mut item = gen_item_from_idx(sword, level);
+            item.attributes.clear();
+            set_affixes(&mut item, level);
+
+            let affixes = item.affixes();
+            assert!(affixes.len() <= RULES.item.max_affixes as usize);
+            for (i, (kind, value)) in affixes.iter().enumerate() {
+                let rule = RULES
+                    .item
+                    .affixes
+                    .iter()
+                    .find(|rule| rule.kind == *kind)
+                    .unwrap();
+                assert!(rule.item_kinds.contains(&ItemKindRough::Weapon));
+                assert!(rule.min_level <= level);
+                assert!(*value >= 1 && *value <= affix_max_value(rule, level));
+                assert!(affixes[..i].iter().all(|(k, _)| k != kind));
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Item identification.
+//!
+//! Item kinds that have appearances in rules are identified by kind,
+//! and shown by their appearances shuffled for each game until identified.
+//! Quality and blessed state are hidden for each item by `ItemFlags::UNIDENTIFIED`.
+
+use crate::game::extrait::*;
+use crate::text::ItemName;
+use common::gamedata::*;
+use common::gobj;
+use common::objholder::ItemIdx;
+use rng::SliceRandom;
+use rules::RULES;
+
+fn has_appearance(idx: ItemIdx) -> bool {
+    let kind = gobj::get_obj(idx).kind.rough();
+    RULES.item.appearances.contains_key(&kind)
+}
+
+/// The player knows the name of this item kind
+pub fn is_kind_identified(knowledge: &ItemKnowledge, idx: ItemIdx) -> bool {
+    !has_appearance(idx) || knowledge.is_identified(idx)
+}
+
+/// The player knows the name of this item.
+/// Items identified one by one are known even if their kinds are not identified.
+pub fn is_name_known(knowledge: &ItemKnowledge, item: &Item) -> bool {
+    !item.flags.contains(ItemFlags::UNIDENTIFIED) || is_kind_identified(knowledge, item.idx)
+}
+
+/// The player knows the quality and blessed state of this item
+pub fn is_identified(item: &Item) -> bool {
+    !item.flags.contains(ItemFlags::UNIDENTIFIED)
+}
+
+/// Identify the item kind. Returns true if it is newly identified.
+pub fn identify_kind(knowledge: &mut ItemKnowledge, idx: ItemIdx) -> bool {
+    has_appearance(idx) && knowledge.identify(idx)
+}
+
+/// Identify the item and its kind. Returns true if something new is revealed.
+pub fn identify(knowledge: &mut ItemKnowledge, item: &mut Item) -> bool {
+    let unidentified = item.flags.contains(ItemFlags::UNIDENTIFIED);
+    item.flags.remove(ItemFlags::UNIDENTIFIED);
+    identify_kind(knowledge, item.idx) || unidentified
+}
+
+/// Using an item reveals its kind to the player
+pub fn identify_by_use(gd: &mut GameData, item: &Item, cid: CharaId) {
+    if cid == CharaId::Player && identify_kind(&mut gd.item_knowledge, item.idx) {
+        game_log_i!("item-kind-identified"; item=ItemName::new(gd, item));
+    }
+}
+
+/// Text id of the appearance of an unidentified item kind
+pub fn appearance(knowledge: &ItemKnowledge, idx: ItemIdx) -> Option<&'static str> {
+    let obj = gobj::get_obj(idx);
+    let kind = obj.kind.rough();
+    let appearances = RULES.item.appearances.get(&kind)?;
+    if appearances.is_empty() {
+        return None;
+    }
+    // Items of the same kind get different appearances while the appearances last
+    let n = gobj::get_objholder()
+        .item
+        .iter()
+        .filter(|o| o.kind.rough() == kind && o.id < obj.id)
+        .count();
+    let order = appearance_order(knowledge.appearance_seed, kind, appearances.len());
+    Some(&appearances[order[n % order.len()]])
+}
+
+fn appearance_order(seed: u64, kind: ItemKindRough, len: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).collect();
+    order.shuffle(&mut rng::seeded(seed ^ kind as u64));
+    order
+}
+
+/// Items of these kinds are generated unidentified
+pub fn is_generated_unidentified(kind: ItemKind) -> bool {
+    RULES.item.unidentified_kinds.contains(&kind.rough())
+}
+
+/// Try to identify an item picked up by the player by Appraisal skill
+pub fn appraise(gd: &mut GameData, item: &mut Item) {
+    if is_identified(item) {
+        return;
+    }
+    let gen_level = item.obj().gen_level;
+    let chara = gd.chara.get_mut(CharaId::Player);
+    let skill_level = chara.skills.get(SkillKind::Appraisal);
+    if !rng::gen_bool(appraisal_probability(skill_level, gen_level)) {
+        return;
+    }
+    chara.add_skill_exp(SkillKind::Appraisal, RULES.exp.appraisal, gen_level);
+    identify(&mut gd.item_knowledge, item);
+    game_log_i!("item-appraised"; item=ItemName::new(gd, item));
+}
+
+fn appraisal_probability(skill_level: u32, gen_level: u32) -> f32 {
+    let skill_level = skill_level as f32 + 1.0;
+    skill_level / (skill_level + (gen_level + RULES.item.appraisal_difficulty) as f32)
+}
+
+/// Identify all items the character has
+pub fn identify_all(gd: &mut GameData, cid: CharaId) {
+    let knowledge = &mut gd.item_knowledge;
+    let chara = gd.chara.get_mut(cid);
+    let mut n = update_items(&mut chara.item_list, |item| identify(knowledge, item));
+    for item in chara.equip.items_mut() {
+        if identify(knowledge, item) {
+            n += 1;
+        }
+    }
+    if n > 0 {
+        game_log_i!("item-identified-all"; chara=gd.chara.get(cid));
+    } else {
+        game_log_i!("item-identified-nothing");
+    }
+}
+
+/// Remove curses of all items the character has
+pub fn remove_curse(gd: &mut GameData, cid: CharaId) {
+    let uncurse = |item: &mut Item| {
+        if item.bless == BlessState::Cursed {
+            item.bless = BlessState::Normal;
+            true
+        } else {
+            false
+        }
+    };
+    let mut n = update_items(
+        gd.get_item_list_mut(ItemListLocation::Chara { cid }),
+        uncurse,
+    );
+    for item in gd.get_equip_list_mut(cid).items_mut() {
+        if uncurse(item) {
+            n += 1;
+        }
+    }
+    if n > 0 {
+        game_log_i!("curse-removed"; chara=gd.chara.get(cid));
+    }
+}
+
+/// Update items in the item list, and returns the number of updated items.
+/// Items are appended again because they may be stacked differently after updating.
+fn update_items<F: FnMut(&mut Item) -> bool>(il: &mut ItemList, mut f: F) -> u32 {
+    let items = std::mem::take(&mut il.items);
+    let mut n_updated = 0;
+    for (mut item, n) in items {
+        if f(&mut item) {
+            n_updated += n;
+        }
+        il.append(item, n);
+    }
+    n_updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appearances_are_shuffled_by_seed() {
+        let order = appearance_order(1, ItemKindRough::Potion, 12);
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..12).collect::<Vec<_>>());
+        assert_eq!(order, appearance_order(1, ItemKindRough::Potion, 12));
+        assert!((2..10).any(|seed| appearance_order(seed, ItemKindRough::Potion, 12) != order));
+    }
+
+    #[test]
+    fn appraisal() {
+        rules::init(concat!(env!("CARGO_MANIFEST_DIR"), "/../assets"), None);
+
+        assert!(appraisal_probability(10, 1) > appraisal_probability(0, 1));
+        assert!(appraisal_probability(10, 30) < appraisal_probability(10, 1));
+        assert!(appraisal_probability(1000, 1) < 1.0);
+    }
+
+    #[test]
+    fn knowledge_of_each_game() {
+        crate::game::test_util::init();
+        let potion: ItemIdx = gobj::id_to_idx(crate::game::test_util::POTION_ID);
+        let mut gd = GameData::empty();
+        let other = GameData::empty();
+        let mut item = crate::game::item::gen::gen_item_from_idx(potion, 1);
+        item.flags.insert(ItemFlags::UNIDENTIFIED);
+        assert!(!is_name_known(&gd.item_knowledge, &item));
+        assert!(appearance(&gd.item_knowledge, potion).is_some());
+
+        assert!(identify(&mut gd.item_knowledge, &mut item));
+        assert!(is_identified(&item));
+        assert!(is_kind_identified(&gd.item_knowledge, potion));
+        assert!(!identify_kind(&mut gd.item_knowledge, potion));
+        assert!(!is_kind_identified(&other.item_knowledge, potion));
+
+        // Kinds without appearances are always known
+        let key: ItemIdx = gobj::id_to_idx(&RULES.item.key_item_id);
+        assert!(is_kind_identified(&other.item_knowledge, key));
+        assert!(!identify_kind(&mut gd.item_knowledge, key));
+    }
+}
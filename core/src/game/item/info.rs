@@ -1,5 +1,6 @@
 use crate::game::extrait::ItemEx;
-use crate::text::ToText;
+use crate::game::item::identify;
+use crate::text::{misc_txt, ItemName, ToText};
 use common::gamedata::*;
 
 const UI_IMG_ID_ITEM_INFO: &str = "!icon-item-info";
@@ -12,10 +13,10 @@ pub struct ItemInfoText {
 }
 
 impl ItemInfoText {
-    pub fn new(item: &Item) -> ItemInfoText {
+    pub fn new(gd: &GameData, item: &Item) -> ItemInfoText {
         let obj = item.obj();
 
-        let item_name = item.to_text().into_owned();
+        let item_name = ItemName::new(gd, item).to_text().into_owned();
         let item_kind = misc_txt_format!("item_info_text-item_kind"; item_kind=obj.kind);
         let mut desc_text = Vec::new();
 
@@ -31,7 +32,7 @@ impl ItemInfoText {
             ItemKind::Throwing => {}
             ItemKind::MagicDevice => {}
             ItemKind::Weapon(weapon_kind) => {
-                let eff = item.calc_eff_without_var();
+                let eff = known_eff(item);
                 if weapon_kind.is_melee() {
                     let t = misc_txt_format!(
                         "item_info_text-melee_weapon"; eff=eff);
@@ -62,6 +63,14 @@ impl ItemInfoText {
             ItemKind::Object => {}
         }
 
+        if !identify::is_identified(item) {
+            let t = misc_txt("item_info_text-unidentified");
+            desc_text.push((UI_IMG_ID_ITEM_INFO, t));
+        } else if item.bless != BlessState::Normal {
+            let t = misc_txt_format!("item_info_text-bless_state"; bless=item.bless);
+            desc_text.push((UI_IMG_ID_ITEM_INFO, t));
+        }
+
         for attr in &item.attributes {
             match attr {
                 ItemAttribute::Material(material) => {
@@ -70,6 +79,10 @@ impl ItemInfoText {
                         "item_info_text-material"; material=material_name);
                     desc_text.push((UI_IMG_ID_ITEM_INFO, t));
                 }
+                // Affixes of unidentified items are unknown
+                ItemAttribute::Affix { kind, value } if identify::is_identified(item) => {
+                    desc_text.push((UI_IMG_ID_ITEM_INFO, affix_text(*kind, *value)));
+                }
                 _ => (),
             }
         }
@@ -81,3 +94,29 @@ impl ItemInfoText {
         }
     }
 }
+
+/// Effectiveness without variation. Blessed state is ignored for unidentified items.
+fn known_eff(item: &Item) -> i32 {
+    if identify::is_identified(item) {
+        item.calc_eff_without_var()
+    } else {
+        let mut item = item.clone();
+        item.bless = BlessState::Normal;
+        item.calc_eff_without_var()
+    }
+}
+
+fn affix_text(kind: AffixKind, value: u16) -> String {
+    match kind {
+        AffixKind::Attr(attr) => {
+            misc_txt_format!("item_info_text-affix-attr"; attr=attr, value=value)
+        }
+        AffixKind::Defence(element) => {
+            misc_txt_format!("item_info_text-affix-defence"; element=element, value=value)
+        }
+        AffixKind::ElementalDamage(element) => misc_txt_format!(
+            "item_info_text-affix-elemental_damage"; element=element, value=value),
+        AffixKind::OnHitStatus(status) => misc_txt_format!(
+            "item_info_text-affix-on_hit_status"; status=status, value=value),
+    }
+}
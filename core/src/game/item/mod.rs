@@ -1,20 +1,20 @@
 pub mod filter;
 pub mod gen;
+pub mod identify;
 pub mod info;
 pub mod merged;
 pub mod throw;
 
-use crate::context::IconIdx;
+use crate::game::extrait::*;
+use crate::text::ItemName;
 use common::gamedata::*;
 use common::gobj;
-use common::obj::ImgVariationRule;
 use common::objholder::ItemIdx;
 use rules::material::Material;
 use rules::RULES;
 
 /// Additional Item methods
 pub trait ItemEx {
-    fn icon(&self) -> IconIdx;
     fn material(&self) -> Option<(MaterialName, &Material)>;
     /// Calculate factor for the item effectiveness
     fn eff_factor(&self) -> f32;
@@ -24,36 +24,19 @@ pub trait ItemEx {
     fn calc_eff_without_var(&self) -> i32;
     /// Calculate item price
     fn price(&self) -> i64;
-    /// Calculate item selling price
-    fn selling_price(&self) -> i64;
     fn w(&self) -> u32;
     fn charge(&self) -> Option<u32>;
     fn charge_mut(&mut self) -> Option<&mut u32>;
     fn title(&self) -> Option<&str>;
+    /// Affixes of this item and their values
+    fn affixes(&self) -> Vec<(AffixKind, u16)>;
+    /// Sum of the values of given affix kind
+    fn affix_value(&self, kind: AffixKind) -> u16;
     /// Calculate throw range by item weight and character STR.
     fn throw_range(&self, str: u16) -> u32;
 }
 
 impl ItemEx for Item {
-    fn icon(&self) -> IconIdx {
-        let obj = self.obj();
-
-        if obj.img.variation_rule == ImgVariationRule::RandomOnGen {
-            for attr in &self.attributes {
-                match attr {
-                    ItemAttribute::ImageVariation(n) => {
-                        return IconIdx::Item {
-                            idx: self.idx,
-                            i_pattern: *n,
-                        };
-                    }
-                    _ => (),
-                }
-            }
-        }
-        IconIdx::from(self.idx)
-    }
-
     fn material(&self) -> Option<(MaterialName, &Material)> {
         for attr in &self.attributes {
             match attr {
@@ -71,6 +54,11 @@ impl ItemEx for Item {
         if let Some((_, material)) = self.material() {
             factor *= material.eff;
         }
+        match self.bless {
+            BlessState::Blessed => factor *= RULES.item.blessed_eff_factor,
+            BlessState::Cursed => factor *= RULES.item.cursed_eff_factor,
+            BlessState::Normal => (),
+        }
         factor
     }
 
@@ -103,10 +91,6 @@ impl ItemEx for Item {
         (item_obj.basic_price as f32 * factor) as i64
     }
 
-    fn selling_price(&self) -> i64 {
-        self.price() / 2
-    }
-
     fn w(&self) -> u32 {
         let item_obj = gobj::get_obj(self.idx);
 
@@ -153,6 +137,24 @@ impl ItemEx for Item {
         None
     }
 
+    fn affixes(&self) -> Vec<(AffixKind, u16)> {
+        self.attributes
+            .iter()
+            .filter_map(|attr| match attr {
+                ItemAttribute::Affix { kind, value } => Some((*kind, *value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn affix_value(&self, kind: AffixKind) -> u16 {
+        self.affixes()
+            .into_iter()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, value)| value)
+            .sum()
+    }
+
     fn throw_range(&self, str: u16) -> u32 {
         let w = std::cmp::max(self.w(), 1);
         std::cmp::min(
@@ -189,20 +191,34 @@ impl ItemListEx for ItemList {
     }
 }
 
-/// Change specified character's equipment by given item
+/// Change specified character's equipment by given item.
+/// Cursed equipment cannot be removed.
 pub fn change_equipment(
     gd: &mut GameData,
     cid: CharaId,
     slot: (EquipSlotKind, u8),
     il: ItemLocation,
 ) {
-    let item = gd.remove_item_and_get(il, 1);
+    if let Some(equipped) = gd.get_equip_list(cid).item(slot.0, slot.1 as usize) {
+        if equipped.bless == BlessState::Cursed {
+            game_log_i!("item-cursed-cannot-remove"; item=ItemName::new(gd, equipped));
+            return;
+        }
+    }
 
-    game_log_i!("item-equip"; chara=gd.chara.get(cid), item=item);
+    let mut item = gd.remove_item_and_get(il, 1);
+    // Equipping reveals the quality and curse of the item
+    identify::identify(&mut gd.item_knowledge, &mut item);
+
+    game_log_i!("item-equip"; chara=gd.chara.get(cid), item=ItemName::new(gd, &item));
+    if item.bless == BlessState::Cursed {
+        game_log_i!("item-equip-cursed"; item=ItemName::new(gd, &item));
+    }
     if let Some(removed_equipment) = gd
         .get_equip_list_mut(cid)
         .equip(slot.0, slot.1 as usize, item)
     {
         gd.get_item_list_mut(il.0).append(removed_equipment, 1);
     }
+    gd.chara.get_mut(cid).update();
 }
@@ -0,0 +1,252 @@
+use super::from_template::stamp_template;
+use crate::map_generator::{GeneratedMap, MapGenerator, TileKind};
+use common::gamedata::map::*;
+use common::gobj;
+use common::maptemplate::MapTemplateObject;
+use common::objholder::*;
+use geom::*;
+use rng::RngStream;
+use rules::floor_gen::*;
+use rules::RULES;
+
+#[derive(Clone, Default)]
+pub struct MapBuilder {
+    w: u32,
+    h: u32,
+    floor: u32,
+    is_deepest_floor: bool,
+    floor_gen_params: Option<&'static FloorGenParams>,
+    tile: TileIdx,
+    wall: WallIdx,
+    music: String,
+    prefab: Option<MapTemplateIdx>,
+    lit_room_probability: f32,
+}
+
+impl MapBuilder {
+    pub fn new(w: u32, h: u32) -> MapBuilder {
+        let mut map_builder = MapBuilder::default();
+        map_builder.w = w;
+        map_builder.h = h;
+        map_builder
+    }
+
+    pub fn floor_gen_id(mut self, id: &str) -> Self {
+        self.floor_gen_params = Some(&RULES.floor_gen.floor_gen_params[id]);
+        self.w = self.floor_gen_params.unwrap().map_size.0 as u32;
+        self.h = self.floor_gen_params.unwrap().map_size.1 as u32;
+        self
+    }
+
+    pub fn build(self) -> Map {
+        self.build_with_prefab().0
+    }
+
+    /// Build a map, and returns the position the prefab is stamped at.
+    /// Returns None if there is no free space for the prefab.
+    pub fn build_with_prefab(self) -> (Map, Option<Vec2d>) {
+        let prefab = self.prefab.map(gobj::get_obj);
+        let lit_room_probability = self.lit_room_probability;
+        let (generated_map, prefab_pos, lit_rooms) = rng::with_stream(RngStream::MapGen, || {
+            let mut generated_map = if let Some(floor_gen_params) = self.floor_gen_params {
+                map_generator(floor_gen_params).generate()
+            } else {
+                MapGenerator::new((self.w, self.h)).flat().generate()
+            };
+            let prefab_pos = prefab.and_then(|t| stamp_prefab(&mut generated_map, t));
+            let lit_rooms: Vec<(Vec2d, Vec2d)> = if lit_room_probability > 0.0 {
+                generated_map
+                    .rooms
+                    .iter()
+                    .filter(|_| rng::gen_bool(lit_room_probability))
+                    .copied()
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            (generated_map, prefab_pos, lit_rooms)
+        });
+        let mut map = generated_map_to_map(
+            generated_map,
+            self.tile,
+            self.wall,
+            self.floor,
+            self.is_deepest_floor,
+        );
+        if let (Some(t), Some(pos)) = (prefab, prefab_pos) {
+            stamp_template(&mut map, t, pos);
+        }
+        for (top_left, bottom_right) in lit_rooms {
+            // Surrounding walls are also lit
+            for p in RectIter::new(top_left + (-1, -1), bottom_right + (1, 1)) {
+                if map.is_inside(p) {
+                    map.tile[p].lit = true;
+                }
+            }
+        }
+        map.music = self.music;
+        (map, prefab_pos)
+    }
+
+    pub fn floor(mut self, floor: u32) -> MapBuilder {
+        self.floor = floor;
+        self
+    }
+
+    pub fn tile(mut self, tile: TileIdx) -> MapBuilder {
+        self.tile = tile;
+        self
+    }
+
+    pub fn wall(mut self, wall: WallIdx) -> MapBuilder {
+        self.wall = wall;
+        self
+    }
+
+    pub fn deepest_floor(mut self, is_deepest_floor: bool) -> MapBuilder {
+        self.is_deepest_floor = is_deepest_floor;
+        self
+    }
+
+    pub fn music(mut self, music: &str) -> MapBuilder {
+        self.music = music.to_owned();
+        self
+    }
+
+    pub fn prefab(mut self, prefab: MapTemplateIdx) -> MapBuilder {
+        self.prefab = Some(prefab);
+        self
+    }
+
+    pub fn lit_room_probability(mut self, p: f32) -> MapBuilder {
+        self.lit_room_probability = p;
+        self
+    }
+}
+
+/// Stamp the template walls into the generated map, and repair its connectivity.
+fn stamp_prefab(gm: &mut GeneratedMap, t: &MapTemplateObject) -> Option<Vec2d> {
+    let size = t.tile.size();
+    let tile = Array2d::from_fn(size.0, size.1, |p| {
+        if t.wall[p].is_empty() {
+            TileKind::Floor
+        } else {
+            TileKind::Wall
+        }
+    });
+    let pos = gm.find_free_space(Vec2d(size.0 as i32, size.1 as i32))?;
+    let entrance = t
+        .entrance
+        .first()
+        .copied()
+        .unwrap_or(Vec2d(size.0 as i32 / 2, size.1 as i32 / 2));
+    gm.stamp(pos, &tile, entrance);
+    if let Err(e) = gm.validate() {
+        trace!("repair map after stamping prefab: {}", e);
+        gm.repair();
+    }
+    Some(pos)
+}
+
+fn map_generator(floor_gen_params: &FloorGenParams) -> MapGenerator {
+    let map_generator = MapGenerator::new(floor_gen_params.map_size);
+    match floor_gen_params.map_gen_kind {
+        MapGenKind::Flat => map_generator.flat(),
+        MapGenKind::Fractal => map_generator.fractal(),
+        MapGenKind::Lattice => map_generator.lattice(5, 4, 3, 7, 0.5),
+        MapGenKind::Rooms => map_generator.rooms(5, 8, 7),
+        MapGenKind::Cave {
+            wall_ratio,
+            n_step,
+            birth_limit,
+            survival_limit,
+        } => map_generator.cave(wall_ratio, n_step, birth_limit, survival_limit),
+        MapGenKind::Bsp {
+            min_room_size,
+            max_room_size,
+            max_depth,
+        } => map_generator.bsp(min_room_size, max_room_size, max_depth),
+    }
+}
+
+pub fn generated_map_to_map(
+    gm: GeneratedMap,
+    tile: TileIdx,
+    wall: WallIdx,
+    floor: u32,
+    is_deepest_floor: bool,
+) -> Map {
+    let size = gm.size;
+    let mut map = Map::new(size.0 as u32, size.1 as u32);
+    let wall_obj = gobj::get_obj(wall);
+
+    trace!("New map creating");
+
+    for p in size.iter_from_zero() {
+        map.tile[p].tile = tile.into();
+        match gm.tile[p] {
+            TileKind::Wall => {
+                let piece_pattern = {
+                    let f = |pos: Vec2d| {
+                        if let Some(t) = gm.tile.get(pos) {
+                            *t == TileKind::Wall
+                        } else {
+                            true
+                        }
+                    };
+                    let mut piece_pattern_flags = PiecePatternFlags::new();
+                    for dir in &Direction::EIGHT_DIRS {
+                        piece_pattern_flags.set(*dir, f(p + dir.as_vec()));
+                    }
+                    let wall_obj = gobj::get_obj(wall);
+                    piece_pattern_flags.to_piece_pattern(wall_obj.img.n_pattern)
+                };
+                map.tile[p].wall = WallIdxPP::with_piece_pattern(wall, piece_pattern);
+                map.tile[p].wall_hp = wall_obj.hp;
+            }
+            TileKind::Door => {
+                map.tile[p].special = SpecialTileKind::Door { lock_level: 0 };
+            }
+            _ => (),
+        }
+    }
+
+    // Set stairs
+    let entrance_stairs = StairsKind::UpStairs;
+    let exit_stairs = StairsKind::DownStairs;
+
+    let dest_floor = if floor == 0 { FLOOR_OUTSIDE } else { floor - 1 };
+    map.entrance.push(gm.entrance);
+    map.tile[gm.entrance].special = SpecialTileKind::Stairs {
+        dest_floor,
+        kind: entrance_stairs,
+    };
+
+    if !is_deepest_floor && gm.exit.is_some() {
+        let dest_floor = floor + 1;
+        map.tile[gm.exit.unwrap()].special = SpecialTileKind::Stairs {
+            dest_floor,
+            kind: exit_stairs,
+        };
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floor_gen_params_reachability() {
+        rules::init(concat!(env!("CARGO_MANIFEST_DIR"), "/../assets"), None);
+
+        for (id, floor_gen_params) in RULES.floor_gen.floor_gen_params.iter() {
+            for seed in 0..50 {
+                rng::reseed(seed);
+                let map = map_generator(floor_gen_params).generate();
+                assert_eq!(map.validate(), Ok(()), "{} with seed {}", id, seed);
+            }
+        }
+    }
+}
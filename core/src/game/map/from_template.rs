@@ -2,11 +2,12 @@ use crate::game::item::gen::from_item_gen;
 use common::gamedata::*;
 use common::gobj;
 use common::maptemplate::*;
+use geom::*;
 
 pub fn from_template(t: &MapTemplateObject, item_own_flag: bool) -> Map {
     let mut map = create_terrain(t);
     set_boundary(&mut map, t, 0);
-    gen_items(&mut map, t, item_own_flag);
+    gen_items(&mut map, t, Vec2d(0, 0), item_own_flag);
     map
 }
 
@@ -43,10 +44,40 @@ fn create_terrain(t: &MapTemplateObject) -> Map {
 
     map.entrance = t.entrance.clone();
     map.music = t.music.clone();
+    map.template_id = Some(t.id.clone());
 
     map
 }
 
+/// Overwrite tiles, walls, decos and items by the template as a prefab.
+/// Template walls are set only where the map has walls,
+/// because corridors may be dug through the prefab.
+pub fn stamp_template(map: &mut Map, t: &MapTemplateObject, offset: Vec2d) {
+    for (pos, c) in t.tile.iter_with_idx() {
+        map.tile[offset + pos].tile = TileLayers::conv_from(*c, &t.tile_table).into();
+    }
+
+    for (pos, c) in t.wall.iter_with_idx() {
+        let tile = &mut map.tile[offset + pos];
+        let wall_idx = WallIdxPP::conv_from(*c, &t.wall_table);
+        if let Some(idx) = wall_idx.idx() {
+            if !tile.wall.is_empty() {
+                tile.wall = wall_idx;
+                tile.wall_hp = gobj::get_obj(idx).hp;
+            }
+        }
+    }
+
+    for (pos, i) in t.deco.iter_with_idx() {
+        if let Some(i) = *i {
+            let deco_id = &t.deco_table[i as usize];
+            map.tile[offset + pos].deco = Some(gobj::id_to_idx(deco_id));
+        }
+    }
+
+    gen_items(map, t, offset, false);
+}
+
 /// Setting Boundaries
 pub fn set_boundary(map: &mut Map, t: &MapTemplateObject, floor: u32) {
     let next_floor = BoundaryBehavior::Floor(floor + 1);
@@ -72,7 +103,7 @@ pub fn set_boundary(map: &mut Map, t: &MapTemplateObject, floor: u32) {
 }
 
 /// Generate items
-fn gen_items(map: &mut Map, t: &MapTemplateObject, item_own_flag: bool) {
+fn gen_items(map: &mut Map, t: &MapTemplateObject, offset: Vec2d, item_own_flag: bool) {
     for (pos, item_gen) in &t.items {
         let mut item = if let Some(item) = from_item_gen(item_gen) {
             item
@@ -85,6 +116,6 @@ fn gen_items(map: &mut Map, t: &MapTemplateObject, item_own_flag: bool) {
         }
 
         // Locate item at the specified tile
-        map.locate_item(item, *pos, 1);
+        map.locate_item(item, offset + *pos, 1);
     }
 }
@@ -1,5 +1,6 @@
 pub mod builder;
 pub mod from_template;
+pub mod path;
 pub mod search;
 pub mod tile_info;
 pub mod wall_damage;
@@ -23,7 +24,7 @@ pub trait MapEx {
 
 impl MapEx for Map {
     fn is_passable(&self, _chara: &Chara, pos: Vec2d) -> bool {
-        if !self.is_inside(pos) {
+        if !self.is_inside(pos) || self.tile[pos].special.is_blocking() {
             return false;
         }
 
@@ -51,6 +52,8 @@ impl MapEx for Map {
 pub fn switch_map_with_pos(game: &mut Game, mid: MapId, pos: Option<Vec2d>) {
     game.ui_request.push_back(super::UiRequest::StopCentering);
     game.clear_target();
+    game.path_cache.clear();
+    game.auto_travel = None;
 
     let save_dir = game.save_dir.as_ref().unwrap();
     let gd = &mut game.gd;
@@ -88,10 +91,24 @@ pub fn switch_map_with_pos(game: &mut Game, mid: MapId, pos: Option<Vec2d>) {
 
     gd.get_current_map_mut()
         .locate_chara(CharaId::Player, new_player_pos);
+    super::party::locate_party(gd);
 
     crate::audio::play_sound("floor-change");
     crate::audio::play_music(&gd.get_current_map().music);
     super::view::update_view_map(game);
+    super::quest::notify(game, super::quest::QuestEvent::MapEntered(mid));
+    super::hook::enter(game, prev_mid);
+    game.autosave_file();
+}
+
+/// Returns the nearest empty tile from the player that the character can be located on
+pub fn empty_tile_near_player(gd: &GameData, chara: &Chara) -> Option<Vec2d> {
+    let map = gd.get_current_map();
+    let center = map.chara_pos(CharaId::Player)?;
+    map.tile
+        .iter_idx()
+        .filter(|pos| map.get_chara(*pos).is_none() && map.is_passable(chara, *pos))
+        .min_by_key(|pos| center.mdistance(*pos))
 }
 
 /// Switch current map to the specified map
@@ -215,7 +232,11 @@ pub fn update_observed_map(game: &mut Game) {
         observed_tile.tile = true;
         observed_tile.wall = tile.wall;
         observed_tile.deco = tile.deco;
-        observed_tile.special = tile.special;
+        observed_tile.special = if tile.special.is_hidden() {
+            SpecialTileKind::None
+        } else {
+            tile.special
+        };
         observed_tile.items.clear();
 
         for &(ref item, _) in tile.item_list.iter().take(MAX_ITEM_FOR_DRAW) {
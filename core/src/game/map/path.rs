@@ -0,0 +1,123 @@
+//! Path finding on the current map
+
+use super::MapEx;
+use crate::game::{Game, InfoGetter};
+use common::gamedata::*;
+use geom::*;
+use std::collections::HashMap;
+
+/// The maximum number of tiles expanded in one path search
+const MAX_SEARCH_NODES: usize = 4096;
+/// Additional cost to pass through a tile that other character stands on
+const CHARA_TILE_COST: u32 = 30;
+/// Additional cost to pass through a locked door. NPCs try to unlock doors on their paths.
+const LOCKED_DOOR_COST: u32 = 20;
+
+/// Holds computed paths of characters on the current map.
+/// Cleared when the current map is switched.
+#[derive(Default)]
+pub struct PathCache {
+    paths: HashMap<CharaId, CachedPath>,
+}
+
+struct CachedPath {
+    goal: Vec2d,
+    /// Game time when this path was computed
+    time: Time,
+    /// Remaining steps. The first element is the next step.
+    steps: Vec<Vec2d>,
+}
+
+impl PathCache {
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+
+    pub fn remove(&mut self, cid: CharaId) {
+        self.paths.remove(&cid);
+    }
+}
+
+/// Search the path from the character position to goal on the current map.
+/// Walls and impassable tiles are avoided, and tiles other characters stand on are
+/// passable with additional cost because they may move.
+pub fn find_path(gd: &GameData, cid: CharaId, goal: Vec2d) -> Option<Vec<Vec2d>> {
+    let map = gd.get_current_map();
+    let start = map.chara_pos(cid)?;
+
+    geom::find_path(start, goal, MAX_SEARCH_NODES, |p| {
+        let cost = tile_cost(gd, cid, p)?;
+        if p != goal && map.tile[p].chara.is_some() {
+            Some(cost + CHARA_TILE_COST)
+        } else {
+            Some(cost)
+        }
+    })
+}
+
+/// Cost to enter the tile ignoring other characters. Returns None if impassable.
+fn tile_cost(gd: &GameData, cid: CharaId, p: Vec2d) -> Option<u32> {
+    let map = gd.get_current_map();
+    if map.is_passable(gd.chara.get(cid), p) {
+        Some(0)
+    } else if cid != CharaId::Player && is_locked_door(map, p) {
+        Some(LOCKED_DOOR_COST)
+    } else {
+        None
+    }
+}
+
+fn is_locked_door(map: &Map, p: Vec2d) -> bool {
+    map.is_inside(p)
+        && map.tile[p].wall.is_empty()
+        && matches!(map.tile[p].special, SpecialTileKind::Door { lock_level } if lock_level > 0)
+}
+
+/// Get the direction of the next step to goal.
+/// Computed paths are cached, and reused while the next step is not blocked.
+/// Returns None if the character is on goal or goal is unreachable.
+pub fn next_step_dir(game: &mut Game, cid: CharaId, goal: Vec2d) -> Option<Direction> {
+    let gd = &game.gd;
+    let pos = gd.chara_pos(cid)?;
+    if pos == goal {
+        game.path_cache.remove(cid);
+        return None;
+    }
+    let now = gd.time.current_time();
+
+    let reusable = if let Some(cached) = game.path_cache.paths.get_mut(&cid) {
+        // Drop steps the character has already walked
+        if let Some(i) = cached.steps.iter().position(|p| *p == pos) {
+            cached.steps.drain(..=i);
+        }
+        match cached.steps.first() {
+            Some(next) if cached.goal == goal => {
+                next.is_adjacent(pos) && (cached.time == now || is_free(gd, cid, *next, goal))
+            }
+            _ => false,
+        }
+    } else {
+        false
+    };
+
+    if !reusable {
+        let steps = find_path(gd, cid, goal)?;
+        game.path_cache.paths.insert(
+            cid,
+            CachedPath {
+                goal,
+                time: now,
+                steps,
+            },
+        );
+    }
+
+    let next = *game.path_cache.paths[&cid].steps.first()?;
+    Some(dir_by_2pos(pos, next))
+}
+
+/// The tile can be entered now or not
+fn is_free(gd: &GameData, cid: CharaId, p: Vec2d, goal: Vec2d) -> bool {
+    let map = gd.get_current_map();
+    tile_cost(gd, cid, p).is_some() && (p == goal || map.tile[p].chara.is_none())
+}
@@ -1,7 +1,10 @@
+use crate::game::stealth;
 use crate::game::Game;
+use common::gamedata::SpecialTileKind;
 use common::gobj;
 use common::objholder::ItemIdx;
 use geom::*;
+use rules::RULES;
 
 pub fn wall_damage(game: &mut Game, pos: Vec2d, power: f32) {
     let map = game.gd.get_current_map_mut();
@@ -22,6 +25,9 @@ pub fn wall_damage(game: &mut Game, pos: Vec2d, power: f32) {
     if wall_hp <= damage {
         let wall_obj = gobj::get_obj(tile.wall.idx().unwrap());
         map.erase_wall(pos);
+        if let SpecialTileKind::HiddenPassage { .. } = map.tile[pos].special {
+            map.tile[pos].special = SpecialTileKind::None;
+        }
         for (mining_reward, n) in &wall_obj.mining_rewards {
             if let Some(item_idx) = gobj::id_to_idx_checked::<ItemIdx>(&mining_reward) {
                 let item = crate::game::item::gen::gen_item_from_idx(item_idx, 1);
@@ -33,4 +39,5 @@ pub fn wall_damage(game: &mut Game, pos: Vec2d, power: f32) {
     } else {
         tile.wall_hp = wall_hp - damage;
     }
+    stealth::make_noise(&mut game.gd, None, pos, RULES.stealth.noise_mining);
 }
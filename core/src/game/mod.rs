@@ -10,21 +10,28 @@ mod debug_command;
 mod dungeon_gen;
 pub mod effect;
 mod eval_expr;
+mod fov;
 pub mod frequent_tex;
+mod hook;
 mod infogetter;
 pub mod item;
 pub mod map;
 pub mod newgame;
 mod npc;
+mod party;
 pub mod playeract;
 pub mod quest;
 mod region;
+pub mod replay;
 pub mod saveload;
 mod script;
 pub mod shop;
 pub mod site;
 mod skill;
+mod stealth;
 mod target;
+#[cfg(test)]
+mod test_util;
 mod town;
 mod turnloop;
 pub mod view;
@@ -39,9 +46,11 @@ pub use self::target::Target;
 use common::gamedata::*;
 use common::gobj;
 use common::objholder::ScriptIdx;
+use common::script::Trigger;
 use geom::Vec2d;
 use std::collections::VecDeque;
 use std::path::PathBuf;
+use std::time::Instant;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum GameState {
@@ -60,10 +69,19 @@ pub struct Game {
     dialog_open_request: Option<DialogOpenRequest>,
     ui_request: VecDeque<UiRequest>,
     script: Option<ScriptEngine>,
+    /// Scripts started after the current script finishes
+    script_queue: VecDeque<(String, Option<CharaId>)>,
     /// Player's current target of shot and similer actions
     target_chara: Option<CharaId>,
     save_dir: Option<PathBuf>,
+    /// Autosave is made when the current map is changed
+    autosave: bool,
+    /// Play time since the last saving is counted from this
+    play_timer: Instant,
     pub view_map: view::ViewMap,
+    path_cache: map::path::PathCache,
+    auto_travel: Option<playeract::AutoTravel>,
+    recorder: Option<replay::Recorder>,
     pub frequent_tex: self::frequent_tex::FrequentTextures,
 }
 
@@ -71,7 +89,7 @@ impl Game {
     pub fn new(gd: GameData) -> Game {
         let save_dir = self::saveload::get_each_save_dir(&gd);
 
-        rng::reseed(crate::config::CONFIG.fix_rand);
+        rng::set_state(gd.rng.clone());
 
         Game {
             gd,
@@ -81,9 +99,15 @@ impl Game {
             dialog_open_request: None,
             ui_request: VecDeque::new(),
             script: None,
+            script_queue: VecDeque::new(),
             target_chara: None,
             save_dir: Some(save_dir),
+            autosave: true,
+            play_timer: Instant::now(),
             view_map: view::ViewMap::new(),
+            path_cache: map::path::PathCache::default(),
+            auto_travel: None,
+            recorder: None,
             frequent_tex: self::frequent_tex::FrequentTextures::new(),
         }
     }
@@ -98,9 +122,15 @@ impl Game {
             dialog_open_request: None,
             ui_request: VecDeque::new(),
             script: None,
+            script_queue: VecDeque::new(),
             target_chara: None,
             save_dir: None,
+            autosave: false,
+            play_timer: Instant::now(),
             view_map: view::ViewMap::new(),
+            path_cache: map::path::PathCache::default(),
+            auto_travel: None,
+            recorder: None,
             frequent_tex: self::frequent_tex::FrequentTextures::new(),
         }
     }
@@ -114,6 +144,16 @@ impl Game {
         turnloop::turn_loop(self);
     }
 
+    /// Advance turns until the player's input is needed.
+    /// Used without windows, so animations are discarded.
+    pub fn advance_until_player_turn(&mut self) {
+        while self.state == GameState::WaitingForNextTurn {
+            self.advance_turn();
+            while self.pop_animation().is_some() {}
+            self.update_before_drawing();
+        }
+    }
+
     /// Update drawing data
     pub fn update_before_drawing(&mut self) {
         map::update_observed_map(self);
@@ -154,6 +194,25 @@ impl Game {
         self.advance_script(None);
     }
 
+    /// Queue a script. It is started when no other script is running.
+    pub fn queue_script(&mut self, id: String, cid: Option<CharaId>) {
+        if gobj::id_to_idx_checked::<ScriptIdx>(&id).is_none() {
+            warn!("unknown script id {}", id);
+            return;
+        }
+        self.script_queue.push_back((id, cid));
+    }
+
+    /// Start the first queued script if no script is running
+    pub fn start_queued_script(&mut self) {
+        if self.script.is_some() {
+            return;
+        }
+        if let Some((id, cid)) = self.script_queue.pop_front() {
+            self.start_script(&id, cid);
+        }
+    }
+
     /// Advance current script.
     /// When called by advance_talk, give player's choice.
     pub fn advance_script(&mut self, choice: Option<Option<u32>>) -> AdvanceScriptResult {
@@ -170,6 +229,7 @@ impl Game {
         match result {
             ExecResult::Quit => {
                 self.script = None;
+                self.start_queued_script();
                 AdvanceScriptResult::Quit
             }
             ExecResult::Talk(cid, talk_text, need_open_talk_dialog) => {
@@ -182,14 +242,22 @@ impl Game {
                 self.request_dialog_open(DialogOpenRequest::ShopBuy { cid });
                 AdvanceScriptResult::Continue
             }
-            ExecResult::ShopSell => {
-                self.request_dialog_open(DialogOpenRequest::ShopSell);
+            ExecResult::ShopSell(cid) => {
+                self.request_dialog_open(DialogOpenRequest::ShopSell { cid });
                 AdvanceScriptResult::Continue
             }
             ExecResult::Quest => {
                 self.request_dialog_open(DialogOpenRequest::Quest);
                 AdvanceScriptResult::Continue
             }
+            ExecResult::Teleport(mid, pos) => {
+                map::switch_map_with_pos(self, mid, Some(pos));
+                self.advance_script(None)
+            }
+            ExecResult::QuestCompleted => {
+                hook::trigger(self, &Trigger::QuestCompleted);
+                self.advance_script(None)
+            }
         }
     }
 
@@ -225,6 +293,7 @@ impl Game {
     }
 
     pub fn end_game(&mut self) {
+        self.finish_recording();
         self.clean_save_data()
     }
 }
@@ -250,7 +319,9 @@ pub enum DialogOpenRequest {
     ShopBuy {
         cid: CharaId,
     },
-    ShopSell,
+    ShopSell {
+        cid: CharaId,
+    },
     PickUpItem,
     Quest,
     GameOver,
@@ -281,3 +352,29 @@ pub mod extrait {
     pub use super::site::SiteEx;
     pub use super::skill::SkillListEx;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geom::Direction;
+
+    #[test]
+    fn run_turn_without_window() {
+        let _lock = test_util::lock();
+        let (gd, cid) = test_util::fixture(Vec2d(12, 12));
+        let mut game = Game::new(gd);
+        game.autosave = false;
+        game.update_before_player_turn();
+        let time = game.gd.time.current_time();
+        let monster_wait_time = game.gd.chara.get(cid).wait_time;
+
+        DoPlayerAction::new(&mut game).try_move(Direction::E);
+        assert!(game.get_state() == GameState::WaitingForNextTurn);
+        game.advance_until_player_turn();
+
+        assert!(game.get_state() == GameState::PlayerTurn);
+        assert_eq!(game.gd.player_pos(), Vec2d(3, 2));
+        assert!(game.gd.time.current_time() > time);
+        assert_ne!(game.gd.chara.get(cid).wait_time, monster_wait_time);
+    }
+}
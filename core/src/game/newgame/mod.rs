@@ -9,6 +9,7 @@ pub struct NewGameBuilder {
     gd: GameData,
     player_name: Option<String>,
     chara_class: Option<CharaClass>,
+    seed: Option<u64>,
 }
 
 impl NewGameBuilder {
@@ -17,6 +18,7 @@ impl NewGameBuilder {
             gd: GameData::empty(),
             player_name: None,
             chara_class: None,
+            seed: None,
         }
     }
 
@@ -28,8 +30,22 @@ impl NewGameBuilder {
         self.chara_class = Some(chara_class);
     }
 
+    /// Set the seed of RNG. If not set, the seed is given by config or generated.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
     pub fn build(mut self) -> GameData {
-        rng::reseed(crate::config::CONFIG.fix_rand);
+        let config = &crate::config::CONFIG;
+        let seed = if let Some(seed) = self.seed.or(config.seed) {
+            seed
+        } else if config.fix_rand {
+            rng::FIXED_SEED
+        } else {
+            rng::gen_seed()
+        };
+        info!("Start new game with seed {}", seed);
+        rng::reseed(seed);
         {
             let mut gd = &mut self.gd;
 
@@ -83,6 +99,8 @@ impl NewGameBuilder {
                 gd.faction.set(*faction_id, faction.default_relation);
             }
         }
+        self.gd.rng = rng::get_state();
+        self.gd.item_knowledge = ItemKnowledge::new(seed);
         self.gd
     }
 }
@@ -0,0 +1,445 @@
+//! Evaluate NPC behavior trees defined in rules.
+//! Decisions only read GameData, and are executed in process_npc_turn.
+
+use crate::game::effect::weapon_to_effect;
+use crate::game::extrait::*;
+use crate::game::item::throw::item_to_throw_effect;
+use crate::game::view::Sight;
+use crate::game::Target;
+use common::gamedata::*;
+use geom::*;
+use rules::{npc_ai::*, RULES};
+
+/// Action decided by NPC AI
+#[derive(Clone, PartialEq, Debug)]
+pub enum NpcAction {
+    Wait,
+    RandomWalk,
+    /// Move to given position by path finding
+    MoveTo(Vec2d),
+    /// Move to the adjacent tile that is far from given position
+    MoveAwayFrom(Vec2d),
+    Shoot(CharaId),
+    ThrowItem(ItemLocation, Target),
+    ReleaseItem(ItemLocation, Target),
+    ActiveSkill(String, Target),
+}
+
+enum Status {
+    Failure,
+    Success,
+    Decided(NpcAction),
+}
+
+/// Decide the action of the character by given behavior tree.
+/// If no action is decided, the character waits.
+pub fn decide(gd: &GameData, cid: CharaId, sight: &Sight, tree: &AiNode) -> NpcAction {
+    match eval_node(gd, cid, sight, tree) {
+        Status::Decided(action) => action,
+        _ => NpcAction::Wait,
+    }
+}
+
+fn eval_node(gd: &GameData, cid: CharaId, sight: &Sight, node: &AiNode) -> Status {
+    match node {
+        AiNode::Select(children) => {
+            for child in children {
+                match eval_node(gd, cid, sight, child) {
+                    Status::Failure => (),
+                    status => return status,
+                }
+            }
+            Status::Failure
+        }
+        AiNode::Sequence(children) => {
+            for child in children {
+                match eval_node(gd, cid, sight, child) {
+                    Status::Success => (),
+                    status => return status,
+                }
+            }
+            Status::Success
+        }
+        AiNode::Cond(cond) => {
+            if eval_cond(gd, cid, sight, cond) {
+                Status::Success
+            } else {
+                Status::Failure
+            }
+        }
+        AiNode::Action(action) => {
+            if let Some(action) = decide_action(gd, cid, sight, action) {
+                Status::Decided(action)
+            } else {
+                Status::Failure
+            }
+        }
+    }
+}
+
+fn eval_cond(gd: &GameData, cid: CharaId, sight: &Sight, cond: &AiCond) -> bool {
+    let chara = gd.chara.get(cid);
+    match cond {
+        AiCond::True => true,
+        AiCond::Not(cond) => !eval_cond(gd, cid, sight, cond),
+        AiCond::Prob(p) => rng::gen_bool(*p),
+        AiCond::HpBelow(ratio) => hp_ratio(chara) < *ratio,
+        AiCond::AllyHpBelow(ratio) => {
+            charas_in_sight(gd, cid, sight, Relationship::ALLY, chara.attr.view_range)
+                .iter()
+                .any(|(ally, _)| hp_ratio(gd.chara.get(*ally)) < *ratio)
+        }
+        AiCond::EnemyInView => {
+            nearest_enemy_within(gd, cid, sight, chara.attr.view_range).is_some()
+        }
+        AiCond::EnemyWithin(d) => nearest_enemy_within(gd, cid, sight, *d as i32).is_some(),
+        AiCond::HasRangedWeapon => chara.equip.item(EquipSlotKind::RangedWeapon, 0).is_some(),
+        AiCond::LeaderFartherThan(d) => {
+            if let Some(leader_pos) = chara.ai.leader.and_then(|leader| gd.chara_pos(leader)) {
+                distance(gd, cid, leader_pos) > *d as i32
+            } else {
+                false
+            }
+        }
+        AiCond::AwayFromInitialPos(d) => distance(gd, cid, chara.ai.initial_pos) > *d as i32,
+    }
+}
+
+fn decide_action(
+    gd: &GameData,
+    cid: CharaId,
+    sight: &Sight,
+    action: &AiAction,
+) -> Option<NpcAction> {
+    let chara = gd.chara.get(cid);
+    match action {
+        AiAction::Wait => Some(NpcAction::Wait),
+        AiAction::RandomWalk => Some(NpcAction::RandomWalk),
+        AiAction::ApproachEnemy => {
+            if let Some(target) = nearest_enemy_within(gd, cid, sight, chara.attr.view_range) {
+                return Some(NpcAction::MoveTo(gd.chara_pos(target)?));
+            }
+            // Go to the position where an enemy was seen or heard last
+            match chara.ai.awareness {
+                Awareness::Hunting { pos, .. } | Awareness::Suspicious { pos, .. }
+                    if gd.chara_pos(cid)? != pos =>
+                {
+                    Some(NpcAction::MoveTo(pos))
+                }
+                _ => None,
+            }
+        }
+        AiAction::FleeFromEnemy => {
+            let target = nearest_enemy_within(gd, cid, sight, chara.attr.view_range)?;
+            Some(NpcAction::MoveAwayFrom(gd.chara_pos(target)?))
+        }
+        AiAction::ShootEnemy => {
+            let weapon = chara.equip.item(EquipSlotKind::RangedWeapon, 0)?;
+            match effect_target(gd, cid, sight, &weapon_to_effect(weapon))? {
+                Target::Chara(target) => Some(NpcAction::Shoot(target)),
+                _ => None,
+            }
+        }
+        AiAction::ThrowItem => {
+            for (i, (item, _)) in chara.item_list.iter().enumerate() {
+                if item.obj().throw_effect.is_none() {
+                    continue;
+                }
+                let il = (ItemListLocation::Chara { cid }, i as u32);
+                let effect = item_to_throw_effect(gd, il, cid);
+                if let Some(target) = effect_target(gd, cid, sight, &effect) {
+                    return Some(NpcAction::ThrowItem(il, target));
+                }
+            }
+            None
+        }
+        AiAction::UseMagicItem => {
+            for (i, (item, _)) in chara.item_list.iter().enumerate() {
+                match item.charge() {
+                    Some(n) if n >= 1 => (),
+                    _ => continue,
+                }
+                if let Some(effect) = item.obj().magical_effect.as_ref() {
+                    if let Some(target) = effect_target(gd, cid, sight, effect) {
+                        let il = (ItemListLocation::Chara { cid }, i as u32);
+                        return Some(NpcAction::ReleaseItem(il, target));
+                    }
+                }
+            }
+            None
+        }
+        AiAction::ActiveSkill(id) => {
+            let active_skill = RULES.active_skills.get(id)?;
+            let target = effect_target(gd, cid, sight, &active_skill.effect)?;
+            Some(NpcAction::ActiveSkill(id.clone(), target))
+        }
+        AiAction::FollowLeader => {
+            let leader_pos = gd.chara_pos(chara.ai.leader?)?;
+            if distance(gd, cid, leader_pos) > 1 {
+                Some(NpcAction::MoveTo(leader_pos))
+            } else {
+                None
+            }
+        }
+        AiAction::ReturnToInitialPos => {
+            let initial_pos = chara.ai.initial_pos;
+            if gd.chara_pos(cid)? != initial_pos {
+                Some(NpcAction::MoveTo(initial_pos))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Choose the target of given effect by its target mode.
+/// Targets must be in sight and in the range of the effect, and area effects are not used
+/// against enemies if allies are in the area. Allies are chosen from the most injured one.
+fn effect_target(gd: &GameData, cid: CharaId, sight: &Sight, effect: &Effect) -> Option<Target> {
+    let range = effect.range as i32;
+    match effect.shape {
+        ShapeKind::OneTile | ShapeKind::Circle => (),
+        // Line effects are not processed by game::effect yet
+        ShapeKind::Line => return None,
+    }
+    match effect.target_mode {
+        TargetMode::None => Some(Target::None),
+        TargetMode::Enemy => charas_in_sight(gd, cid, sight, Relationship::HOSTILE, range)
+            .into_iter()
+            .find(|(_, pos)| !hits_ally(gd, cid, effect, *pos))
+            .map(|(target, _)| Target::Chara(target)),
+        TargetMode::Ally => {
+            let mut target = None;
+            let mut min_ratio = 1.0;
+            for (ally, _) in charas_in_sight(gd, cid, sight, Relationship::ALLY, range) {
+                let ratio = hp_ratio(gd.chara.get(ally));
+                if ratio < min_ratio {
+                    min_ratio = ratio;
+                    target = Some(ally);
+                }
+            }
+            target.map(Target::Chara)
+        }
+    }
+}
+
+/// The area of the effect centered at pos includes allies or not
+fn hits_ally(gd: &GameData, cid: CharaId, effect: &Effect, pos: Vec2d) -> bool {
+    if effect.shape != ShapeKind::Circle {
+        return false;
+    }
+    let map = gd.get_current_map();
+    Shape::Circle {
+        center: pos,
+        radius: effect.size,
+    }
+    .iter()
+    .into_iter()
+    .filter(|p| map.is_inside(*p))
+    .filter_map(|p| map.tile[p].chara)
+    .any(|other| gd.chara_relation(cid, other) == Relationship::ALLY)
+}
+
+fn hp_ratio(chara: &Chara) -> f32 {
+    if chara.attr.max_hp <= 0 {
+        return 1.0;
+    }
+    chara.hp as f32 / chara.attr.max_hp as f32
+}
+
+fn distance(gd: &GameData, cid: CharaId, pos: Vec2d) -> i32 {
+    if let Some(center) = gd.chara_pos(cid) {
+        center.mdistance(pos)
+    } else {
+        i32::MAX
+    }
+}
+
+fn nearest_enemy_within(gd: &GameData, cid: CharaId, sight: &Sight, range: i32) -> Option<CharaId> {
+    charas_in_sight(gd, cid, sight, Relationship::HOSTILE, range)
+        .first()
+        .map(|(target, _)| *target)
+}
+
+/// Characters that have given relationship in range and in sight, sorted by distance.
+/// Allies include the character itself.
+/// Enemies are not found until the character notices them.
+fn charas_in_sight(
+    gd: &GameData,
+    cid: CharaId,
+    sight: &Sight,
+    relation: Relationship,
+    range: i32,
+) -> Vec<(CharaId, Vec2d)> {
+    let center = if let Some(center) = gd.chara_pos(cid) {
+        center
+    } else {
+        return vec![];
+    };
+    if relation == Relationship::HOSTILE
+        && cid != CharaId::Player
+        && !gd.chara.get(cid).ai.awareness.is_hunting()
+    {
+        return vec![];
+    }
+    let mut charas: Vec<(CharaId, Vec2d)> = gd
+        .get_charas_on_map()
+        .into_iter()
+        .filter(|other| gd.chara_relation(cid, *other) == relation)
+        .filter_map(|other| gd.chara_pos(other).map(|pos| (other, pos)))
+        .filter(|(_, pos)| center.mdistance(*pos) <= range && sight.is_visible(*pos))
+        .collect();
+    charas.sort_by_key(|(_, pos)| center.mdistance(*pos));
+    charas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{self, add_chara, chara};
+    use common::objholder::WallIdx;
+
+    fn tree(kind: &str) -> &'static AiNode {
+        test_util::init();
+        let kind: NpcAIKind = serde_json::from_value(kind.into()).unwrap();
+        &RULES.npc_ai.get(kind).tree
+    }
+
+    /// The monster is hunting the player
+    fn fixture(monster_pos: Vec2d) -> (GameData, CharaId) {
+        let (mut gd, cid) = test_util::fixture(monster_pos);
+        gd.chara.get_mut(cid).ai.awareness = Awareness::Hunting {
+            target: CharaId::Player,
+            pos: Vec2d(2, 2),
+            turns: 1,
+        };
+        (gd, cid)
+    }
+
+    #[test]
+    fn flee_at_low_hp() {
+        let (mut gd, cid) = fixture(Vec2d(5, 5));
+        assert_eq!(
+            decide(&gd, cid, &Sight::new(&gd, cid), tree("coward")),
+            NpcAction::MoveTo(Vec2d(2, 2))
+        );
+        gd.chara.get_mut(cid).hp = 20;
+        assert_eq!(
+            decide(&gd, cid, &Sight::new(&gd, cid), tree("coward")),
+            NpcAction::MoveAwayFrom(Vec2d(2, 2))
+        );
+        // Out of view
+        gd.chara.get_mut(cid).attr.view_range = 2;
+        assert_eq!(
+            decide(&gd, cid, &Sight::new(&gd, cid), tree("coward")),
+            NpcAction::Wait
+        );
+    }
+
+    #[test]
+    fn guard_position() {
+        let (mut gd, cid) = fixture(Vec2d(12, 12));
+        assert_eq!(
+            decide(&gd, cid, &Sight::new(&gd, cid), tree("guard")),
+            NpcAction::Wait
+        );
+        gd.chara.get_mut(cid).ai.initial_pos = Vec2d(14, 14);
+        assert_eq!(
+            decide(&gd, cid, &Sight::new(&gd, cid), tree("guard")),
+            NpcAction::MoveTo(Vec2d(14, 14))
+        );
+        gd.get_current_map_mut()
+            .locate_chara(CharaId::Player, Vec2d(10, 10));
+        assert_eq!(
+            decide(&gd, cid, &Sight::new(&gd, cid), tree("guard")),
+            NpcAction::MoveTo(Vec2d(10, 10))
+        );
+        gd.chara.get_mut(cid).ai.initial_pos = Vec2d(2, 12);
+        assert_eq!(
+            decide(&gd, cid, &Sight::new(&gd, cid), tree("guard")),
+            NpcAction::MoveTo(Vec2d(2, 12))
+        );
+    }
+
+    #[test]
+    fn follow_leader_and_heal_ally() {
+        let (mut gd, monster) = fixture(Vec2d(14, 2));
+        let leader = add_chara(&mut gd, chara("monster", Vec2d(8, 8)), Vec2d(8, 8));
+        gd.chara.get_mut(monster).ai.leader = Some(leader);
+        assert_eq!(
+            decide(&gd, monster, &Sight::new(&gd, monster), tree("follower")),
+            NpcAction::MoveTo(Vec2d(8, 8))
+        );
+
+        gd.chara.get_mut(leader).hp = 10;
+        assert_eq!(
+            decide(&gd, monster, &Sight::new(&gd, monster), tree("caster")),
+            NpcAction::Wait
+        );
+        gd.get_current_map_mut().locate_chara(monster, Vec2d(8, 4));
+        assert_eq!(
+            decide(&gd, monster, &Sight::new(&gd, monster), tree("caster")),
+            NpcAction::ActiveSkill("heal".into(), Target::Chara(leader))
+        );
+    }
+
+    #[test]
+    fn target_in_sight_and_range() {
+        let tree: AiNode =
+            serde_json::from_str(r#"{ "action": { "active_skill": "fire_ball" } }"#).unwrap();
+        let (mut gd, monster) = fixture(Vec2d(6, 2));
+        let player = Target::Chara(CharaId::Player);
+        assert_eq!(
+            decide(&gd, monster, &Sight::new(&gd, monster), &tree),
+            NpcAction::ActiveSkill("fire_ball".into(), player)
+        );
+
+        // Blocked by a wall
+        gd.get_current_map_mut().tile[Vec2d(4, 2)].wall = WallIdxPP::new(WallIdx::default());
+        assert_eq!(
+            decide(&gd, monster, &Sight::new(&gd, monster), &tree),
+            NpcAction::Wait
+        );
+        gd.get_current_map_mut().tile[Vec2d(4, 2)].wall = WallIdxPP::empty();
+
+        // The explosion hits an ally
+        let ally = add_chara(&mut gd, chara("monster", Vec2d(2, 3)), Vec2d(2, 3));
+        assert_eq!(
+            decide(&gd, monster, &Sight::new(&gd, monster), &tree),
+            NpcAction::Wait
+        );
+        gd.get_current_map_mut().locate_chara(ally, Vec2d(12, 12));
+        assert_eq!(
+            decide(&gd, monster, &Sight::new(&gd, monster), &tree),
+            NpcAction::ActiveSkill("fire_ball".into(), player)
+        );
+
+        // Out of range
+        gd.get_current_map_mut().locate_chara(monster, Vec2d(9, 2));
+        assert_eq!(
+            decide(&gd, monster, &Sight::new(&gd, monster), &tree),
+            NpcAction::Wait
+        );
+    }
+
+    #[test]
+    fn unaware_of_enemy() {
+        let tree: AiNode = serde_json::from_str(r#"{ "action": "approach_enemy" }"#).unwrap();
+        let (mut gd, cid) = fixture(Vec2d(5, 5));
+        gd.chara.get_mut(cid).ai.awareness = Awareness::Unaware;
+        assert_eq!(
+            decide(&gd, cid, &Sight::new(&gd, cid), &tree),
+            NpcAction::Wait
+        );
+
+        // Investigate the noise
+        gd.chara.get_mut(cid).ai.awareness = Awareness::Suspicious {
+            pos: Vec2d(8, 8),
+            turns: 1,
+        };
+        assert_eq!(
+            decide(&gd, cid, &Sight::new(&gd, cid), &tree),
+            NpcAction::MoveTo(Vec2d(8, 8))
+        );
+    }
+}
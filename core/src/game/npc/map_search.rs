@@ -0,0 +1,19 @@
+//! Functions to search map information needed to determine NPC's behavior.
+
+use crate::game::map::path::next_step_dir;
+use crate::game::{Game, InfoGetter};
+use common::gamedata::*;
+use geom::*;
+
+/// Returns direction to the next step of the path to target position.
+/// If there is no path, returns the direct direction to target.
+pub fn dir_to_pos(game: &mut Game, cid: CharaId, target_pos: Vec2d) -> Direction {
+    if let Some(dir) = next_step_dir(game, cid, target_pos) {
+        return dir;
+    }
+    if let Some(pos) = game.gd.chara_pos(cid) {
+        geom::dir_by_2pos(pos, target_pos)
+    } else {
+        Direction::NONE
+    }
+}
@@ -0,0 +1,87 @@
+//! Functions for NPC's AI and actions
+
+pub mod ai;
+pub mod map_search;
+
+use self::ai::NpcAction;
+use super::action;
+use super::map::MapEx;
+use super::stealth;
+use super::view;
+use super::{Game, InfoGetter};
+use common::gamedata::*;
+use geom::*;
+use rng::*;
+use rules::RULES;
+
+pub fn process_npc_turn(game: &mut Game, cid: CharaId) {
+    let ai_kind = game.gd.chara.get(cid).ai.kind;
+    let ai_rule = RULES.npc_ai.get(ai_kind);
+    let sight = view::Sight::new(&game.gd, cid);
+    stealth::update_awareness(&mut game.gd, cid, &sight);
+
+    match ai::decide(&game.gd, cid, &sight, &ai_rule.tree) {
+        NpcAction::Wait => (),
+        NpcAction::RandomWalk => {
+            random_walk(game, cid);
+        }
+        NpcAction::MoveTo(pos) => {
+            let dir = map_search::dir_to_pos(game, cid, pos);
+            action::try_move(game, cid, dir);
+        }
+        NpcAction::MoveAwayFrom(pos) => {
+            move_away_from(game, cid, pos);
+        }
+        NpcAction::Shoot(target) => {
+            action::shoot_target(game, cid, target);
+        }
+        NpcAction::ThrowItem(il, target) => {
+            action::throw_item(game, il, cid, target);
+        }
+        NpcAction::ReleaseItem(il, target) => {
+            action::release_item(game, il, cid, target);
+        }
+        NpcAction::ActiveSkill(id, target) => {
+            action::use_active_skill(game, cid, &id, target);
+        }
+    }
+}
+
+/// Move npc at random
+fn random_walk(game: &mut Game, cid: CharaId) {
+    let dir = Direction::new(
+        *[HDirection::Left, HDirection::None, HDirection::Right]
+            .choose(&mut get_rng())
+            .unwrap(),
+        *[VDirection::Up, VDirection::None, VDirection::Down]
+            .choose(&mut get_rng())
+            .unwrap(),
+    );
+    action::try_move(game, cid, dir);
+}
+
+/// Move npc to the adjacent tile that is the farthest from given position
+fn move_away_from(game: &mut Game, cid: CharaId, from: Vec2d) {
+    let pos = if let Some(pos) = game.gd.chara_pos(cid) {
+        pos
+    } else {
+        return;
+    };
+    let map = game.gd.get_current_map();
+    let chara = game.gd.chara.get(cid);
+
+    let mut best_dir = None;
+    let mut best_distance = pos.mdistance(from);
+    for dir in &Direction::EIGHT_DIRS {
+        let p = pos + dir.as_vec();
+        let d = p.mdistance(from);
+        if d > best_distance && map.is_passable(chara, p) && map.tile[p].chara.is_none() {
+            best_dir = Some(*dir);
+            best_distance = d;
+        }
+    }
+
+    if let Some(dir) = best_dir {
+        action::try_move(game, cid, dir);
+    }
+}
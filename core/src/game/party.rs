@@ -0,0 +1,164 @@
+//! Functions for the player's party members
+
+use super::extrait::*;
+use common::gamedata::*;
+use geom::*;
+use rules::RULES;
+
+/// Party members are located within this distance from the player after switching map
+const LOCATE_RANGE: i32 = 5;
+
+/// Make the character a party member of the player.
+/// Returns the new id of the character, or None if the party is full.
+pub fn recruit(gd: &mut GameData, cid: CharaId) -> Option<CharaId> {
+    if let CharaId::Party { .. } = cid {
+        return Some(cid);
+    }
+    if gd.chara.party().len() >= RULES.chara.max_party_members as usize {
+        let chara = gd.chara.get(cid);
+        game_log_i!("party-full"; chara=chara);
+        return None;
+    }
+
+    let cid = gd.add_party_member(cid);
+    let chara = gd.chara.get_mut(cid);
+    chara.faction = FactionId::player();
+    chara.rel = Relationship::ALLY;
+    chara.ai.kind = RULES.chara.party_ai_kind;
+    chara.ai.leader = Some(CharaId::Player);
+    game_log_i!("party-join"; chara=chara);
+    Some(cid)
+}
+
+/// Locate party members around the player on the current map.
+pub fn locate_party(gd: &mut GameData) {
+    let player_pos = gd.player_pos();
+    let mut candidates: Vec<Vec2d> = MDistRangeIter::new(player_pos, LOCATE_RANGE)
+        .map(|(_, pos)| pos)
+        .filter(|pos| *pos != player_pos)
+        .collect();
+    candidates.sort_by_key(|pos| pos.mdistance(player_pos));
+
+    for cid in gd.chara.party() {
+        if gd.chara_pos(cid).is_some() {
+            continue;
+        }
+        let map = gd.get_current_map();
+        let chara = gd.chara.get(cid);
+        let pos = candidates
+            .iter()
+            .copied()
+            .find(|pos| map.is_passable(chara, *pos) && map.tile[*pos].chara.is_none());
+
+        if let Some(pos) = pos {
+            gd.get_current_map_mut().locate_chara(cid, pos);
+        } else {
+            warn!("no space to locate party member {:?}", cid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util;
+    use common::gobj;
+
+    #[test]
+    fn recruit_until_party_is_full() {
+        let _lock = test_util::lock();
+        let (mut gd, cid) = test_util::fixture(Vec2d(3, 2));
+
+        let member = recruit(&mut gd, cid).unwrap();
+        assert_eq!(member, CharaId::Party { n: 0 });
+        assert_eq!(recruit(&mut gd, member), Some(member));
+        assert_eq!(gd.chara_pos(member), Some(Vec2d(3, 2)));
+        let chara = gd.chara.get(member);
+        assert_eq!(chara.faction, FactionId::player());
+        assert_eq!(chara.ai.leader, Some(CharaId::Player));
+
+        for i in 1..RULES.chara.max_party_members {
+            let pos = Vec2d(4 + i as i32, 2);
+            let cid = test_util::add_chara(&mut gd, test_util::chara("monster", pos), pos);
+            assert!(recruit(&mut gd, cid).is_some());
+        }
+        let cid = test_util::add_chara(
+            &mut gd,
+            test_util::chara("monster", Vec2d(9, 9)),
+            Vec2d(9, 9),
+        );
+        assert_eq!(recruit(&mut gd, cid), None);
+        assert_eq!(
+            gd.chara.party().len(),
+            RULES.chara.max_party_members as usize
+        );
+    }
+
+    #[test]
+    fn locate_party_near_player() {
+        let (mut gd, _) = test_util::fixture(Vec2d(3, 2));
+        let wall = gobj::id_to_idx(test_util::WALL_ID);
+        gd.get_current_map_mut().set_wall(Vec2d(1, 2), wall);
+        gd.get_current_map_mut().set_wall(Vec2d(2, 1), wall);
+        let a = gd.add_chara(test_util::chara("!player", Vec2d(0, 0)), CharaKind::Party);
+        let b = gd.add_chara(test_util::chara("!player", Vec2d(0, 0)), CharaKind::Party);
+
+        locate_party(&mut gd);
+        assert_eq!(gd.chara_pos(a), Some(Vec2d(2, 3)));
+        let pos = gd.chara_pos(b).unwrap();
+        assert_eq!(pos.mdistance(Vec2d(2, 2)), 2);
+
+        // Located members are not moved
+        locate_party(&mut gd);
+        assert_eq!(gd.chara_pos(a), Some(Vec2d(2, 3)));
+        assert_eq!(gd.chara_pos(b), Some(pos));
+    }
+
+    #[test]
+    fn locate_party_without_space() {
+        let (mut gd, _) = test_util::fixture(Vec2d(3, 2));
+        let wall = gobj::id_to_idx(test_util::WALL_ID);
+        let map = gd.get_current_map_mut();
+        for pos in map.tile.iter_idx() {
+            if map.tile[pos].chara.is_none() {
+                map.set_wall(pos, wall);
+            }
+        }
+        let cid = gd.add_chara(test_util::chara("!player", Vec2d(0, 0)), CharaKind::Party);
+
+        locate_party(&mut gd);
+        assert_eq!(gd.chara_pos(cid), None);
+    }
+
+    #[test]
+    fn party_member_travels_between_maps() {
+        let mut gd = GameData::empty();
+        let rid = gd.region.add_region(Region::new("test", Map::new(8, 8), 0));
+        let region_mid = MapId::RegionMap { rid };
+        gd.set_initial_mapid(region_mid);
+        let sid = gd
+            .add_site(Site::new(1, None), SiteKind::Other, rid, Vec2d(1, 1))
+            .unwrap();
+        let site_mid = gd.add_map(Map::new(8, 8), sid, 0);
+
+        gd.add_chara(Chara::default(), CharaKind::Player);
+        gd.get_current_map_mut()
+            .locate_chara(CharaId::Player, Vec2d(1, 1));
+        let cid = gd.add_chara_to_map(Chara::default(), region_mid);
+        gd.get_current_map_mut().locate_chara(cid, Vec2d(2, 2));
+
+        let member = gd.add_party_member(cid);
+        assert_eq!(member, CharaId::Party { n: 0 });
+        assert_eq!(gd.chara.party(), vec![member]);
+        assert_eq!(gd.get_current_map().chara_pos(member), Some(Vec2d(2, 2)));
+        assert!(gd.get_charas_on_map().iter().all(|c| *c != cid));
+
+        // The party member is kept while OnMap characters are swapped out
+        gd.set_current_mapid(site_mid);
+        assert_eq!(gd.get_current_map().chara_pos(member), None);
+        assert_eq!(gd.chara.party(), vec![member]);
+        gd.set_current_mapid(region_mid);
+        assert_eq!(gd.get_current_map().chara_pos(member), None);
+        assert_eq!(gd.chara.party(), vec![member]);
+    }
+}
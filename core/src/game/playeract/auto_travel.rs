@@ -0,0 +1,226 @@
+//! Auto-travel moves the player to the destination step by step over multiple turns.
+
+use super::DoPlayerAction;
+use crate::game::map::path::next_step_dir;
+use crate::game::{Game, InfoGetter};
+use crate::text::ItemName;
+use common::gamedata::*;
+use geom::*;
+
+pub struct AutoTravel {
+    dest: Vec2d,
+    /// Player's HP at the previous step
+    hp: i32,
+    /// Hostile characters that were already visible when auto-travel started
+    known_hostiles: Vec<CharaId>,
+}
+
+impl AutoTravel {
+    fn new(game: &Game, dest: Vec2d) -> AutoTravel {
+        AutoTravel {
+            dest,
+            hp: game.gd.chara.get(CharaId::Player).hp,
+            known_hostiles: visible_hostiles(game),
+        }
+    }
+}
+
+impl<'a> DoPlayerAction<'a> {
+    /// Start auto-travel to dest, and take the first step.
+    /// If dest is unreachable, try to move toward dest directly.
+    pub fn move_to(&mut self, dest: Vec2d) {
+        if dest == self.gd().player_pos() {
+            self.stop_auto_travel();
+            return;
+        }
+        let travel = match self.0.auto_travel.take() {
+            Some(travel) if travel.dest == dest => travel,
+            _ => AutoTravel::new(self.0, dest),
+        };
+        self.0.auto_travel = Some(travel);
+
+        if next_step_dir(self.0, CharaId::Player, dest).is_none() {
+            self.stop_auto_travel();
+            self.step_toward(dest);
+            return;
+        }
+        self.continue_auto_travel();
+    }
+
+    /// Take the next step of auto-travel.
+    /// Returns false if auto-travel is not active or has been stopped.
+    pub fn continue_auto_travel(&mut self) -> bool {
+        let dest = if let Some(travel) = self.0.auto_travel.as_ref() {
+            travel.dest
+        } else {
+            return false;
+        };
+
+        if self.check_auto_travel_interrupted() {
+            self.stop_auto_travel();
+            return false;
+        }
+
+        let dir = if let Some(dir) = next_step_dir(self.0, CharaId::Player, dest) {
+            dir
+        } else {
+            self.stop_auto_travel();
+            return false;
+        };
+
+        let prev_pos = self.gd().player_pos();
+        self.try_move(dir);
+        let pos = self.gd().player_pos();
+        if pos == prev_pos || pos == dest {
+            self.stop_auto_travel();
+            return pos != prev_pos;
+        }
+
+        // Stop on the tile that has items
+        let gd = self.gd();
+        if let Some((item, _)) = gd.item_on_player_tile().iter().next() {
+            let player = gd.chara.get(CharaId::Player);
+            game_log_i!("auto_travel-item-found"; chara=player, item=ItemName::new(gd, item));
+            self.stop_auto_travel();
+        }
+        true
+    }
+
+    pub fn stop_auto_travel(&mut self) {
+        self.0.auto_travel = None;
+    }
+
+    /// Check player's situation, and returns true if auto-travel should be stopped
+    fn check_auto_travel_interrupted(&mut self) -> bool {
+        let hostiles = visible_hostiles(self.0);
+        let gd = &self.0.gd;
+        let player = gd.chara.get(CharaId::Player);
+        let travel = self.0.auto_travel.as_mut().unwrap();
+
+        if let Some(cid) = hostiles
+            .iter()
+            .find(|cid| !travel.known_hostiles.contains(cid))
+        {
+            game_log_i!("auto_travel-hostile-found"; chara=player, target=gd.chara.get(*cid));
+            return true;
+        }
+        travel.known_hostiles = hostiles;
+
+        if player.hp < travel.hp {
+            game_log_i!("auto_travel-damaged"; chara=player);
+            return true;
+        }
+        travel.hp = player.hp;
+
+        false
+    }
+}
+
+/// Hostile characters in player's view
+fn visible_hostiles(game: &Game) -> Vec<CharaId> {
+    let gd = &game.gd;
+    let map = gd.get_current_map();
+    map.iter_charaid()
+        .filter(|&&cid| {
+            if cid == CharaId::Player
+                || gd.chara_relation(CharaId::Player, cid) != Relationship::HOSTILE
+            {
+                return false;
+            }
+            if let Some(pos) = map.chara_pos(cid) {
+                game.view_map.get_tile_visible(pos)
+            } else {
+                false
+            }
+        })
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util;
+    use common::gobj;
+
+    /// The whole region map is visible, so the monster at (15, 15) is known
+    /// from the start and does not stop auto-travel
+    fn start_game() -> Game {
+        let (gd, _) = test_util::fixture(Vec2d(15, 15));
+        let mut game = test_util::game(gd);
+        game.update_before_player_turn();
+        game
+    }
+
+    /// Continue auto-travel until it stops, and returns the number of steps
+    fn travel(game: &mut Game, dest: Vec2d) -> u32 {
+        DoPlayerAction::new(game).move_to(dest);
+        let mut steps = 1;
+        loop {
+            game.advance_until_player_turn();
+            if !DoPlayerAction::new(game).continue_auto_travel() {
+                return steps;
+            }
+            steps += 1;
+        }
+    }
+
+    #[test]
+    fn travel_to_destination() {
+        let _lock = test_util::lock();
+        let mut game = start_game();
+
+        assert_eq!(travel(&mut game, Vec2d(5, 2)), 3);
+        assert_eq!(game.gd.player_pos(), Vec2d(5, 2));
+        assert!(game.auto_travel.is_none());
+    }
+
+    #[test]
+    fn stop_on_item() {
+        let _lock = test_util::lock();
+        let mut game = start_game();
+        let map = game.gd.get_current_map_mut();
+        for pos in RectIter::new((1, 1), (3, 3)) {
+            if pos != Vec2d(2, 2) {
+                let idx = gobj::id_to_idx(test_util::TORCH_ID);
+                let item = crate::game::item::gen::gen_item_from_idx(idx, 1);
+                map.locate_item(item, pos, 1);
+            }
+        }
+
+        assert_eq!(travel(&mut game, Vec2d(6, 2)), 1);
+        assert!(game.gd.is_item_on_player_tile());
+        assert!(game.auto_travel.is_none());
+    }
+
+    #[test]
+    fn stop_when_damaged() {
+        let _lock = test_util::lock();
+        let mut game = start_game();
+
+        DoPlayerAction::new(&mut game).move_to(Vec2d(8, 2));
+        game.advance_until_player_turn();
+        let pos = game.gd.player_pos();
+        game.gd.chara.get_mut(CharaId::Player).hp -= 10;
+
+        assert!(!DoPlayerAction::new(&mut game).continue_auto_travel());
+        assert_eq!(game.gd.player_pos(), pos);
+        assert!(game.auto_travel.is_none());
+    }
+
+    #[test]
+    fn stop_when_hostile_appears() {
+        let _lock = test_util::lock();
+        let mut game = start_game();
+
+        DoPlayerAction::new(&mut game).move_to(Vec2d(8, 2));
+        game.advance_until_player_turn();
+        let pos = game.gd.player_pos();
+        let monster = test_util::chara("monster", Vec2d(10, 10));
+        test_util::add_chara(&mut game.gd, monster, Vec2d(10, 10));
+
+        assert!(!DoPlayerAction::new(&mut game).continue_auto_travel());
+        assert_eq!(game.gd.player_pos(), pos);
+        assert!(game.auto_travel.is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use super::DoPlayerAction;
 use crate::game::map::MapEx;
+use crate::game::replay::RecordedInput;
 use crate::game::{action, DialogOpenRequest, InfoGetter};
 use crate::text::ToText;
 use common::gamedata::*;
@@ -48,7 +49,8 @@ impl<'a> DoPlayerAction<'a> {
         }
     }
 
-    pub fn move_to(&mut self, dest: Vec2d) {
+    /// Move one step toward dest without path finding
+    pub(super) fn step_toward(&mut self, dest: Vec2d) {
         let d = dest - self.gd().player_pos();
         let hdir = if d.0 < 0 {
             HDirection::Left
@@ -96,6 +98,12 @@ impl<'a> DoPlayerAction<'a> {
     /// This function will be called when players use stairs or try to exit from map boundaries.
     /// In the latter case, dir is not None and represents player's move direction.
     pub fn goto_next_floor(&mut self, dir: Direction, dialog: bool) {
+        self.record(RecordedInput::GotoNextFloor { dir, dialog }, |pa| {
+            pa.switch_floor(dir, dialog)
+        });
+    }
+
+    fn switch_floor(&mut self, dir: Direction, dialog: bool) {
         enum LogMessage {
             ExitToOutside,
             EnterSite(String),
@@ -70,7 +70,7 @@ impl<'a> DoPlayerAction<'a> {
                 self.0
                     .anim_queue
                     .push(Animation::img_onetile(*MINING_ANIM_IDX, pos));
-                audio::play_sound("mining");
+                crate::audio::play_sound("mining");
                 self.0.finish_player_turn();
             }
         }
@@ -0,0 +1,590 @@
+//! Quest handlings
+
+use super::chara::gen::{choose_npc_chara_template, create_chara};
+use super::Game;
+use crate::text::ItemName;
+use common::gamedata::*;
+use common::gobj;
+use common::obj::StoryQuestObject;
+use common::objholder::{CharaTemplateIdx, ItemIdx};
+use common::script::Trigger;
+use rng::{GameRng, SliceRandom};
+use rules::quest::{QuestKind, QuestTemplate};
+use rules::RULES;
+use std::collections::HashMap;
+
+/// Try to generate a quest from other templates if generation failed
+const MAX_GEN_TRY: usize = 5;
+
+/// Game events that make progress of quests
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QuestEvent {
+    /// A character died
+    CharaDied { cid: CharaId, idx: CharaTemplateIdx },
+    /// The player created an item
+    ItemCreated(ItemIdx),
+    /// The player entered a map
+    MapEntered(MapId),
+    /// The player reported to the quest client
+    Report,
+    /// Game time passed
+    TimePassed,
+}
+
+/// Update quest list of current town
+pub fn update_town_quest(gd: &mut GameData) {
+    let quests: Vec<Quest> = (0..RULES.quest.n_town_quests)
+        .filter_map(|_| gen_quest(gd))
+        .collect();
+
+    current_town_mut(gd).quests = quests;
+}
+
+/// Returns available quest in the current town
+pub fn available_quests(gd: &GameData) -> &[Quest] {
+    let mid = gd.get_current_mapid();
+    let town = match gd.region.get_site(mid.sid()).content {
+        SiteContent::Town { ref town } => town,
+        _ => unreachable!(),
+    };
+
+    town.quests.as_ref()
+}
+
+/// Undertake quest in the current town. Returns false if the quest cannot be started.
+pub fn undertake_quest(game: &mut Game, i: u32) -> bool {
+    let gd = &mut game.gd;
+    let mut quest = current_town_mut(gd).quests[i as usize].clone();
+
+    match &mut quest {
+        Quest::Deliver { idx, .. } => {
+            let item = super::item::gen::gen_item_from_idx(*idx, 1);
+            game_log_i!("quest-deliver-receive-item"; item=ItemName::new(gd, &item));
+            gd.chara.get_mut(CharaId::Player).item_list.append(item, 1);
+        }
+        Quest::Escort { idx, cid, .. } => {
+            if let Some(escortee) = spawn_escortee(gd, *idx) {
+                *cid = Some(escortee);
+            } else {
+                return false;
+            }
+        }
+        _ => (),
+    }
+
+    current_town_mut(gd).quests.remove(i as usize);
+    gd.quest.start_new_quest(quest);
+    true
+}
+
+/// Update quests by the event, and start hooked scripts if any quest is completed
+pub fn notify(game: &mut Game, event: QuestEvent) {
+    if process_event(&mut game.gd, event) {
+        super::hook::trigger(game, &Trigger::QuestCompleted);
+    }
+}
+
+/// Update quests by the event. Returns true if any quest is completed.
+pub fn process_event(gd: &mut GameData, event: QuestEvent) -> bool {
+    let mut completed = false;
+
+    for (state, quest) in update_quests(gd, event) {
+        match state {
+            QuestState::Completed => {
+                completed = true;
+                if let Quest::SlayMonsters { idx, goal, .. } = quest {
+                    game_log_i!("quest-complete-slay_monsters"; monster=idx, n=goal);
+                } else {
+                    game_log_i!("quest-complete"; quest=quest);
+                }
+                // The escortee leaves the party after arriving together
+                if let Quest::Escort { cid: Some(cid), .. } = quest {
+                    let chara = gd.chara.get(cid);
+                    game_log_i!("party-leave"; chara=chara);
+                    gd.remove_chara(cid);
+                }
+            }
+            QuestState::Failed => quest_failed(gd, quest),
+            _ => (),
+        }
+    }
+
+    completed
+}
+
+/// Fail all active quests except story quests. Used by scripts.
+pub fn fail_active_quests(gd: &mut GameData) {
+    let mut failed = Vec::new();
+    for (state, quest) in gd.quest.iter_mut() {
+        if *state == QuestState::Active {
+            *state = QuestState::Failed;
+            failed.push(quest.clone());
+        }
+    }
+    for quest in failed {
+        quest_failed(gd, quest);
+    }
+}
+
+fn quest_failed(gd: &mut GameData, quest: Quest) {
+    game_log_i!("quest-failed"; quest=quest);
+    // The escortee leaves the party if still alive
+    if let Quest::Escort { cid: Some(cid), .. } = quest {
+        if gd.chara.party().contains(&cid) {
+            let chara = gd.chara.get(cid);
+            game_log_i!("party-leave"; chara=chara);
+            gd.remove_chara(cid);
+        }
+    }
+}
+
+/// Update states of active quests by the event. Returns quests whose state is changed.
+fn update_quests(gd: &mut GameData, event: QuestEvent) -> Vec<(QuestState, Quest)> {
+    let now = gd.time.current_time();
+    let mut changed = Vec::new();
+
+    for (state, quest) in gd.quest.iter_mut() {
+        if *state != QuestState::Active {
+            continue;
+        }
+
+        if matches!(quest.deadline(), Some(deadline) if deadline < now) {
+            *state = QuestState::Failed;
+            changed.push((*state, quest.clone()));
+            continue;
+        }
+
+        let new_state = match quest {
+            Quest::SlayMonsters {
+                idx, goal, killed, ..
+            } => match event {
+                QuestEvent::CharaDied { cid, idx: dead }
+                    if dead == *idx && !matches!(cid, CharaId::Party { .. }) =>
+                {
+                    *killed += 1;
+                    if *killed == *goal {
+                        Some(QuestState::Completed)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            },
+            Quest::Deliver {
+                idx, destination, ..
+            } => match event {
+                QuestEvent::MapEntered(mid) if is_in_site(mid, *destination) => {
+                    let il = &mut gd.chara.get_mut(CharaId::Player).item_list;
+                    if il.count(*idx) > 0 {
+                        il.consume(*idx, 1, |_, _| (), false);
+                        Some(QuestState::Completed)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            },
+            Quest::Fetch { idx, goal, .. } => match event {
+                QuestEvent::Report => {
+                    let il = &mut gd.chara.get_mut(CharaId::Player).item_list;
+                    if il.count(*idx) >= *goal {
+                        il.consume(*idx, *goal, |_, _| (), false);
+                        Some(QuestState::Completed)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            },
+            Quest::Escort {
+                cid: Some(escortee),
+                destination,
+                ..
+            } => match event {
+                QuestEvent::CharaDied { cid, .. } if cid == *escortee => Some(QuestState::Failed),
+                QuestEvent::MapEntered(mid)
+                    if is_in_site(mid, *destination)
+                        && gd.region.get_map(mid).chara_pos(*escortee).is_some() =>
+                {
+                    Some(QuestState::Completed)
+                }
+                _ => None,
+            },
+            Quest::Escort { cid: None, .. } => None,
+            Quest::ExploreFloor { sid, floor, .. } => match event {
+                QuestEvent::MapEntered(mid) if is_in_site(mid, *sid) && mid.floor() >= *floor => {
+                    Some(QuestState::Completed)
+                }
+                _ => None,
+            },
+            Quest::Craft {
+                idx, goal, crafted, ..
+            } => match event {
+                QuestEvent::ItemCreated(created) if created == *idx => {
+                    *crafted += 1;
+                    if *crafted == *goal {
+                        Some(QuestState::Completed)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            },
+        };
+
+        if let Some(new_state) = new_state {
+            *state = new_state;
+            changed.push((new_state, quest.clone()));
+        }
+    }
+
+    changed
+}
+
+/// Receive rewards of completed quests.
+/// Quests completed by reporting must be updated by `QuestEvent::Report` before this.
+pub fn receive_rewards(gd: &mut GameData) -> bool {
+    let mut money = 0;
+    let mut n_completed = 0;
+    let mut exist_completed_quest = false;
+
+    for (state, quest) in gd.quest.iter_mut() {
+        if *state == QuestState::Completed {
+            exist_completed_quest = true;
+            n_completed += 1;
+            let reward = quest.reward();
+            money += reward.money;
+            *state = QuestState::RewardReceived;
+        }
+    }
+
+    // Failed quests are removed when the player reports to the client
+    gd.quest.remove_reward_received();
+
+    if exist_completed_quest {
+        gd.player.add_money(money);
+        // Completed quests develop the town
+        let mid = gd.get_current_mapid();
+        if !mid.is_region_map() {
+            if let SiteContent::Town { town } = &mut gd.region.get_site_mut(mid.sid()).content {
+                let development = RULES.town.development_per_quest * n_completed;
+                town.development = town.development.saturating_add(development);
+            }
+        }
+        game_log_i!("quest-reward-receive-money"; money=money);
+    }
+    exist_completed_quest
+}
+
+/// Move the story quest to given stage. Used by scripts.
+pub fn set_story_quest_stage(gd: &mut GameData, id: &str, stage: &str) {
+    let obj: &StoryQuestObject = if let Some(obj) = gobj::get_by_id_checked(id) {
+        obj
+    } else {
+        warn!("unknown story quest \"{}\"", id);
+        return;
+    };
+    if !obj.has_stage(stage) {
+        warn!("unknown stage \"{}\" of story quest \"{}\"", stage, id);
+        return;
+    }
+
+    let now = gd.time.current_time();
+    if gd.quest.set_story_quest_stage(id, stage, now) {
+        let quest = gd.quest.story_quest(id).unwrap();
+        game_log_i!("quest-updated"; quest=quest);
+    }
+}
+
+/// Complete or fail the story quest. Used by scripts.
+/// Returns false if the quest is not active.
+pub fn finish_story_quest(gd: &mut GameData, id: &str, state: QuestState) -> bool {
+    if !gd.quest.finish_story_quest(id, state) {
+        warn!("story quest \"{}\" is not active", id);
+        return false;
+    }
+
+    let quest = gd.quest.story_quest(id).unwrap();
+    if state == QuestState::Completed {
+        game_log_i!("quest-complete"; quest=quest);
+    } else {
+        game_log_i!("quest-failed"; quest=quest);
+    }
+    true
+}
+
+fn current_town_mut(gd: &mut GameData) -> &mut Town {
+    let mid = gd.get_current_mapid();
+    match gd.region.get_site_mut(mid.sid()).content {
+        SiteContent::Town { ref mut town } => town,
+        _ => unreachable!(),
+    }
+}
+
+fn is_in_site(mid: MapId, sid: SiteId) -> bool {
+    !mid.is_region_map() && mid.sid() == sid
+}
+
+/// Generate a quest from templates available for the player's level
+fn gen_quest(gd: &GameData) -> Option<Quest> {
+    let level = gd.chara.get(CharaId::Player).level;
+    let mut templates: Vec<(&String, &QuestTemplate)> = RULES
+        .quest
+        .templates
+        .iter()
+        .filter(|(_, template)| template.min_level <= level)
+        .collect();
+    templates.sort_by_key(|(id, _)| *id);
+
+    // Some kinds of quests cannot be generated in the current situation
+    for _ in 0..MAX_GEN_TRY {
+        let (_, template) = templates
+            .choose_weighted(&mut GameRng, |(_, template)| template.weight)
+            .ok()?;
+        if let Some(quest) = gen_quest_from_template(gd, template, level) {
+            return Some(quest);
+        }
+    }
+    None
+}
+
+fn gen_quest_from_template(gd: &GameData, template: &QuestTemplate, level: u32) -> Option<Quest> {
+    let goal = rng::gen_range_inclusive(template.goal[0], template.goal[1]);
+    let reward = |goal: u32| Reward {
+        money: template.reward_money
+            + template.reward_money_per_level * level as i64
+            + template.reward_money_per_goal * goal as i64,
+        item: Vec::new(),
+    };
+    let deadline = template.deadline_days.map(|days| {
+        let mut deadline = gd.time.current_time();
+        deadline.advance(days as u64 * SECS_PER_DAY);
+        deadline
+    });
+    let targets = &template.targets;
+
+    let quest = match template.kind {
+        QuestKind::SlayMonsters => Quest::SlayMonsters {
+            reward: reward(goal),
+            idx: chara_target(targets, &RULES.quest.slay_race_probability, level)?,
+            goal,
+            killed: 0,
+            deadline,
+        },
+        QuestKind::Deliver => Quest::Deliver {
+            reward: reward(goal),
+            deadline,
+            idx: item_target(targets, level)?,
+            destination: other_town(gd)?,
+        },
+        QuestKind::Fetch => Quest::Fetch {
+            reward: reward(goal),
+            deadline,
+            idx: item_target(targets, level)?,
+            goal,
+        },
+        QuestKind::Escort => Quest::Escort {
+            reward: reward(goal),
+            deadline,
+            idx: chara_target(targets, &RULES.quest.escort_race_probability, level)?,
+            cid: None,
+            destination: other_town(gd)?,
+        },
+        QuestKind::ExploreFloor => {
+            let rid = gd.get_current_mapid().rid();
+            let sid = *gd
+                .region
+                .get(rid)
+                .get_site_ids(SiteKind::AutoGenDungeon)
+                .choose(&mut GameRng)?;
+            let goal = goal.min(gd.region.get_site(sid).max_floor()).max(1);
+            Quest::ExploreFloor {
+                reward: reward(goal),
+                deadline,
+                sid,
+                floor: goal - 1,
+            }
+        }
+        QuestKind::Craft => Quest::Craft {
+            reward: reward(goal),
+            deadline,
+            idx: craft_target(targets, level)?,
+            goal,
+            crafted: 0,
+        },
+    };
+    Some(quest)
+}
+
+fn chara_target(
+    targets: &[String],
+    race_probability: &HashMap<String, f32>,
+    level: u32,
+) -> Option<CharaTemplateIdx> {
+    if !targets.is_empty() {
+        return gobj::id_to_idx_checked(targets.choose(&mut GameRng)?);
+    }
+
+    let available = gobj::get_objholder()
+        .chara_template
+        .iter()
+        .any(|ct| race_probability.contains_key(&ct.race));
+    if available {
+        Some(choose_npc_chara_template(race_probability, level))
+    } else {
+        None
+    }
+}
+
+fn item_target(targets: &[String], level: u32) -> Option<ItemIdx> {
+    if !targets.is_empty() {
+        return gobj::id_to_idx_checked(targets.choose(&mut GameRng)?);
+    }
+
+    super::item::gen::gen_item_by_level(level, |_| 1.0, false).map(|item| item.idx)
+}
+
+/// Choose an item from recipes that the player can create at the level
+fn craft_target(targets: &[String], level: u32) -> Option<ItemIdx> {
+    if !targets.is_empty() {
+        return gobj::id_to_idx_checked(targets.choose(&mut GameRng)?);
+    }
+
+    let products: Vec<&str> = CreationKind::ALL
+        .iter()
+        .flat_map(|kind| RULES.creation.recipes(*kind))
+        .filter(|recipe| recipe.difficulty <= level)
+        .map(|recipe| recipe.product.as_str())
+        .collect();
+    gobj::id_to_idx_checked(products.choose(&mut GameRng)?)
+}
+
+/// Choose a town in the current region except the current site
+fn other_town(gd: &GameData) -> Option<SiteId> {
+    let mid = gd.get_current_mapid();
+    let towns: Vec<SiteId> = gd
+        .region
+        .get(mid.rid())
+        .get_site_ids(SiteKind::Town)
+        .into_iter()
+        .filter(|sid| mid.is_region_map() || *sid != mid.sid())
+        .collect();
+    towns.choose(&mut GameRng).copied()
+}
+
+/// Spawn a character near the player, and make it a party member
+fn spawn_escortee(gd: &mut GameData, idx: CharaTemplateIdx) -> Option<CharaId> {
+    let ct = gobj::get_obj(idx);
+    let chara = create_chara(idx, ct.gen_level, FactionId::player(), None);
+    let pos = super::map::empty_tile_near_player(gd, &chara)?;
+    let mid = gd.get_current_mapid();
+    let cid = gd.add_chara_to_map(chara, mid);
+    gd.get_current_map_mut().locate_chara(cid, pos);
+
+    let new_cid = super::party::recruit(gd, cid);
+    if new_cid.is_none() {
+        gd.remove_chara(cid);
+    }
+    new_cid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util;
+    use geom::Vec2d;
+
+    #[test]
+    fn progress_by_events() {
+        let mut gd = GameData::empty();
+        let rid = gd.region.add_region(Region::new("test", Map::new(8, 8), 0));
+        gd.set_initial_mapid(MapId::RegionMap { rid });
+        let sid = gd
+            .add_site(
+                Site::new(3, None),
+                SiteKind::AutoGenDungeon,
+                rid,
+                Vec2d(1, 1),
+            )
+            .unwrap();
+        let floor0 = gd.add_map(Map::new(8, 8), sid, 0);
+        let floor1 = gd.add_map(Map::new(8, 8), sid, 0);
+
+        let idx = ItemIdx::from_usize(0);
+        gd.quest.start_new_quest(Quest::ExploreFloor {
+            reward: Reward::default(),
+            deadline: None,
+            sid,
+            floor: 1,
+        });
+        gd.quest.start_new_quest(Quest::Craft {
+            reward: Reward::default(),
+            deadline: None,
+            idx,
+            goal: 2,
+            crafted: 0,
+        });
+        gd.quest.start_new_quest(Quest::ExploreFloor {
+            reward: Reward::default(),
+            deadline: Some(gd.time.current_time()),
+            sid,
+            floor: 0,
+        });
+        gd.time.advance(1);
+
+        let states = |gd: &GameData| gd.quest.iter().map(|(s, _)| *s).collect::<Vec<_>>();
+        let changed = |changed: Vec<(QuestState, Quest)>| {
+            changed.into_iter().map(|(s, _)| s).collect::<Vec<_>>()
+        };
+
+        // The expired quest fails before it is completed
+        let result = update_quests(&mut gd, QuestEvent::MapEntered(floor0));
+        assert_eq!(changed(result), vec![QuestState::Failed]);
+
+        let result = update_quests(&mut gd, QuestEvent::ItemCreated(idx));
+        assert!(result.is_empty());
+        let result = update_quests(&mut gd, QuestEvent::MapEntered(floor1));
+        assert_eq!(changed(result), vec![QuestState::Completed]);
+        assert_eq!(
+            states(&gd),
+            vec![
+                QuestState::Completed,
+                QuestState::Active,
+                QuestState::Failed
+            ]
+        );
+        if let Some((_, Quest::Craft { crafted, .. })) = gd.quest.iter().nth(1) {
+            assert_eq!(*crafted, 1);
+        } else {
+            unreachable!();
+        }
+
+        gd.quest.remove_reward_received();
+        assert_eq!(states(&gd), vec![QuestState::Completed, QuestState::Active]);
+    }
+
+    #[test]
+    fn escortee_leaves_when_escort_fails() {
+        let _lock = test_util::lock();
+        let (mut gd, _) = test_util::fixture(Vec2d(12, 12));
+        let cid = test_util::add_chara(
+            &mut gd,
+            test_util::chara("player", Vec2d(3, 2)),
+            Vec2d(3, 2),
+        );
+        let cid = gd.add_party_member(cid);
+        gd.quest.start_new_quest(Quest::Escort {
+            reward: Reward::default(),
+            deadline: Some(gd.time.current_time()),
+            idx: CharaTemplateIdx::from_usize(0),
+            cid: Some(cid),
+            destination: SiteId::default(),
+        });
+        gd.time.advance(1);
+
+        assert!(!process_event(&mut gd, QuestEvent::TimePassed));
+        assert_eq!(gd.quest.iter().next().unwrap().0, QuestState::Failed);
+        assert!(gd.chara.party().is_empty());
+        assert!(gd.get_current_map().chara_pos(cid).is_none());
+    }
+}
@@ -0,0 +1,459 @@
+//! Recording player inputs, and replaying them without window.
+//! All inputs given to `DoPlayerAction` that change the game are recorded.
+
+use super::newgame::NewGameBuilder;
+use super::{Command, DialogOpenRequest, DoPlayerAction, Game, InfoGetter, Target, UiRequest};
+use common::gamedata::*;
+use common::objholder::ItemIdx;
+use geom::*;
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// An input from the player that changes the game
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedInput {
+    Command(Command),
+    ContinueAutoTravel,
+    StopAutoTravel,
+    /// Answer to a yes/no dialog
+    YesNo(bool),
+    /// Tile selected in targeting mode
+    Target(Vec2d),
+    PickUpItem {
+        il: ItemLocation,
+        n: ItemMoveNum,
+    },
+    DropItem {
+        il: ItemLocation,
+        n: u32,
+    },
+    ThrowItem {
+        il: ItemLocation,
+    },
+    DrinkItem {
+        il: ItemLocation,
+    },
+    EatItem {
+        il: ItemLocation,
+    },
+    UseItem {
+        il: ItemLocation,
+    },
+    ReadItem {
+        il: ItemLocation,
+    },
+    ReleaseItem {
+        il: ItemLocation,
+    },
+    BuyItem {
+        il: ItemLocation,
+        cid: CharaId,
+    },
+    SellItem {
+        il: ItemLocation,
+        cid: CharaId,
+    },
+    ChangeEquipment {
+        cid: CharaId,
+        slot: (EquipSlotKind, u8),
+        il: ItemLocation,
+    },
+    Talk {
+        dir: Direction,
+    },
+    AdvanceTalk {
+        choice: Option<u32>,
+    },
+    AdvanceScript,
+    UndertakeQuest {
+        i: u32,
+    },
+    StartCreation {
+        kind: CreationKind,
+        recipe: Recipe,
+        ill: ItemListLocation,
+        prior_high_quality: bool,
+        material_to_use: Option<ItemIdx>,
+    },
+    GotoNextFloor {
+        dir: Direction,
+        dialog: bool,
+    },
+    Restart,
+    SetTarget {
+        pos: Vec2d,
+    },
+    /// Text given to the debug command dialog
+    DebugCommand(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recording {
+    pub player_name: String,
+    pub chara_class: CharaClass,
+    pub seed: u64,
+    pub inputs: Vec<RecordedInput>,
+    /// Hash of GameData at the end of recording
+    pub hash: u64,
+}
+
+pub struct Recorder {
+    path: PathBuf,
+    recording: Recording,
+}
+
+impl Game {
+    /// Start recording inputs of the current game.
+    /// Must be called just after starting a new game.
+    pub fn start_recording(&mut self, path: &Path) {
+        info!("Start recording to {:?}", path.to_string_lossy());
+        let recording = Recording {
+            player_name: self.gd.player_name().to_owned(),
+            chara_class: self.gd.chara.get(CharaId::Player).class,
+            seed: self.gd.rng.seed(),
+            inputs: Vec::new(),
+            hash: 0,
+        };
+        self.recorder = Some(Recorder {
+            path: path.to_owned(),
+            recording,
+        });
+    }
+
+    /// Write the recording to the file with the hash of the current GameData
+    pub fn finish_recording(&mut self) {
+        let mut recorder = if let Some(recorder) = self.recorder.take() {
+            recorder
+        } else {
+            return;
+        };
+        recorder.recording.hash = gamedata_hash(&self.gd);
+
+        let result = File::create(&recorder.path)
+            .map(BufWriter::new)
+            .and_then(|file| {
+                serde_json::to_writer_pretty(file, &recorder.recording).map_err(|e| e.into())
+            });
+        match result {
+            Ok(_) => info!("Recorded to {:?}", recorder.path.to_string_lossy()),
+            Err(e) => warn!(
+                "Failed to write recording to {:?}: {}",
+                recorder.path.to_string_lossy(),
+                e
+            ),
+        }
+    }
+}
+
+impl<'a> DoPlayerAction<'a> {
+    /// Record the input, and execute it by given function.
+    /// Inputs given while executing it are parts of this input, so they are not recorded.
+    pub(super) fn record<T>(
+        &mut self,
+        input: RecordedInput,
+        f: impl FnOnce(&mut DoPlayerAction) -> T,
+    ) -> T {
+        let mut recorder = self.0.recorder.take();
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.recording.inputs.push(input);
+        }
+        let result = f(self);
+        if recorder.is_some() {
+            self.0.recorder = recorder;
+        }
+        result
+    }
+
+    /// Execute a command that acts in the game without any window.
+    /// Returns false if the command needs to be processed by windows.
+    pub fn exec_game_command(&mut self, command: &Command) -> bool {
+        // Other commands than MoveTo cancel auto-travel
+        match command {
+            Command::Move { .. }
+            | Command::Shoot { .. }
+            | Command::UseTool { .. }
+            | Command::Enter
+            | Command::Search => self.stop_auto_travel(),
+            Command::MoveTo { .. } => (),
+            _ => return false,
+        }
+
+        self.record(RecordedInput::Command(command.clone()), |pa| {
+            match *command {
+                Command::Move { dir } => {
+                    pa.try_move(dir);
+                }
+                Command::MoveTo { dest } => {
+                    pa.move_to(dest);
+                }
+                Command::Shoot { target } => {
+                    pa.shoot(target);
+                }
+                Command::UseTool { target } => {
+                    pa.use_tool(target);
+                }
+                Command::Enter => {
+                    // If player is on stairs, move from this map
+                    if pa.gd().on_map_entrance() {
+                        pa.goto_next_floor(Direction::none(), true);
+                    }
+                }
+                Command::Search => {
+                    pa.search();
+                }
+                _ => unreachable!(),
+            }
+        });
+        true
+    }
+
+    /// Take the next step of auto-travel if it is active
+    pub fn step_auto_travel(&mut self) {
+        if self.0.auto_travel.is_some() {
+            self.record(RecordedInput::ContinueAutoTravel, |pa| {
+                pa.continue_auto_travel();
+            });
+        }
+    }
+
+    /// Stop auto-travel by player's other inputs
+    pub fn interrupt_auto_travel(&mut self) {
+        if self.0.auto_travel.is_some() {
+            self.record(RecordedInput::StopAutoTravel, |pa| pa.stop_auto_travel());
+        }
+    }
+
+    pub fn answer_yes_no(
+        &mut self,
+        callback: &mut dyn FnMut(&mut DoPlayerAction, bool),
+        answer: bool,
+    ) {
+        self.record(RecordedInput::YesNo(answer), |pa| callback(pa, answer));
+    }
+
+    /// Give the selected tile to the callback of targeting mode
+    pub fn select_target(&mut self, callback: &dyn Fn(&mut DoPlayerAction, Target), pos: Vec2d) {
+        self.record(RecordedInput::Target(pos), |pa| {
+            callback(pa, Target::Tile(pos))
+        });
+    }
+}
+
+/// Replay the recorded inputs from a new game
+pub fn replay(recording: &Recording) -> Game {
+    let mut builder = NewGameBuilder::new();
+    builder.set_player_name(&recording.player_name);
+    builder.set_chara_class(recording.chara_class);
+    builder.set_seed(recording.seed);
+
+    let mut game = Game::new(builder.build());
+    game.autosave = false;
+    game.update_before_player_turn();
+    game.start_new_game();
+    replay_inputs(&mut game, &recording.inputs);
+    game
+}
+
+/// Give the recorded inputs to the game
+pub fn replay_inputs(game: &mut Game, inputs: &[RecordedInput]) {
+    for input in inputs {
+        game.advance_until_player_turn();
+        let dialog_open_request = game.pop_dialog_open_request();
+        let mut targeting_callback = None;
+        while let Some(req) = game.pop_ui_request() {
+            if let UiRequest::StartTargeting { callback, .. } = req {
+                targeting_callback = Some(callback);
+            }
+        }
+        let mut pa = DoPlayerAction::new(game);
+
+        match input {
+            RecordedInput::Command(command) => {
+                pa.exec_game_command(command);
+            }
+            RecordedInput::ContinueAutoTravel => pa.step_auto_travel(),
+            RecordedInput::StopAutoTravel => pa.interrupt_auto_travel(),
+            RecordedInput::YesNo(answer) => {
+                if let Some(DialogOpenRequest::YesNo { mut callback, .. }) = dialog_open_request {
+                    pa.answer_yes_no(&mut *callback, *answer);
+                } else {
+                    warn!("Recorded yes/no answer without yes/no dialog");
+                }
+            }
+            RecordedInput::Target(pos) => {
+                if let Some(callback) = targeting_callback {
+                    pa.select_target(&*callback, *pos);
+                } else {
+                    warn!("Recorded target without targeting mode");
+                }
+            }
+            RecordedInput::PickUpItem { il, n } => {
+                pa.pick_up_item(*il, *n);
+            }
+            RecordedInput::DropItem { il, n } => {
+                pa.drop_item(*il, *n);
+            }
+            RecordedInput::ThrowItem { il } => pa.throw_item(*il),
+            RecordedInput::DrinkItem { il } => pa.drink_item(*il),
+            RecordedInput::EatItem { il } => pa.eat_item(*il),
+            RecordedInput::UseItem { il } => pa.use_item(*il),
+            RecordedInput::ReadItem { il } => {
+                pa.read_item(*il);
+            }
+            RecordedInput::ReleaseItem { il } => pa.release_item(*il),
+            RecordedInput::BuyItem { il, cid } => pa.buy_item(*il, *cid),
+            RecordedInput::SellItem { il, cid } => pa.sell_item(*il, *cid),
+            RecordedInput::ChangeEquipment { cid, slot, il } => {
+                pa.change_equipment(*cid, *slot, *il)
+            }
+            RecordedInput::Talk { dir } => pa.try_talk(*dir),
+            RecordedInput::AdvanceTalk { choice } => {
+                pa.advance_talk(*choice);
+            }
+            RecordedInput::AdvanceScript => {
+                pa.advance_script();
+            }
+            RecordedInput::UndertakeQuest { i } => {
+                pa.undertake_quest(*i);
+            }
+            RecordedInput::StartCreation {
+                kind,
+                recipe,
+                ill,
+                prior_high_quality,
+                material_to_use,
+            } => pa.start_creation(*kind, recipe, *ill, *prior_high_quality, *material_to_use),
+            RecordedInput::GotoNextFloor { dir, dialog } => pa.goto_next_floor(*dir, *dialog),
+            RecordedInput::Restart => pa.restart(),
+            RecordedInput::SetTarget { pos } => {
+                pa.set_target(*pos);
+            }
+            RecordedInput::DebugCommand(command) => pa.exec_debug_command(command),
+        }
+    }
+    game.advance_until_player_turn();
+}
+
+/// Replay the recording file, and check the hash of the result.
+/// Returns false if the file cannot be loaded or the hash does not match.
+pub fn replay_file(path: &Path) -> bool {
+    let recording: Recording = match File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| e.to_string())
+        .and_then(|file| serde_json::from_reader(file).map_err(|e| e.to_string()))
+    {
+        Ok(recording) => recording,
+        Err(e) => {
+            error!("Cannot load recording {:?}: {}", path.to_string_lossy(), e);
+            return false;
+        }
+    };
+
+    info!(
+        "Replay {} inputs from {:?}",
+        recording.inputs.len(),
+        path.to_string_lossy()
+    );
+    let game = replay(&recording);
+    let hash = gamedata_hash(&game.gd);
+
+    if hash == recording.hash {
+        info!("Replay finished. GameData hash matched: {:016x}", hash);
+        true
+    } else {
+        error!(
+            "Replay finished. GameData hash mismatched: recorded {:016x}, replayed {:016x}",
+            recording.hash, hash
+        );
+        false
+    }
+}
+
+/// Calculate the hash of GameData and the current map
+pub fn gamedata_hash(gd: &GameData) -> u64 {
+    let mut hasher = fnv::FnvHasher::default();
+    hasher.write(&serde_cbor::to_vec(gd).expect("GameData serialization failed"));
+    hasher.write(&serde_cbor::to_vec(gd.get_current_map()).expect("Map serialization failed"));
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util;
+    use common::gobj;
+
+    fn start_game() -> Game {
+        rng::reseed(1);
+        let (gd, _) = test_util::fixture(Vec2d(12, 12));
+        let mut game = test_util::game(gd);
+        game.gd.chara.get_mut(CharaId::Player).name = Some("player".into());
+        let key = crate::game::item::gen::gen_item_from_idx(
+            gobj::id_to_idx(&rules::RULES.item.key_item_id),
+            1,
+        );
+        let il = ItemListLocation::Chara {
+            cid: CharaId::Player,
+        };
+        game.gd.get_item_list_mut(il).append(key, 2);
+        game.update_before_player_turn();
+        game
+    }
+
+    fn act(game: &mut Game, f: impl FnOnce(&mut DoPlayerAction)) {
+        game.advance_until_player_turn();
+        f(&mut DoPlayerAction::new(game));
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_game() {
+        let _lock = test_util::lock();
+        let mut game = start_game();
+        let initial_hash = gamedata_hash(&game.gd);
+        game.start_recording(Path::new("test.json"));
+
+        let il = (
+            ItemListLocation::Chara {
+                cid: CharaId::Player,
+            },
+            0,
+        );
+        act(&mut game, |pa| {
+            pa.exec_game_command(&Command::Move { dir: Direction::E });
+        });
+        act(&mut game, |pa| {
+            pa.drop_item(il, 1);
+        });
+        act(&mut game, |pa| {
+            pa.exec_game_command(&Command::Search);
+        });
+        act(&mut game, |pa| {
+            let il = (
+                ItemListLocation::OnMap {
+                    mid: pa.gd().get_current_mapid(),
+                    pos: pa.gd().player_pos(),
+                },
+                0,
+            );
+            pa.pick_up_item(il, ItemMoveNum::All);
+        });
+        act(&mut game, |pa| {
+            pa.exec_game_command(&Command::Move { dir: Direction::S });
+        });
+        game.advance_until_player_turn();
+
+        let recording = game.recorder.take().unwrap().recording;
+        assert_eq!(recording.inputs.len(), 5);
+        assert_eq!(recording.inputs[1], RecordedInput::DropItem { il, n: 1 });
+        let hash = gamedata_hash(&game.gd);
+        assert_ne!(hash, initial_hash);
+
+        let mut replayed = start_game();
+        assert_eq!(gamedata_hash(&replayed.gd), initial_hash);
+        replay_inputs(&mut replayed, &recording.inputs);
+        assert_eq!(gamedata_hash(&replayed.gd), hash);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::config::{CONFIG, USER_DIR};
+use crate::game::Game;
+use crate::text::ToText;
+use common::basic::{AUTOSAVE_DIR_NAME, SAVE_DIR_NAME, SAVE_EXTENSION};
+use common::gamedata::{GameData, MapId, MetaData};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+impl Game {
+    pub fn save_file(&mut self) {
+        let path = self.gd.save_dir(get_save_dir());
+        self.save_to(&path);
+    }
+
+    /// Save to the newest autosave slot. Older autosaves are rotated.
+    pub fn autosave_file(&mut self) {
+        let n_slot = CONFIG.autosave_slots;
+        if !self.autosave || n_slot == 0 {
+            return;
+        }
+
+        for i in (0..(n_slot - 1)).rev() {
+            let src = get_autosave_dir(&self.gd, i);
+            if !src.exists() {
+                continue;
+            }
+            let dest = get_autosave_dir(&self.gd, i + 1);
+            let result = if dest.exists() {
+                fs::remove_dir_all(&dest)
+            } else {
+                Ok(())
+            }
+            .and_then(|_| fs::rename(&src, &dest));
+            if let Err(e) = result {
+                warn!(
+                    "Failed to rotate autosave {:?}: {}",
+                    src.to_string_lossy(),
+                    e
+                );
+                return;
+            }
+        }
+
+        let path = get_autosave_dir(&self.gd, 0);
+        self.save_to(&path);
+    }
+
+    fn save_to(&mut self, path: &Path) {
+        self.gd.rng = rng::get_state();
+        self.update_save_summary();
+
+        if let Some(parent) = path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                warn!("Failed to create save directory : {}", e);
+                return;
+            }
+        }
+
+        let map_src_dir = self.save_dir.as_ref().unwrap().join("maps");
+        match self.gd.save(path, &map_src_dir) {
+            Ok(_) => info!("Saved to {:?}", path.to_string_lossy()),
+            Err(e) => warn!("Faild to saving to {:?}: {}", path.to_string_lossy(), e),
+        }
+    }
+
+    fn update_save_summary(&mut self) {
+        let elapsed = self.play_timer.elapsed().as_secs();
+        self.play_timer += std::time::Duration::from_secs(elapsed);
+
+        let gd = &mut self.gd;
+        let player = gd.chara.get(common::gamedata::CharaId::Player);
+        let chara_name = player.name.clone().unwrap_or_default();
+        let level = player.level;
+        let location = match gd.get_current_mapid() {
+            MapId::SiteMap { sid, floor } => {
+                format!("{} ({})", gd.region.get_site(sid).to_text(), floor + 1)
+            }
+            MapId::RegionMap { rid } => gd.region.get(rid).name.clone(),
+        };
+
+        let summary = gd.meta.summary_mut();
+        summary.chara_name = chara_name;
+        summary.level = level;
+        summary.location = location;
+        summary.play_time += elapsed;
+    }
+
+    pub fn clean_save_data(&self) {
+        let save_dir = get_save_dir();
+        let path = self.gd.save_dir(save_dir);
+        if !path.exists() {
+            return;
+        }
+        match self.gd.clean_map_dir(&path) {
+            Ok(_) => info!("Clean map dir {:?}", path.to_string_lossy()),
+            Err(e) => warn!("Faild to clean map dir {:?}: {}", path.to_string_lossy(), e),
+        }
+    }
+}
+
+/// List save directories. Autosaves follow manual saves.
+pub fn save_file_list() -> Result<Vec<PathBuf>, std::io::Error> {
+    let save_dir = get_save_dir();
+
+    if !save_dir.exists() {
+        fs::create_dir_all(&save_dir)?;
+    }
+
+    let mut list = save_dirs_in(&save_dir)?;
+    let autosave_dir = save_dir.join(AUTOSAVE_DIR_NAME);
+    if autosave_dir.exists() {
+        list.extend(save_dirs_in(&autosave_dir)?);
+    }
+
+    Ok(list)
+}
+
+fn save_dirs_in(dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut list = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let file = entry?;
+
+        if !file.file_type()?.is_dir() {
+            continue;
+        }
+
+        let path = file.path();
+
+        let extension = path.extension();
+
+        if extension.is_some() && extension.unwrap() == SAVE_EXTENSION {
+            list.push(path);
+        } else if extension.is_some() && extension.unwrap() == "old" {
+            // Saving was interrupted
+            let path = path.with_extension("");
+            if !path.exists() {
+                common::saveload::recover_save_dir(&path)?;
+                // Backups are not listed
+                if path.extension() == Some(SAVE_EXTENSION.as_ref()) {
+                    list.push(path);
+                }
+            }
+        }
+    }
+
+    list.sort();
+    Ok(list)
+}
+
+/// Returns true if the path is an autosave directory
+pub fn is_autosave(path: &Path) -> bool {
+    path.parent()
+        .and_then(|parent| parent.file_name())
+        .map(|name| name == AUTOSAVE_DIR_NAME)
+        .unwrap_or(false)
+}
+
+/// Load the save directory. An autosave is restored to the manual save directory before loading,
+/// because maps not loaded yet are read from the manual save directory during play.
+/// The overwritten manual save is kept as a backup.
+pub fn load_save_file(path: &Path) -> Result<GameData, Box<dyn std::error::Error>> {
+    if !is_autosave(path) {
+        return GameData::load(path);
+    }
+
+    common::saveload::recover_save_dir(path)?;
+    let meta = MetaData::load(path)?;
+    let dest = get_save_dir().join(format!("{}.{}", meta.save_name(), SAVE_EXTENSION));
+    if let Some(backup) = common::saveload::restore_save_dir(path, &dest)? {
+        info!(
+            "Backed up {:?} to {:?}",
+            dest.to_string_lossy(),
+            backup.to_string_lossy()
+        );
+    }
+    info!(
+        "Restored autosave {:?} to {:?}",
+        path.to_string_lossy(),
+        dest.to_string_lossy()
+    );
+    GameData::load(&dest)
+}
+
+/// Upgrade save data in the given directory to the current format version.
+/// The path is a save directory, or a directory that contains save directories.
+/// Returns false if any save data cannot be upgraded.
+pub fn upgrade_save_dirs(path: &Path) -> bool {
+    let save_dirs = if path.join("metadata").exists() {
+        vec![path.to_owned()]
+    } else {
+        match fs::read_dir(path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_dir() && path.extension() == Some(SAVE_EXTENSION.as_ref()))
+                .collect(),
+            Err(e) => {
+                error!("Cannot read directory {:?}: {}", path.to_string_lossy(), e);
+                return false;
+            }
+        }
+    };
+
+    let mut result = true;
+    for save_dir in &save_dirs {
+        match common::migration::upgrade_save_dir(save_dir) {
+            Ok(true) => info!("Upgraded {:?}", save_dir.to_string_lossy()),
+            Ok(false) => info!("{:?} is already up to date", save_dir.to_string_lossy()),
+            Err(e) => {
+                error!("Failed to upgrade {:?}: {}", save_dir.to_string_lossy(), e);
+                result = false;
+            }
+        }
+    }
+    result
+}
+
+/// Generate random id for FileBox
+pub fn gen_box_id(gd: &GameData) -> u64 {
+    use rng::*;
+
+    loop {
+        let s = thread_rng().gen::<u64>();
+
+        // Check generated name is not used
+        let mut path = get_each_save_dir(gd);
+        path.push("maps");
+        path.push(format!("{:016x}", s));
+        if !path.exists() {
+            return s;
+        }
+    }
+}
+
+fn get_save_dir() -> PathBuf {
+    USER_DIR.clone().join(SAVE_DIR_NAME)
+}
+
+/// Get autosave directory path "save_dir/autosave/save_name.n"
+fn get_autosave_dir(gd: &GameData, n: u32) -> PathBuf {
+    get_save_dir().join(AUTOSAVE_DIR_NAME).join(format!(
+        "{}.{}.{}",
+        gd.meta.save_name(),
+        n,
+        SAVE_EXTENSION
+    ))
+}
+
+/// Get each save directory path "save_dir/save_name"
+pub fn get_each_save_dir(gd: &GameData) -> PathBuf {
+    get_save_dir().join(format!("{}.{}", gd.meta.save_name(), SAVE_EXTENSION))
+}
+
+/// Get map save directory
+pub fn get_map_dir(gd: &GameData) -> PathBuf {
+    get_each_save_dir(gd).join("maps")
+}
@@ -0,0 +1,651 @@
+//! Script engine implementation
+
+use common::gamedata::*;
+use common::gobj;
+use common::objholder::CharaTemplateIdx;
+use common::script::*;
+use geom::*;
+
+use crate::game::eval_expr::{chara_ref, EvalExpr, Locals};
+use crate::game::extrait::*;
+use crate::text::ItemName;
+
+/// Maximum number of instructions executed without waiting for player's input.
+/// Prevents infinite loops in scripts from freezing the game.
+const MAX_STEPS: usize = 100_000;
+
+pub struct ScriptEngine {
+    script: &'static Script,
+    pos: ScriptPos,
+    cid: Option<CharaId>,
+    talking: bool,
+    /// Local variables in the current scope
+    locals: Locals,
+    /// Return positions and local variables of callers
+    call_stack: Vec<(ScriptPos, Locals)>,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum ExecResult {
+    Talk(Option<CharaId>, TalkText, bool),
+    ShopBuy(CharaId),
+    ShopSell(CharaId),
+    Quest,
+    Teleport(MapId, Vec2d),
+    /// Some quests are completed. Hooked scripts are queued, and the script continues.
+    QuestCompleted,
+    Quit,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TalkText {
+    pub text_id: &'static str,
+    pub choices: Option<&'static [(String, String)]>,
+}
+
+/// Unwrap Value as bool
+macro_rules! as_bool {
+    ($v:expr) => {{
+        match $v {
+            Value::Bool(v) => v,
+            Value::RefUnknownVar => false,
+            _ => {
+                return ExecResult::Quit;
+            }
+        }
+    }};
+}
+
+/// Unwrap Value as int
+macro_rules! as_int {
+    ($v:expr) => {{
+        match $v {
+            Value::Int(v) => v,
+            _ => {
+                return ExecResult::Quit;
+            }
+        }
+    }};
+}
+
+/// Jump to the given section and continue loop.
+macro_rules! jump {
+    ($s:expr, $section:expr) => {{
+        match $section.as_ref() {
+            QUIT_SECTION => return ExecResult::Quit,
+            CONTINUE_SECTION => {
+                $s.pos.advance();
+                continue;
+            }
+            _ => (),
+        }
+        $s.pos.set_section($section);
+        continue;
+    }};
+}
+
+/// Return from the current subroutine and continue loop.
+/// Quit if there is no caller.
+macro_rules! ret {
+    ($s:expr) => {{
+        if let Some((pos, locals)) = $s.call_stack.pop() {
+            $s.pos = pos;
+            $s.locals = locals;
+            continue;
+        } else {
+            break ExecResult::Quit;
+        }
+    }};
+}
+
+/// Unwrap or return with warning message.
+macro_rules! ur {
+    ($a:expr, $e:expr) => {{
+        if let Some(a) = $a {
+            a
+        } else {
+            warn!(concat!("script error: ", $e));
+            return ExecResult::Quit;
+        }
+    }};
+}
+
+impl ScriptEngine {
+    pub fn new(id: &str, cid: Option<CharaId>) -> ScriptEngine {
+        let script_obj: &ScriptObject = gobj::get_by_id(id);
+        ScriptEngine {
+            script: &script_obj.script,
+            pos: ScriptPos {
+                section: "start".to_owned(),
+                i: 0,
+            },
+            cid,
+            talking: false,
+            locals: Locals::default(),
+            call_stack: Vec::new(),
+        }
+    }
+
+    pub fn exec(&mut self, gd: &mut GameData) -> ExecResult {
+        let mut steps = 0;
+        let result = loop {
+            steps += 1;
+            if steps > MAX_STEPS {
+                warn!("script error: too many steps at {:?}", self.pos);
+                break ExecResult::Quit;
+            }
+
+            let instruction = if let Some(instruction) = self.script.get(&self.pos) {
+                instruction
+            } else {
+                // The end of a subroutine section
+                ret!(self);
+            };
+
+            match instruction {
+                Instruction::Jump(section) => {
+                    jump!(self, section);
+                }
+                Instruction::JumpIf(section, expr) => {
+                    if as_bool!(expr.eval(gd, self.cid, &self.locals)) {
+                        jump!(self, section);
+                    }
+                }
+                Instruction::Talk(text_id, choices) => {
+                    let need_open_talk_dialog = if self.talking {
+                        false
+                    } else {
+                        self.talking = true;
+                        true
+                    };
+
+                    let choices = if choices.is_empty() {
+                        None
+                    } else {
+                        Some(choices.as_ref())
+                    };
+                    return ExecResult::Talk(
+                        self.cid,
+                        TalkText { text_id, choices },
+                        need_open_talk_dialog,
+                    );
+                }
+                Instruction::GSet(name, v) => {
+                    let v = v.eval(gd, self.cid, &self.locals);
+                    gd.vars.set_global_var(name, v);
+                }
+                Instruction::Set(name, v) => {
+                    let v = v.eval(gd, self.cid, &self.locals);
+                    self.locals.insert(name.clone(), v);
+                }
+                Instruction::Call(section, args) => {
+                    if !self.script.has_section(section) {
+                        warn!("script error: unknown section {}", section);
+                        break ExecResult::Quit;
+                    }
+                    let params = self.script.params(section);
+                    if params.len() != args.len() {
+                        warn!(
+                            "script error: {} needs {} arguments, but given {}",
+                            section,
+                            params.len(),
+                            args.len()
+                        );
+                        break ExecResult::Quit;
+                    }
+                    let locals: Locals = params
+                        .iter()
+                        .cloned()
+                        .zip(args.iter().map(|arg| arg.eval(gd, self.cid, &self.locals)))
+                        .collect();
+                    let mut return_pos = self.pos.clone();
+                    return_pos.advance();
+                    self.call_stack
+                        .push((return_pos, std::mem::replace(&mut self.locals, locals)));
+                    self.pos.set_section(section);
+                    continue;
+                }
+                Instruction::Return(v) => {
+                    if let Some(v) = v {
+                        let v = v.eval(gd, self.cid, &self.locals);
+                        gd.vars.set_last_result(v);
+                    }
+                    ret!(self);
+                }
+                Instruction::While(cond, end) => {
+                    if !as_bool!(cond.eval(gd, self.cid, &self.locals)) {
+                        self.pos.i = *end;
+                        continue;
+                    }
+                }
+                Instruction::EndWhile(start) => {
+                    self.pos.i = *start;
+                    continue;
+                }
+                Instruction::ReceiveItem(id, n) => {
+                    let item = crate::game::item::gen::gen_item_from_id(&id, 1);
+                    let n = as_int!(n.eval(gd, self.cid, &self.locals));
+                    let il = gd.get_item_list_mut(ItemListLocation::PLAYER);
+                    il.append(item.clone(), n as u32);
+                    let player = gd.chara.get(CharaId::Player);
+                    game_log_i!("player-receive-item"; chara=player, item=ItemName::new(gd, &item), n=n);
+                    gd.chara.get_mut(CharaId::Player).update();
+                }
+                Instruction::ReceiveMoney(v) => {
+                    let amount = as_int!(v.eval(gd, self.cid, &self.locals));
+                    gd.player.add_money(amount.into());
+                    let player = gd.chara.get(CharaId::Player);
+                    game_log_i!("player-receive-money"; chara=player, amount=amount);
+                }
+                Instruction::RemoveItem(item_id) => {
+                    let il = ur!(gd.player_item_location(item_id), "cannot find item");
+                    gd.remove_item(il, 1);
+                    gd.chara.get_mut(CharaId::Player).update();
+                }
+                Instruction::ChangeFactionRelation(faction, v) => {
+                    let v = as_int!(v.eval(gd, self.cid, &self.locals));
+                    gd.faction.change(*faction, v);
+                }
+                Instruction::LearnSkill(kind) => {
+                    let player = gd.chara.get_mut(CharaId::Player);
+                    let result = player.skills.learn_new_skill(*kind);
+                    if result {
+                        game_log_i!("skill-learned"; chara=player, skill=kind);
+                    }
+                    gd.vars.set_last_result(Value::Bool(result));
+                }
+                Instruction::GainSkillExp(kind, v) => {
+                    let exp = as_int!(v.eval(gd, self.cid, &self.locals));
+                    let player = gd.chara.get_mut(CharaId::Player);
+                    let lv = player.skills.get(*kind);
+                    player.add_skill_exp(*kind, std::cmp::max(exp, 0) as u32, lv);
+                }
+                Instruction::AddAttr(kind, v) => {
+                    let v = as_int!(v.eval(gd, self.cid, &self.locals));
+                    let player = gd.chara.get_mut(CharaId::Player);
+                    // Attributes must not be lower than 1
+                    let v = std::cmp::max(v, 1 - player.attr.get(*kind) as i32);
+                    let v = v.clamp(i16::MIN.into(), i16::MAX.into()) as i16;
+                    player.attr_revision.add(*kind, v);
+                    player.update();
+                }
+                Instruction::AddHp(chara, v) => {
+                    let v = as_int!(v.eval(gd, self.cid, &self.locals));
+                    let cid = ur!(chara_ref(*chara, self.cid), "cid is needed");
+                    let chara = gd.chara.get_mut(cid);
+                    chara.hp = (chara.hp + v).clamp(1, std::cmp::max(chara.attr.max_hp, 1));
+                }
+                Instruction::SpawnChara(id, faction) => {
+                    let idx: CharaTemplateIdx =
+                        ur!(gobj::id_to_idx_checked(id), "unknown chara template");
+                    let lv = gobj::get_obj(idx).gen_level;
+                    let chara = crate::game::chara::gen::create_chara(idx, lv, *faction, None);
+                    let pos = ur!(
+                        super::map::empty_tile_near_player(gd, &chara),
+                        "no empty tile to spawn a character"
+                    );
+                    let mid = gd.get_current_mapid();
+                    let cid = gd.add_chara_to_map(chara, mid);
+                    gd.get_current_map_mut().locate_chara(cid, pos);
+                }
+                Instruction::Teleport(path) => {
+                    let (mid, pos) =
+                        ur!(gd.region.path_to_map_id_and_pos(path), "unknown map path");
+                    break ExecResult::Teleport(mid, pos);
+                }
+                Instruction::StartCombat(faction) => {
+                    let cid = ur!(self.cid, "cid is needed");
+                    gd.chara.get_mut(cid).faction = *faction;
+                    super::stealth::alert(gd, cid, CharaId::Player);
+                }
+                Instruction::Special(SpecialInstruction::ShopBuy) => {
+                    let cid = ur!(self.cid, "cid is needed");
+                    restock_shops(gd, cid);
+                    break ExecResult::ShopBuy(cid);
+                }
+                Instruction::Special(SpecialInstruction::ShopSell) => {
+                    let cid = ur!(self.cid, "cid is needed");
+                    restock_shops(gd, cid);
+                    break ExecResult::ShopSell(cid);
+                }
+                Instruction::Special(SpecialInstruction::GetDungeonLocation) => {
+                    let mid = gd.get_current_mapid();
+                    super::region::gen_dungeon_max(gd, mid.rid());
+                }
+                Instruction::Special(SpecialInstruction::QuestWindow) => {
+                    super::quest::update_town_quest(gd);
+                    break ExecResult::Quest;
+                }
+                Instruction::Special(SpecialInstruction::ReceiveQuestRewards) => {
+                    let completed =
+                        super::quest::process_event(gd, super::quest::QuestEvent::Report);
+                    let result = super::quest::receive_rewards(gd);
+                    gd.vars.set_last_result(Value::Bool(result));
+                    if completed {
+                        break ExecResult::QuestCompleted;
+                    }
+                }
+                Instruction::Special(SpecialInstruction::Recruit) => {
+                    let cid = ur!(self.cid, "cid is needed");
+                    let new_cid = super::party::recruit(gd, cid);
+                    if new_cid.is_some() {
+                        self.cid = new_cid;
+                    }
+                    gd.vars.set_last_result(Value::Bool(new_cid.is_some()));
+                }
+                Instruction::Print(v) => {
+                    let v = v.eval(gd, self.cid, &self.locals);
+                    eprintln!("script print: {:?}", v);
+                }
+                Instruction::SetQuestStage(id, stage) => {
+                    super::quest::set_story_quest_stage(gd, id, stage);
+                }
+                Instruction::CompleteQuest(id) => {
+                    if super::quest::finish_story_quest(gd, id, QuestState::Completed) {
+                        break ExecResult::QuestCompleted;
+                    }
+                }
+                Instruction::FailQuest(id) => {
+                    super::quest::finish_story_quest(gd, id, QuestState::Failed);
+                }
+                Instruction::FailQuests => {
+                    super::quest::fail_active_quests(gd);
+                }
+            }
+            self.pos.advance();
+        };
+
+        self.pos.advance();
+        result
+    }
+
+    pub fn continue_talk(&mut self, gd: &mut GameData, choice: Option<u32>) -> ExecResult {
+        match self.script.get(&self.pos).expect("instruction not found") {
+            Instruction::Talk(_, choices) => {
+                if let Some(c) = choice {
+                    let next_section = &choices[c as usize].1;
+                    if next_section == QUIT_SECTION {
+                        return ExecResult::Quit;
+                    }
+                    if next_section == CONTINUE_SECTION {
+                        self.pos.advance();
+                    } else {
+                        self.pos.set_section(next_section);
+                    }
+                } else {
+                    assert!(choices.is_empty());
+                    self.pos.advance();
+                }
+                self.exec(gd)
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Restock shops of the town where the shopkeeper lives
+fn restock_shops(gd: &mut GameData, cid: CharaId) {
+    if let CharaId::OnSite { sid, .. } = cid {
+        super::town::restock_shops_if_needed(gd, sid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::hashmap::HashMap;
+
+    fn engine(script: Script) -> ScriptEngine {
+        ScriptEngine {
+            script: Box::leak(Box::new(script)),
+            pos: ScriptPos {
+                section: "start".to_owned(),
+                i: 0,
+            },
+            cid: None,
+            talking: false,
+            locals: Locals::default(),
+            call_stack: Vec::new(),
+        }
+    }
+
+    fn int(i: i32) -> Expr {
+        Expr::Value(Value::Int(i))
+    }
+
+    fn term(a: Expr, o: Operator, b: Expr) -> Expr {
+        Expr::Term(vec![(Operator::None, a), (o, b)])
+    }
+
+    #[test]
+    fn call_subroutine_in_loop() {
+        let lvar = |name: &str| Expr::LVar(name.to_owned());
+        let mut sections = HashMap::default();
+        // sum = 0; i = 0; while i < 4 { sum = sum + double(i); i = i + 1 }
+        sections.insert(
+            "start".to_owned(),
+            vec![
+                Instruction::Set("sum".to_owned(), int(0)),
+                Instruction::Set("i".to_owned(), int(0)),
+                Instruction::While(term(lvar("i"), Operator::Less, int(4)), 7),
+                Instruction::Call("double".to_owned(), vec![lvar("i")]),
+                Instruction::Set(
+                    "sum".to_owned(),
+                    term(lvar("sum"), Operator::Add, Expr::GVar("?".to_owned())),
+                ),
+                Instruction::Set("i".to_owned(), term(lvar("i"), Operator::Add, int(1))),
+                Instruction::EndWhile(2),
+                Instruction::GSet("result".to_owned(), lvar("sum")),
+                Instruction::GSet("i".to_owned(), Expr::LVar("n".to_owned())),
+            ],
+        );
+        sections.insert(
+            "double".to_owned(),
+            vec![Instruction::Return(Some(term(
+                lvar("n"),
+                Operator::Mul,
+                int(2),
+            )))],
+        );
+        let mut script = Script::from_map(sections);
+        script.set_params("double", vec!["n".to_owned()]);
+
+        let mut gd = GameData::empty();
+        assert_eq!(engine(script).exec(&mut gd), ExecResult::Quit);
+        assert_eq!(gd.vars.global_var("result"), Some(&Value::Int(12)));
+        // Local variables of the subroutine are not visible from the caller
+        assert_eq!(gd.vars.global_var("i"), Some(&Value::RefUnknownVar));
+    }
+
+    #[test]
+    fn infinite_loop() {
+        let mut sections = HashMap::default();
+        sections.insert(
+            "start".to_owned(),
+            vec![
+                Instruction::While(Expr::Value(Value::Bool(true)), 2),
+                Instruction::EndWhile(0),
+            ],
+        );
+        let mut gd = GameData::empty();
+        assert_eq!(
+            engine(Script::from_map(sections)).exec(&mut gd),
+            ExecResult::Quit
+        );
+    }
+
+    #[test]
+    fn change_faction_relation() {
+        let citizen = FactionId::new("citizen").unwrap();
+        let relation = || Expr::FactionRelation(citizen);
+        let mut sections = HashMap::default();
+        sections.insert(
+            "start".to_owned(),
+            vec![
+                Instruction::ChangeFactionRelation(citizen, int(90)),
+                Instruction::ChangeFactionRelation(citizen, int(50)),
+                Instruction::GSet("a".to_owned(), relation()),
+                Instruction::ChangeFactionRelation(citizen, int(-300)),
+                Instruction::GSet("b".to_owned(), relation()),
+            ],
+        );
+        let mut gd = GameData::empty();
+        engine(Script::from_map(sections)).exec(&mut gd);
+        // Relations are clamped to -100 - 100
+        assert_eq!(gd.vars.global_var("a"), Some(&Value::Int(100)));
+        assert_eq!(gd.vars.global_var("b"), Some(&Value::Int(-100)));
+    }
+
+    #[test]
+    fn story_quest_exprs() {
+        let stage = |stage: &str| Expr::QuestStage("main".to_owned(), stage.to_owned());
+        let mut sections = HashMap::default();
+        sections.insert(
+            "start".to_owned(),
+            vec![
+                Instruction::GSet("a".to_owned(), stage("start")),
+                Instruction::GSet("b".to_owned(), stage("cave")),
+                Instruction::GSet(
+                    "c".to_owned(),
+                    Expr::QuestState("main".to_owned(), QuestState::Active),
+                ),
+                Instruction::GSet(
+                    "d".to_owned(),
+                    Expr::QuestState("side".to_owned(), QuestState::Active),
+                ),
+            ],
+        );
+        let mut gd = GameData::empty();
+        let now = gd.time.current_time();
+        gd.quest.set_story_quest_stage("main", "start", now);
+        engine(Script::from_map(sections)).exec(&mut gd);
+        assert_eq!(gd.vars.global_var("a"), Some(&Value::Bool(true)));
+        assert_eq!(gd.vars.global_var("b"), Some(&Value::Bool(false)));
+        assert_eq!(gd.vars.global_var("c"), Some(&Value::Bool(true)));
+        // Not started quests are in no state
+        assert_eq!(gd.vars.global_var("d"), Some(&Value::Bool(false)));
+    }
+
+    #[test]
+    fn fetch_quest_completed_by_report() {
+        let _lock = crate::game::test_util::lock();
+        let (mut gd, _) = crate::game::test_util::fixture(Vec2d(12, 12));
+        let idx: common::objholder::ItemIdx = gobj::id_to_idx(&rules::RULES.item.key_item_id);
+        let item = crate::game::item::gen::gen_item_from_idx(idx, 1);
+        gd.chara.get_mut(CharaId::Player).item_list.append(item, 2);
+        gd.quest.start_new_quest(Quest::Fetch {
+            reward: Reward {
+                money: 100,
+                item: Vec::new(),
+            },
+            deadline: None,
+            idx,
+            goal: 2,
+        });
+        let mut sections = HashMap::default();
+        sections.insert(
+            "start".to_owned(),
+            vec![
+                Instruction::Special(SpecialInstruction::ReceiveQuestRewards),
+                Instruction::GSet("a".to_owned(), int(1)),
+            ],
+        );
+        let mut engine = engine(Script::from_map(sections));
+        let money = gd.player.money();
+
+        // The script stops to trigger hooks, and continues after that
+        assert_eq!(engine.exec(&mut gd), ExecResult::QuestCompleted);
+        assert_eq!(gd.player.money(), money + 100);
+        assert_eq!(gd.chara.get(CharaId::Player).item_list.count(idx), 0);
+        assert_eq!(engine.exec(&mut gd), ExecResult::Quit);
+        assert_eq!(gd.vars.global_var("a"), Some(&Value::Int(1)));
+    }
+
+    #[test]
+    fn complete_story_quest() {
+        let _lock = crate::game::test_util::lock();
+        let mut sections = HashMap::default();
+        sections.insert(
+            "start".to_owned(),
+            vec![
+                Instruction::CompleteQuest("main".to_owned()),
+                Instruction::CompleteQuest("main".to_owned()),
+                Instruction::GSet("a".to_owned(), int(1)),
+            ],
+        );
+        let mut gd = GameData::empty();
+        let now = gd.time.current_time();
+        gd.quest.set_story_quest_stage("main", "start", now);
+        let mut engine = engine(Script::from_map(sections));
+
+        // Hooks are triggered only by the first completion
+        assert_eq!(engine.exec(&mut gd), ExecResult::QuestCompleted);
+        assert_eq!(engine.exec(&mut gd), ExecResult::Quit);
+        assert_eq!(gd.vars.global_var("a"), Some(&Value::Int(1)));
+    }
+
+    #[test]
+    fn add_hp() {
+        let _lock = crate::game::test_util::lock();
+        let (mut gd, monster) = crate::game::test_util::fixture(Vec2d(12, 12));
+        let hp = |gd: &GameData, cid| gd.chara.get(cid).hp;
+        let mut sections = HashMap::default();
+        sections.insert(
+            "start".to_owned(),
+            vec![
+                Instruction::AddHp(CharaRef::Talker, int(-30)),
+                Instruction::AddHp(CharaRef::Player, int(-1000)),
+                Instruction::GSet("a".to_owned(), Expr::Hp(CharaRef::Player)),
+                Instruction::AddHp(CharaRef::Player, int(1000)),
+            ],
+        );
+        let script = Script::from_map(sections);
+
+        let mut e = engine(script.clone());
+        e.cid = Some(monster);
+        e.exec(&mut gd);
+        assert_eq!(hp(&gd, monster), 70);
+        // HP is kept between 1 and max HP
+        assert_eq!(gd.vars.global_var("a"), Some(&Value::Int(1)));
+        assert_eq!(hp(&gd, CharaId::Player), 100);
+
+        // No talking character
+        assert_eq!(engine(script).exec(&mut gd), ExecResult::Quit);
+        assert_eq!(hp(&gd, CharaId::Player), 100);
+    }
+
+    #[test]
+    fn fail_quests() {
+        let _lock = crate::game::test_util::lock();
+        let mut gd = GameData::empty();
+        let quest = Quest::ExploreFloor {
+            reward: Reward::default(),
+            deadline: None,
+            sid: SiteId::default(),
+            floor: 1,
+        };
+        gd.quest.start_new_quest(quest.clone());
+        gd.quest.start_new_quest(quest);
+        gd.quest.iter_mut().next().unwrap().0 = QuestState::Completed;
+        let now = gd.time.current_time();
+        gd.quest.set_story_quest_stage("main", "start", now);
+        let mut sections = HashMap::default();
+        sections.insert(
+            "start".to_owned(),
+            vec![
+                Instruction::FailQuests,
+                Instruction::GSet("a".to_owned(), Expr::QuestCount(QuestState::Failed)),
+                Instruction::GSet("b".to_owned(), Expr::QuestCount(QuestState::Completed)),
+                Instruction::GSet(
+                    "c".to_owned(),
+                    Expr::QuestState("main".to_owned(), QuestState::Active),
+                ),
+            ],
+        );
+        engine(Script::from_map(sections)).exec(&mut gd);
+        assert_eq!(gd.vars.global_var("a"), Some(&Value::Int(1)));
+        assert_eq!(gd.vars.global_var("b"), Some(&Value::Int(1)));
+        // Story quests are not changed
+        assert_eq!(gd.vars.global_var("c"), Some(&Value::Bool(true)));
+    }
+}
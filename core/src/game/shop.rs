@@ -0,0 +1,208 @@
+use crate::game::extrait::*;
+use crate::game::item::gen::gen_item_by_level;
+use crate::game::item::identify;
+use crate::text::ItemName;
+use common::gamedata::*;
+use common::sitegen::ShopGenData;
+use rules::RULES;
+
+/// Buy an item from the shop of given shopkeeper
+pub fn buy_item(gd: &mut GameData, il: ItemLocation, cid: CharaId) {
+    let price = buy_price(gd, gd.get_item(il).0, cid);
+    if gd.player.has_money(price) {
+        gd.player.sub_money(price);
+        gd.get_shop_mut(cid).money += price;
+        let idx = gd.get_item(il).0.idx;
+        identify::identify_kind(&mut gd.item_knowledge, idx);
+        gd.move_item(
+            il,
+            ItemListLocation::Chara {
+                cid: CharaId::Player,
+            },
+            1,
+        );
+        traded(gd, cid, price);
+    } else {
+        game_log_i!("shop-lack-of-money"; chara=gd.chara.get(CharaId::Player));
+    }
+}
+
+/// Sell an item to given shopkeeper
+pub fn sell_item(gd: &mut GameData, il: ItemLocation, cid: CharaId) {
+    let item = gd.get_item(il).0;
+    let idx = item.idx;
+    let kind = item.kind.rough();
+    let price = sell_price(gd, item, cid);
+    let shop = gd.get_shop_mut(cid);
+    if shop.money < price {
+        game_log_i!("shop-lack-of-money-shopkeeper"; chara=gd.chara.get(cid));
+        return;
+    }
+    shop.money -= price;
+    gd.player.add_money(price);
+    // The shopkeeper tells what the item is
+    if identify::identify_kind(&mut gd.item_knowledge, idx) {
+        game_log_i!("item-kind-identified"; item=ItemName::new(gd, gd.get_item(il).0));
+    }
+    gd.remove_item(il, 1);
+    if let Some(town) = shop_town_mut(gd, cid) {
+        *town.supply.entry(kind).or_insert(0) += 1;
+    }
+    traded(gd, cid, price);
+}
+
+/// Price to buy the item from given shopkeeper
+pub fn buy_price(gd: &GameData, item: &Item, cid: CharaId) -> i64 {
+    let factor = buy_price_factor(trade_bonus(gd, cid)) * supply_factor(gd, item, cid);
+    std::cmp::max((item.price() as f32 * factor) as i64, 1)
+}
+
+/// Price to sell the item to given shopkeeper
+pub fn sell_price(gd: &GameData, item: &Item, cid: CharaId) -> i64 {
+    let factor = sell_price_factor(trade_bonus(gd, cid)) * supply_factor(gd, item, cid);
+    (item.price() as f32 * factor) as i64
+}
+
+/// Price bonus by the player's charisma, Trading skill,
+/// and the relation to the shopkeeper's faction
+fn trade_bonus(gd: &GameData, cid: CharaId) -> f32 {
+    let player = gd.chara.get(CharaId::Player);
+    let relation = i32::from(gd.faction.get(gd.chara.get(cid).faction));
+    let trading = player.skills.get(SkillKind::Trading);
+
+    player.attr.cha as f32 * RULES.town.price_bonus_per_cha
+        + trading as f32 * RULES.town.price_bonus_per_trading
+        + relation as f32 * RULES.town.price_bonus_per_relation
+}
+
+fn buy_price_factor(bonus: f32) -> f32 {
+    let r = &RULES.town;
+    (r.buy_price_factor - bonus).max(r.min_buy_price_factor)
+}
+
+fn sell_price_factor(bonus: f32) -> f32 {
+    let r = &RULES.town;
+    (r.sell_price_factor + bonus).clamp(0.0, r.max_sell_price_factor)
+}
+
+fn supply_factor(gd: &GameData, item: &Item, cid: CharaId) -> f32 {
+    let supply = shop_town(gd, cid).map_or(0, |town| town.supply(item.kind.rough()));
+    supply_price_factor(supply)
+}
+
+fn supply_price_factor(supply: u32) -> f32 {
+    let r = &RULES.town;
+    (1.0 - supply as f32 * r.supply_price_drop).max(r.min_supply_price_factor)
+}
+
+/// Develop the town and train the player's Trading skill
+fn traded(gd: &mut GameData, cid: CharaId, price: i64) {
+    let shop_level = gd.get_shop(cid).level;
+    if let Some(town) = shop_town_mut(gd, cid) {
+        town.development = town.development.saturating_add(price as u32);
+    }
+    let player = gd.chara.get_mut(CharaId::Player);
+    player.add_skill_exp(SkillKind::Trading, RULES.exp.trading, shop_level);
+    player.update();
+}
+
+fn shop_town(gd: &GameData, cid: CharaId) -> Option<&Town> {
+    match cid {
+        CharaId::OnSite { sid, .. } => match &gd.region.get_site(sid).content {
+            SiteContent::Town { town } => Some(town),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn shop_town_mut(gd: &mut GameData, cid: CharaId) -> Option<&mut Town> {
+    match cid {
+        CharaId::OnSite { sid, .. } => match &mut gd.region.get_site_mut(sid).content {
+            SiteContent::Town { town } => Some(town),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Shop level calculated from the development of the town
+pub fn shop_level(development: u32) -> u32 {
+    let level = 1 + development / RULES.town.development_per_shop_level;
+    std::cmp::min(level, RULES.town.max_shop_level)
+}
+
+/// Restock a shop. Items are regenerated, and shopkeeper's money is refilled.
+pub fn restock_shop(shop: &mut Shop, shop_gen: &ShopGenData, level: u32) {
+    shop.level = level;
+    update_items_on_shop(shop, shop_gen);
+    let money = RULES.town.shop_money_base + RULES.town.shop_money_per_level * level as i64;
+    shop.money = std::cmp::max(shop.money, money);
+}
+
+/// Update items on a shop
+pub fn update_items_on_shop(shop: &mut Shop, shop_gen: &ShopGenData) {
+    shop.items.clear();
+
+    let n_gen_item = rng::gen_range(RULES.town.min_shop_items, RULES.town.max_shop_items);
+
+    for _ in 0..n_gen_item {
+        if let Some(mut item) = gen_shop_item(shop.level, &shop.kind, shop_gen) {
+            // Shopkeepers know their items
+            item.flags.remove(ItemFlags::UNIDENTIFIED);
+            shop.items.append(item, 1);
+        }
+    }
+}
+
+/// Generate new item at shops
+fn gen_shop_item(floor_level: u32, shop_kind: &ShopKind, shop_gen: &ShopGenData) -> Option<Item> {
+    let f = |item_obj: &ItemObject| match shop_kind {
+        ShopKind::Specified => {
+            if shop_gen.id.iter().any(|id| item_obj.id == *id) {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        ShopKind::Equipment => match item_obj.kind {
+            ItemKind::Weapon(_) | ItemKind::Armor(_) => 1.0,
+            _ => 0.0,
+        },
+        ShopKind::Potion => match item_obj.kind {
+            ItemKind::Potion => 1.0,
+            _ => 0.0,
+        },
+        ShopKind::Food => match item_obj.kind {
+            ItemKind::Food => 1.0,
+            _ => 0.0,
+        },
+    };
+    gen_item_by_level(floor_level, f, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_factors() {
+        rules::init(concat!(env!("CARGO_MANIFEST_DIR"), "/../assets"), None);
+        let r = &RULES.town;
+
+        assert_eq!(buy_price_factor(0.0), r.buy_price_factor);
+        assert_eq!(sell_price_factor(0.0), r.sell_price_factor);
+        // Selling price never exceeds buying price even with large bonus
+        assert!(sell_price_factor(100.0) < buy_price_factor(100.0));
+        // Hostile shopkeepers don't pay negative price
+        assert_eq!(sell_price_factor(-100.0), 0.0);
+
+        assert_eq!(supply_price_factor(0), 1.0);
+        assert!(supply_price_factor(5) < supply_price_factor(1));
+        assert_eq!(supply_price_factor(100_000), r.min_supply_price_factor);
+
+        assert_eq!(shop_level(0), 1);
+        assert_eq!(shop_level(r.development_per_shop_level * 2), 3);
+        assert_eq!(shop_level(u32::MAX), r.max_shop_level);
+    }
+}
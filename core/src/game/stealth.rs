@@ -0,0 +1,263 @@
+//! Stealth and noise.
+//! NPCs notice enemies in sight or hear noises, and their awareness decides whether they chase enemies.
+
+use crate::game::view::Sight;
+use crate::game::InfoGetter;
+use common::gamedata::*;
+use geom::*;
+use rules::RULES;
+
+/// Make a noise at the position. NPCs hostile to the source in the volume range become suspicious.
+/// If the source is None, all NPCs in the range hear it.
+pub fn make_noise(gd: &mut GameData, source: Option<CharaId>, pos: Vec2d, volume: i32) {
+    if volume <= 0 {
+        return;
+    }
+    let listeners: Vec<CharaId> = gd
+        .get_charas_on_map()
+        .into_iter()
+        .filter(|cid| *cid != CharaId::Player && Some(*cid) != source)
+        .filter(|cid| match source {
+            Some(source) => gd.chara_relation(*cid, source) == Relationship::HOSTILE,
+            None => true,
+        })
+        .filter(|cid| match gd.chara_pos(*cid) {
+            Some(listener_pos) => listener_pos.mdistance(pos) <= volume,
+            None => false,
+        })
+        .collect();
+
+    for cid in listeners {
+        let ai = &mut gd.chara.get_mut(cid).ai;
+        if !ai.awareness.is_hunting() {
+            ai.awareness = Awareness::Suspicious {
+                pos,
+                turns: RULES.stealth.suspicious_turns,
+            };
+        }
+    }
+}
+
+/// Noise volume of walking, reduced by Stealth skill
+pub fn walking_noise(gd: &GameData, cid: CharaId) -> i32 {
+    let rule = &RULES.stealth;
+    let skill_level = gd.chara.get(cid).skills.get(SkillKind::Stealth) as f32;
+    (rule.noise_walk as f32 * rule.stealth_base / (rule.stealth_base + skill_level)).round() as i32
+}
+
+/// Make a noise after the character moved to the position
+pub fn walk(gd: &mut GameData, cid: CharaId, pos: Vec2d) {
+    let volume = match gd.get_current_map().tile[pos].special {
+        SpecialTileKind::Door { .. } => RULES.stealth.noise_door,
+        _ => walking_noise(gd, cid),
+    };
+    make_noise(gd, Some(cid), pos, volume);
+}
+
+/// Returns true if attacks to the target get the sneak attack bonus.
+/// Only enemies unaware of any enemy are exposed, not ones hunting another character.
+pub fn is_exposed_to(gd: &GameData, target: CharaId, attacker: CharaId) -> bool {
+    target != CharaId::Player
+        && gd.chara.get(target).ai.awareness == Awareness::Unaware
+        && gd.chara_relation(attacker, target) == Relationship::HOSTILE
+}
+
+/// Make the character start hunting the target
+pub fn alert(gd: &mut GameData, cid: CharaId, target: CharaId) {
+    if cid == CharaId::Player {
+        return;
+    }
+    let pos = if let Some(pos) = gd.chara_pos(target) {
+        pos
+    } else {
+        return;
+    };
+    gd.chara.get_mut(cid).ai.awareness = Awareness::Hunting {
+        target,
+        pos,
+        turns: RULES.stealth.hunting_turns,
+    };
+}
+
+/// Update the awareness of the NPC by enemies in sight. Called at the beginning of NPC turns.
+pub fn update_awareness(gd: &mut GameData, cid: CharaId, sight: &Sight) {
+    let rule = &RULES.stealth;
+    let center = if let Some(center) = gd.chara_pos(cid) {
+        center
+    } else {
+        return;
+    };
+
+    let mut enemies: Vec<(CharaId, Vec2d)> = gd
+        .get_charas_on_map()
+        .into_iter()
+        .filter(|other| *other != cid && gd.chara_relation(cid, *other) == Relationship::HOSTILE)
+        .filter_map(|other| gd.chara_pos(other).map(|pos| (other, pos)))
+        .filter(|(_, pos)| sight.is_visible(*pos))
+        .collect();
+    enemies.sort_by_key(|(_, pos)| center.mdistance(*pos));
+
+    let awareness = gd.chara.get(cid).ai.awareness;
+    let hunting = |target: CharaId, pos: Vec2d| Awareness::Hunting {
+        target,
+        pos,
+        turns: rule.hunting_turns,
+    };
+
+    let awareness = match awareness {
+        Awareness::Hunting { target, pos, turns } => {
+            if let Some((_, target_pos)) = enemies.iter().find(|(enemy, _)| *enemy == target) {
+                hunting(target, *target_pos)
+            } else if let Some((enemy, enemy_pos)) = enemies.first() {
+                hunting(*enemy, *enemy_pos)
+            } else if turns > 1 {
+                Awareness::Hunting {
+                    target,
+                    pos,
+                    turns: turns - 1,
+                }
+            } else {
+                Awareness::Suspicious {
+                    pos,
+                    turns: rule.suspicious_turns,
+                }
+            }
+        }
+        Awareness::Suspicious { .. } | Awareness::Unaware => {
+            let suspicious = awareness != Awareness::Unaware;
+            if let Some((enemy, enemy_pos)) = enemies
+                .iter()
+                .find(|(enemy, pos)| notice(gd, cid, *enemy, center, *pos, suspicious))
+            {
+                hunting(*enemy, *enemy_pos)
+            } else {
+                match awareness {
+                    Awareness::Suspicious { pos, turns } if turns > 1 => Awareness::Suspicious {
+                        pos,
+                        turns: turns - 1,
+                    },
+                    _ => Awareness::Unaware,
+                }
+            }
+        }
+    };
+    gd.chara.get_mut(cid).ai.awareness = awareness;
+}
+
+/// Roll whether the character notices the enemy in sight.
+/// Adjacent enemies are always noticed.
+fn notice(
+    gd: &GameData,
+    cid: CharaId,
+    enemy: CharaId,
+    center: Vec2d,
+    pos: Vec2d,
+    suspicious: bool,
+) -> bool {
+    if center.is_adjacent(pos) {
+        return true;
+    }
+    let distance = center.mdistance(pos);
+    let rule = &RULES.stealth;
+    let view_range = std::cmp::max(gd.chara.get(cid).attr.view_range, 1);
+    let skill_level = gd.chara.get(enemy).skills.get(SkillKind::Stealth) as f32;
+    let mut p = rule.notice_probability * (1.0 - distance as f32 / (view_range + 1) as f32)
+        / (1.0 + rule.stealth_factor * skill_level);
+    if suspicious {
+        p *= rule.suspicious_notice_factor;
+    }
+    rng::gen_bool(p.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{add_chara, chara, fixture};
+
+    fn update(gd: &mut GameData, cid: CharaId) {
+        let sight = Sight::new(gd, cid);
+        update_awareness(gd, cid, &sight);
+    }
+
+    #[test]
+    fn noise_makes_enemies_suspicious() {
+        let (mut gd, cid) = fixture(Vec2d(10, 10));
+        make_noise(&mut gd, Some(CharaId::Player), Vec2d(2, 2), 4);
+        assert_eq!(gd.chara.get(cid).ai.awareness, Awareness::Unaware);
+
+        make_noise(&mut gd, Some(CharaId::Player), Vec2d(6, 6), 8);
+        assert_eq!(
+            gd.chara.get(cid).ai.awareness,
+            Awareness::Suspicious {
+                pos: Vec2d(6, 6),
+                turns: RULES.stealth.suspicious_turns,
+            }
+        );
+
+        // Hunting characters keep hunting
+        alert(&mut gd, cid, CharaId::Player);
+        make_noise(&mut gd, None, Vec2d(12, 12), 4);
+        assert!(gd.chara.get(cid).ai.awareness.is_hunting());
+    }
+
+    #[test]
+    fn stealth_reduces_walking_noise() {
+        let (mut gd, _) = fixture(Vec2d(10, 10));
+        assert_eq!(
+            walking_noise(&gd, CharaId::Player),
+            RULES.stealth.noise_walk
+        );
+        gd.chara
+            .get_mut(CharaId::Player)
+            .skills
+            .skills
+            .insert(SkillKind::Stealth, 30);
+        assert!(walking_noise(&gd, CharaId::Player) < RULES.stealth.noise_walk);
+    }
+
+    #[test]
+    fn lose_sight_of_target() {
+        let (mut gd, cid) = fixture(Vec2d(3, 3));
+        update(&mut gd, cid);
+        assert_eq!(
+            gd.chara.get(cid).ai.awareness,
+            Awareness::Hunting {
+                target: CharaId::Player,
+                pos: Vec2d(2, 2),
+                turns: RULES.stealth.hunting_turns,
+            }
+        );
+
+        gd.get_current_map_mut()
+            .locate_chara(CharaId::Player, Vec2d(14, 14));
+        for _ in 0..RULES.stealth.hunting_turns {
+            update(&mut gd, cid);
+        }
+        assert_eq!(
+            gd.chara.get(cid).ai.awareness,
+            Awareness::Suspicious {
+                pos: Vec2d(2, 2),
+                turns: RULES.stealth.suspicious_turns,
+            }
+        );
+        for _ in 0..RULES.stealth.suspicious_turns {
+            update(&mut gd, cid);
+        }
+        assert_eq!(gd.chara.get(cid).ai.awareness, Awareness::Unaware);
+    }
+
+    #[test]
+    fn sneak_attack_only_on_unaware_enemies() {
+        let (mut gd, cid) = fixture(Vec2d(3, 3));
+        assert!(is_exposed_to(&gd, cid, CharaId::Player));
+        assert!(!is_exposed_to(&gd, CharaId::Player, cid));
+
+        // Hunting another character
+        let other = add_chara(&mut gd, chara("monster", Vec2d(8, 8)), Vec2d(8, 8));
+        alert(&mut gd, cid, other);
+        assert!(!is_exposed_to(&gd, cid, CharaId::Player));
+
+        // Friendly fire
+        assert!(!is_exposed_to(&gd, other, cid));
+    }
+}
@@ -2,7 +2,7 @@ use crate::game::Game;
 use common::gamedata::*;
 use geom::*;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Target {
     None,
     Tile(Vec2d),
@@ -0,0 +1,318 @@
+//! Helpers for unit tests of game logic.
+//!
+//! Object data are loaded from a small pak generated in a temporary directory,
+//! because object packages are not included in this repository.
+
+use super::Game;
+use common::gamedata::*;
+use common::obj::*;
+use common::objholder::ItemIdx;
+use common::script::{Instruction, Script, ScriptHook, Trigger};
+use geom::{Array2d, Vec2d};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, Once};
+
+pub const TILE_ID: &str = "test-tile";
+pub const WALL_ID: &str = "test-wall";
+pub const TRAP_ID: &str = "test-trap";
+pub const CHARA_TEMPLATE_ID: &str = "test-chara";
+/// Tool item with light radius 3
+pub const TORCH_ID: &str = "test-torch";
+/// Potion item, unidentified until its kind is known
+pub const POTION_ID: &str = "test-potion";
+/// Melee weapon
+pub const SWORD_ID: &str = "test-sword";
+/// Body armor with defence 5 against each element
+pub const ARMOR_ID: &str = "test-armor";
+/// Map template whose hooks start `SCRIPT_ID` at 0:00 and 23:00
+pub const MAP_TEMPLATE_ID: &str = "test-map";
+pub const SCRIPT_ID: &str = "test-script";
+/// Prefab with NPCs at (0, 0), (1, 1), (8, 8), (20, 0) and an unknown NPC at (0, 1)
+pub const PREFAB_ID: &str = "test-prefab";
+
+static INIT: Once = Once::new();
+
+lazy_static! {
+    static ref GAME_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Initialize rules, config, texts and objects for tests
+pub fn init() {
+    INIT.call_once(|| {
+        let assets_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets");
+        std::env::set_var("RUSTED_RUINS_ASSETS_DIR", assets_dir);
+        rules::init(assets_dir, None);
+        crate::init_lazy_statics();
+        common::gobj::init(vec![write_test_pak()]);
+    });
+}
+
+/// Tests that write the game log or run turns must hold this lock,
+/// because the game log is shared between test threads.
+pub fn lock() -> MutexGuard<'static, ()> {
+    init();
+    GAME_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn chara(faction: &str, pos: Vec2d) -> Chara {
+    Chara {
+        faction: FactionId::new(faction).unwrap(),
+        attr: CharaAttributes {
+            max_hp: 100,
+            spd: 100,
+            view_range: 8,
+            ..CharaAttributes::default()
+        },
+        hp: 100,
+        ai: CharaAI {
+            initial_pos: pos,
+            ..CharaAI::default()
+        },
+        ..Chara::default()
+    }
+}
+
+/// 16x16 map with the player at (2, 2), and a monster hostile to the player
+pub fn fixture(monster_pos: Vec2d) -> (GameData, CharaId) {
+    init();
+    let mut gd = GameData::empty();
+    let rid = gd
+        .region
+        .add_region(Region::new("test", Map::new(16, 16), 0));
+    gd.set_initial_mapid(MapId::RegionMap { rid });
+    let monster = FactionId::new("monster").unwrap();
+    gd.faction.set(monster, gd.faction.get(monster) - 100);
+
+    gd.add_chara(chara("!player", Vec2d(2, 2)), CharaKind::Player);
+    gd.get_current_map_mut()
+        .locate_chara(CharaId::Player, Vec2d(2, 2));
+    let cid = add_chara(&mut gd, chara("monster", monster_pos), monster_pos);
+    (gd, cid)
+}
+
+pub fn add_chara(gd: &mut GameData, chara: Chara, pos: Vec2d) -> CharaId {
+    let cid = gd.add_chara_to_map(chara, gd.get_current_mapid());
+    gd.get_current_map_mut().locate_chara(cid, pos);
+    cid
+}
+
+/// Equipment with given affixes and no other attributes
+pub fn equipment(id: &str, affixes: &[(AffixKind, u16)]) -> Item {
+    let idx: ItemIdx = common::gobj::id_to_idx(id);
+    Item {
+        idx,
+        kind: common::gobj::get_obj(idx).kind,
+        flags: ItemFlags::empty(),
+        quality: ItemQuality::default(),
+        attributes: affixes
+            .iter()
+            .map(|&(kind, value)| ItemAttribute::Affix { kind, value })
+            .collect(),
+        bless: BlessState::Normal,
+    }
+}
+
+/// Game without save directory
+pub fn game(gd: GameData) -> Game {
+    let mut game = Game::empty();
+    game.gd = gd;
+    game
+}
+
+fn write_test_pak() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rusted-ruins-test-pak-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut builder = tar::Builder::new(std::fs::File::create(dir.join("test.pak")).unwrap());
+
+    for (i, obj) in test_objects().iter().enumerate() {
+        let mut data = Vec::new();
+        common::pakutil::write_object(&mut data, obj).unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, format!("{}.obj", i), data.as_slice())
+            .unwrap();
+    }
+    builder.finish().unwrap();
+    dir
+}
+
+fn img() -> Img {
+    Img {
+        data: Vec::new(),
+        w: 1,
+        h: 1,
+        grid_nx: 1,
+        grid_ny: 1,
+        n_frame: 1,
+        n_pattern: 1,
+        n_anim_frame: 1,
+        duration: 0,
+        variation_rule: ImgVariationRule::None,
+    }
+}
+
+fn test_objects() -> Vec<Object> {
+    vec![
+        Object::Tile(TileObject {
+            id: TILE_ID.into(),
+            img: img(),
+            kind: TileKind::Ground,
+            symbol_color: (0, 0, 0),
+        }),
+        Object::Wall(WallObject {
+            id: WALL_ID.into(),
+            hp: 100,
+            base_draw: true,
+            img: img(),
+            symbol_color: (0, 0, 0),
+            build_skill: None,
+            materials: None,
+            mining_rewards: Vec::new(),
+        }),
+        Object::SpecialTile(SpecialTileObject {
+            id: TRAP_ID.into(),
+            always_background: false,
+            img: img(),
+            trap: Some(TrapParams {
+                effect: Effect {
+                    kind: vec![EffectKind::Status {
+                        status: StatusEffect::Asleep,
+                    }],
+                    ..Effect::default()
+                },
+                power: 10.0,
+                difficulty: 0,
+            }),
+        }),
+        Object::CharaTemplate(CharaTemplateObject {
+            id: CHARA_TEMPLATE_ID.into(),
+            img: img(),
+            race: "animal".into(),
+            gen_weight: 1.0,
+            gen_level: 1,
+            default_ai_kind: NpcAIKind::default(),
+            base_attr: CharaBaseAttr {
+                base_hp: 100,
+                str: 10,
+                vit: 10,
+                dex: 10,
+                int: 10,
+                wil: 10,
+                cha: 10,
+                spd: 100,
+            },
+            skill_bonus: Default::default(),
+            hooks: Vec::new(),
+        }),
+        Object::Item(ItemObject {
+            kind: ItemKind::Special,
+            group: "key".into(),
+            ..item(&rules::RULES.item.key_item_id)
+        }),
+        Object::Item(ItemObject {
+            kind: ItemKind::Tool,
+            light_radius: 3,
+            ..item(TORCH_ID)
+        }),
+        Object::Item(ItemObject {
+            kind: ItemKind::Potion,
+            ..item(POTION_ID)
+        }),
+        Object::Item(ItemObject {
+            kind: ItemKind::Weapon(WeaponKind::Sword),
+            ..item(SWORD_ID)
+        }),
+        Object::Item(ItemObject {
+            kind: ItemKind::Armor(ArmorKind::Body),
+            def: ElementArray([5; 6]),
+            ..item(ARMOR_ID)
+        }),
+        Object::MapTemplate(MapTemplateObject {
+            hooks: [0, 23]
+                .iter()
+                .map(|hour| ScriptHook {
+                    trigger: Trigger::Hour(*hour),
+                    script_id: SCRIPT_ID.into(),
+                })
+                .collect(),
+            ..map_template(MAP_TEMPLATE_ID)
+        }),
+        Object::MapTemplate(MapTemplateObject {
+            npcs: [Vec2d(0, 0), Vec2d(1, 1), Vec2d(8, 8), Vec2d(20, 0)]
+                .iter()
+                .map(|pos| {
+                    let id = CHARA_TEMPLATE_ID.into();
+                    (*pos, NpcGen { id })
+                })
+                .chain(std::iter::once((
+                    Vec2d(0, 1),
+                    NpcGen {
+                        id: "unknown-chara".into(),
+                    },
+                )))
+                .collect(),
+            ..map_template(PREFAB_ID)
+        }),
+        Object::Script(ScriptObject {
+            id: SCRIPT_ID.into(),
+            script: Script::from_map(
+                std::iter::once(("start".to_owned(), Vec::<Instruction>::new())).collect(),
+            ),
+        }),
+    ]
+}
+
+fn map_template(id: &str) -> MapTemplateObject {
+    MapTemplateObject {
+        id: id.into(),
+        w: 1,
+        h: 1,
+        tile_table: Vec::new(),
+        tile: Array2d::new(1, 1, Default::default()),
+        wall_table: Vec::new(),
+        wall: Array2d::new(1, 1, Default::default()),
+        deco_table: Vec::new(),
+        deco: Array2d::new(1, 1, None),
+        boundary: Default::default(),
+        entrance: Default::default(),
+        items: Vec::new(),
+        music: String::new(),
+        npcs: Vec::new(),
+        hooks: Vec::new(),
+    }
+}
+
+fn item(id: &str) -> ItemObject {
+    ItemObject {
+        id: id.into(),
+        img: img(),
+        kind: ItemKind::Object,
+        group: String::new(),
+        default_flags: ItemFlags::empty(),
+        basic_price: 100,
+        w: 10,
+        quality_kind: QualityKind::None,
+        gen_weight: 0.0,
+        shop_weight: 0.0,
+        gen_level: 1,
+        eff: 0,
+        eff_var: 0,
+        def: ElementArray([0; 6]),
+        throw_effect: None,
+        magical_effect: None,
+        medical_effect: None,
+        use_effect: None,
+        tool_effect: ToolEffect::None,
+        light_radius: 0,
+        nutrition: 0,
+        charge: [0, 0],
+        harvest: None,
+        facility: None,
+        material_group: String::new(),
+        material: MaterialName::new(),
+        titles: Vec::new(),
+    }
+}
@@ -2,6 +2,7 @@ use common::gamedata::*;
 use common::gobj;
 use common::obj::SiteGenObject;
 use geom::Vec2d;
+use rules::RULES;
 
 /// Create town from SiteGenObect and add it to region map
 pub fn add_town(gd: &mut GameData, rid: RegionId, pos: Vec2d, town_id: &str) {
@@ -27,6 +28,7 @@ pub fn add_town(gd: &mut GameData, rid: RegionId, pos: Vec2d, town_id: &str) {
                 kind: shop_gen_data.kind,
                 items: ItemList::new(),
                 level: 1,
+                money: 0,
             };
             town.add_shop(shop, shop_gen_data.chara_n);
         }
@@ -34,10 +36,27 @@ pub fn add_town(gd: &mut GameData, rid: RegionId, pos: Vec2d, town_id: &str) {
     update_shops(gd, sid, sg);
 }
 
+/// Restock shops in the town if the restock interval has passed since the last restocking
+pub fn restock_shops_if_needed(gd: &mut GameData, sid: SiteId) {
+    let now = gd.time.current_time();
+    let town = match &gd.region.get_site(sid).content {
+        SiteContent::Town { town } => town,
+        _ => return,
+    };
+    let interval = Duration::from_days(RULES.town.restock_interval_days.into());
+    if now.duration_from(town.last_restock) < interval {
+        return;
+    }
+
+    let sg: &SiteGenObject = gobj::get_by_id(town.id());
+    update_shops(gd, sid, sg);
+}
+
 /// Update shop states
 pub fn update_shops(gd: &mut GameData, sid: SiteId, sg: &SiteGenObject) {
-    use crate::game::shop::update_items_on_shop;
+    use crate::game::shop::{restock_shop, shop_level};
 
+    let now = gd.time.current_time();
     let site = gd.region.get_site_mut(sid);
     let town = match &mut site.content {
         SiteContent::Town { ref mut town } => town,
@@ -47,8 +66,16 @@ pub fn update_shops(gd: &mut GameData, sid: SiteId, sg: &SiteGenObject) {
         }
     };
 
-    for (i, shop) in town.iter_shops_mut().enumerate() {
-        let shop_gen = &sg.shops[i];
-        update_items_on_shop(shop, shop_gen);
+    let level = shop_level(town.development);
+    for (n, shop) in town.iter_shops_mut() {
+        if let Some(shop_gen) = sg.shops.iter().find(|shop_gen| shop_gen.chara_n == *n) {
+            restock_shop(shop, shop_gen, level);
+        }
+    }
+
+    for supply in town.supply.values_mut() {
+        *supply = (*supply as f32 * RULES.town.supply_decay) as u32;
     }
+    town.supply.retain(|_, supply| *supply > 0);
+    town.last_restock = now;
 }
@@ -1,10 +1,13 @@
 use super::chara::preturn::preturn;
 use super::chara::CharaEx;
+use super::hook;
 use super::npc::process_npc_turn;
+use super::quest::{self, QuestEvent};
 use super::DialogOpenRequest;
 use super::{Game, GameState, InfoGetter};
 use common::basic::WAIT_TIME_NUMERATOR;
 use common::gamedata::*;
+use common::script::Trigger;
 use rules::RULES;
 
 /// Main game turn loop
@@ -30,6 +33,7 @@ pub fn turn_loop(game: &mut Game) {
             if cid == CharaId::Player {
                 game.state = GameState::PlayerTurn;
                 game.update_before_player_turn();
+                game.start_queued_script();
                 return;
             } else {
                 process_npc_turn(game, cid);
@@ -97,6 +101,9 @@ fn remove_dying_charas(game: &mut Game) -> bool {
                 game.request_dialog_open(DialogOpenRequest::GameOver);
                 return true;
             }
+            let idx = chara.template;
+            hook::trigger_chara(game, cid, &Trigger::Death);
+            quest::notify(game, QuestEvent::CharaDied { cid, idx });
             // Remove dying chara
             game.gd.remove_chara_from_map(cid);
             // If the current target is cid, remove it
@@ -118,5 +125,53 @@ fn advance_game_time(game: &mut Game, advanced_clock: u32) {
     const AVERAGE_CLOCK_PER_TURN: u32 = WAIT_TIME_NUMERATOR / 100;
     let advanced_secs =
         minutes_per_turn * 60.0 * advanced_clock as f32 / AVERAGE_CLOCK_PER_TURN as f32;
-    game.gd.time.advance(advanced_secs as u64)
+    pass_time(game, advanced_secs as u64);
+}
+
+/// Advance game time, and trigger hooks for every hour that has come
+fn pass_time(game: &mut Game, secs: u64) {
+    let hours = |game: &Game| {
+        game.gd
+            .time
+            .current_time()
+            .duration_from(Time::default())
+            .as_hours()
+    };
+    let prev_hours = hours(game);
+    game.gd.time.advance(secs);
+    let new_hours = hours(game);
+
+    for h in (prev_hours + 1)..=new_hours {
+        hook::trigger(game, &Trigger::Hour((h % 24) as u16));
+    }
+    if new_hours != prev_hours {
+        quest::notify(game, QuestEvent::TimePassed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util;
+    use geom::Vec2d;
+
+    #[test]
+    fn hour_hooks_for_every_hour() {
+        let _lock = test_util::lock();
+        let (gd, _) = test_util::fixture(Vec2d(12, 12));
+        let mut game = test_util::game(gd);
+        game.gd.get_current_map_mut().template_id = Some(test_util::MAP_TEMPLATE_ID.to_owned());
+        let hour = game.gd.time.current_date().hour as u64;
+        game.gd.time.advance((22 + 24 - hour) % 24 * SECS_PER_HOUR);
+
+        // 22:00 -> 22:30
+        pass_time(&mut game, SECS_PER_HOUR / 2);
+        assert!(game.script_queue.is_empty());
+        // 22:30 -> 23:30
+        pass_time(&mut game, SECS_PER_HOUR);
+        assert_eq!(game.script_queue.len(), 1);
+        // 23:30 -> 23:30 of the next day, including 0:00
+        pass_time(&mut game, SECS_PER_DAY);
+        assert_eq!(game.script_queue.len(), 3);
+    }
 }
@@ -0,0 +1,288 @@
+//! This module processes the view of characters
+
+use super::fov::fov;
+use crate::game::Game;
+use crate::game::InfoGetter;
+use common::gamedata::*;
+use common::gobj;
+use geom::*;
+use rules::RULES;
+
+/// The cache for determining player's view
+pub struct ViewMap {
+    visible: Array2d<bool>,
+}
+
+impl ViewMap {
+    pub fn new() -> ViewMap {
+        ViewMap {
+            visible: Array2d::new(128, 128, false),
+        }
+    }
+
+    fn reserve_size(&mut self, w: u32, h: u32) {
+        let size = self.visible.size();
+        if size.0 >= w || size.1 >= h {
+            use std::cmp::max;
+            self.visible = Array2d::new(max(size.0, w), max(size.1, h), false);
+        }
+    }
+
+    fn fill(&mut self, w: u32, h: u32, value: bool) {
+        for ny in 0..h {
+            for nx in 0..w {
+                self.visible[(nx, ny)] = value;
+            }
+        }
+    }
+
+    pub fn get_tile_visible(&self, pos: Vec2d) -> bool {
+        if self.visible.in_range(pos) {
+            self.visible[pos]
+        } else {
+            false
+        }
+    }
+}
+
+pub fn update_view_map(game: &mut Game) {
+    let map = game.gd.get_current_map();
+    let (w, h) = map.size();
+    let view_map = &mut game.view_map;
+    view_map.reserve_size(w, h);
+
+    if game.gd.get_current_mapid().is_region_map() {
+        view_map.fill(w, h, true); // Fill by true when region map
+        return;
+    }
+
+    // Fill by false
+    view_map.fill(w, h, false); // Fill by false
+
+    calc_visible_tiles(&game.gd, CharaId::Player, |pos| {
+        view_map.visible[pos] = true;
+    });
+}
+
+/// Ambient light level of the current map (0.0 - 1.0).
+/// Dungeons get darker on deeper floors, and outdoor maps follow the time of day.
+pub fn ambient_light(gd: &GameData) -> f32 {
+    let mid = gd.get_current_mapid();
+    if mid.is_region_map() {
+        return 1.0;
+    }
+    let rule = &RULES.view;
+    match gd.region.get_site(mid.sid()).content {
+        SiteContent::AutoGenDungeon { .. } => {
+            (rule.dungeon_light_base - mid.floor() as f32 * rule.dungeon_light_per_floor).max(0.0)
+        }
+        _ => {
+            let hour = gd.time.current_date().hour as usize;
+            rule.outdoor_light[hour % 24]
+        }
+    }
+}
+
+/// Light sources on the current map, their positions and radii
+fn light_sources(gd: &GameData) -> Vec<(Vec2d, i32)> {
+    gd.get_charas_on_map()
+        .into_iter()
+        .filter_map(|cid| {
+            let item = gd.chara.get(cid).equip.item(EquipSlotKind::Tool, 0)?;
+            let radius = gobj::get_obj(item.idx).light_radius as i32;
+            if radius > 0 {
+                gd.chara_pos(cid).map(|pos| (pos, radius))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Call given function for each tile the character can see.
+/// A tile is visible if it is in the view range, not occluded by walls, and lit.
+/// The function may be called more than once for the same tile.
+pub fn calc_visible_tiles<F: FnMut(Vec2d)>(gd: &GameData, cid: CharaId, mut f: F) {
+    let center = if let Some(center) = gd.chara_pos(cid) {
+        center
+    } else {
+        return;
+    };
+    let map = gd.get_current_map();
+    let range = gd.chara.get(cid).attr.view_range;
+    let dark_range = std::cmp::max(
+        RULES.view.dark_view_range,
+        (range as f32 * ambient_light(gd)).round() as i32,
+    );
+    let lights = light_sources(gd);
+
+    let is_lit = |pos: Vec2d| {
+        center.mdistance(pos) <= dark_range
+            || map.tile[pos].lit
+            || lights
+                .iter()
+                .any(|(light_pos, radius)| light_pos.mdistance(pos) <= *radius)
+    };
+    let is_blocking = |pos: Vec2d| {
+        !map.is_inside(pos) || !map.tile[pos].wall.is_empty() || map.tile[pos].special.is_blocking()
+    };
+
+    fov(center, range, is_blocking, |pos| {
+        if map.is_inside(pos) && center.mdistance(pos) <= range && is_lit(pos) {
+            f(pos);
+        }
+    });
+}
+
+/// Tiles a character can see.
+/// NPCs compute this once at the beginning of their turns, and use it for their decisions.
+pub struct Sight {
+    /// Position of the top left tile of `visible`
+    origin: Vec2d,
+    visible: Array2d<bool>,
+}
+
+impl Sight {
+    pub fn new(gd: &GameData, cid: CharaId) -> Sight {
+        let range = std::cmp::max(gd.chara.get(cid).attr.view_range, 0);
+        let center = gd.chara_pos(cid).unwrap_or(Vec2d(0, 0));
+        let size = (range * 2 + 1) as u32;
+        let origin = center - (range, range);
+        let mut visible = Array2d::new(size, size, false);
+        calc_visible_tiles(gd, cid, |pos| visible[pos - origin] = true);
+        Sight { origin, visible }
+    }
+
+    /// Returns true if the character can see given position
+    pub fn is_visible(&self, pos: Vec2d) -> bool {
+        let pos = pos - self.origin;
+        self.visible.in_range(pos) && self.visible[pos]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{self, add_chara, chara, TORCH_ID};
+
+    /// The player is at (2, 2) on the given floor of a dungeon
+    fn dungeon(floor: u32) -> GameData {
+        let (mut gd, _) = test_util::fixture(Vec2d(12, 12));
+        let rid = gd.get_current_mapid().rid();
+        let mut site = Site::new(floor + 1, None);
+        site.content = SiteContent::AutoGenDungeon {
+            dungeon_kind: DungeonKind::Ruin,
+        };
+        let sid = gd
+            .add_site(site, SiteKind::AutoGenDungeon, rid, Vec2d(0, 0))
+            .unwrap();
+        for _ in 0..=floor {
+            gd.add_map(Map::new(16, 16), sid, 0);
+        }
+        gd.set_current_mapid(MapId::SiteMap { sid, floor });
+        gd.get_current_map_mut()
+            .locate_chara(CharaId::Player, Vec2d(2, 2));
+        gd
+    }
+
+    fn visible_tiles(gd: &GameData, cid: CharaId) -> Vec<Vec2d> {
+        let mut tiles = Vec::new();
+        calc_visible_tiles(gd, cid, |pos| tiles.push(pos));
+        tiles.sort();
+        tiles.dedup();
+        tiles
+    }
+
+    #[test]
+    fn ambient_light_by_map() {
+        let (mut gd, _) = test_util::fixture(Vec2d(12, 12));
+        assert_eq!(ambient_light(&gd), 1.0);
+        // Outdoor light does not affect region maps
+        gd.time.advance(SECS_PER_HOUR * 22);
+        assert_eq!(ambient_light(&gd), 1.0);
+
+        let rule = &RULES.view;
+        let light = |floor| {
+            let gd = dungeon(floor);
+            assert_eq!(
+                visible_tiles(&gd, CharaId::Player)
+                    .iter()
+                    .map(|pos| pos.mdistance(Vec2d(2, 2)))
+                    .max(),
+                Some(std::cmp::max(
+                    rule.dark_view_range,
+                    (8.0 * ambient_light(&gd)).round() as i32
+                ))
+            );
+            ambient_light(&gd)
+        };
+        assert!((light(0) - rule.dungeon_light_base).abs() < 1e-6);
+        assert!(light(2) < light(1));
+        assert_eq!(light(10), 0.0);
+    }
+
+    #[test]
+    fn see_lit_rooms_in_darkness() {
+        let mut gd = dungeon(10);
+        let rule = &RULES.view;
+        assert!(visible_tiles(&gd, CharaId::Player)
+            .iter()
+            .all(|pos| pos.mdistance(Vec2d(2, 2)) <= rule.dark_view_range));
+
+        for x in 6..9 {
+            gd.get_current_map_mut().tile[Vec2d(x, 2)].lit = true;
+        }
+        let sight = Sight::new(&gd, CharaId::Player);
+        assert!((6..9).all(|x| sight.is_visible(Vec2d(x, 2))));
+        assert!(!sight.is_visible(Vec2d(5, 2)));
+        assert!(!sight.is_visible(Vec2d(9, 2)));
+
+        // Lit tiles out of the view range are not visible
+        gd.chara.get_mut(CharaId::Player).attr.view_range = 4;
+        let sight = Sight::new(&gd, CharaId::Player);
+        assert!(sight.is_visible(Vec2d(6, 2)));
+        assert!(!sight.is_visible(Vec2d(7, 2)));
+    }
+
+    #[test]
+    fn torches_light_around_holders() {
+        let mut gd = dungeon(10);
+        let mut holder = chara("!player", Vec2d(10, 2));
+        holder.equip = EquipItemList::new(&[EquipSlotKind::Tool]);
+        holder.equip.equip(
+            EquipSlotKind::Tool,
+            0,
+            crate::game::item::gen::gen_item_from_id(TORCH_ID, 1),
+        );
+        let cid = add_chara(&mut gd, holder, Vec2d(10, 2));
+        assert_eq!(light_sources(&gd), vec![(Vec2d(10, 2), 3)]);
+
+        let sight = Sight::new(&gd, CharaId::Player);
+        assert!(sight.is_visible(Vec2d(7, 2)));
+        assert!(sight.is_visible(Vec2d(9, 3)));
+        assert!(!sight.is_visible(Vec2d(6, 2)));
+
+        // Walls block the light
+        gd.get_current_map_mut()
+            .set_wall(Vec2d(5, 2), gobj::id_to_idx(test_util::WALL_ID));
+        let sight = Sight::new(&gd, CharaId::Player);
+        assert!(!sight.is_visible(Vec2d(7, 2)));
+
+        // The holder sees tiles lit by its own torch
+        let sight = Sight::new(&gd, cid);
+        assert!(sight.is_visible(Vec2d(10, 5)));
+        assert!(!sight.is_visible(Vec2d(10, 6)));
+    }
+
+    #[test]
+    fn sight_matches_visible_tiles() {
+        let mut gd = dungeon(1);
+        gd.get_current_map_mut()
+            .set_wall(Vec2d(4, 3), gobj::id_to_idx(test_util::WALL_ID));
+        let sight = Sight::new(&gd, CharaId::Player);
+        let tiles = visible_tiles(&gd, CharaId::Player);
+        for pos in RectIter::new(Vec2d(-2, -2), Vec2d(17, 17)) {
+            assert_eq!(sight.is_visible(pos), tiles.contains(&pos), "{:?}", pos);
+        }
+    }
+}
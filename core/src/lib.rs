@@ -0,0 +1,47 @@
+//! Game core of Rusted Ruins.
+//! Includes game logic, texts and logs, and does not depend on SDL.
+//! Front-ends create `game::Game`, and drive it through `game::DoPlayerAction`.
+
+extern crate rusted_ruins_common as common;
+extern crate rusted_ruins_geom as geom;
+extern crate rusted_ruins_map_generator as map_generator;
+extern crate rusted_ruins_rng as rng;
+extern crate rusted_ruins_rules as rules;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log as applog;
+
+#[doc(hidden)]
+pub use fluent;
+
+#[macro_use]
+pub mod log;
+#[macro_use]
+pub mod text;
+pub mod audio;
+pub mod chara_log;
+pub mod config;
+pub mod game;
+
+/// Initialize lazy_static values
+pub fn init_lazy_statics() {
+    config::init();
+    text::init();
+    log::init();
+}
+
+pub fn init_obj() {
+    let mut data_dirs = config::get_data_dirs();
+    for d in data_dirs.iter_mut() {
+        info!("Loading objects from \"{}\"", d.to_string_lossy());
+        d.push("paks");
+    }
+    common::gobj::init(data_dirs);
+}
+
+pub fn init_rules() {
+    rules::init(&*config::ASSETS_DIR, config::ADDON_DIR.as_ref());
+}
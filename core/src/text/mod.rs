@@ -3,6 +3,8 @@ pub mod readable;
 mod text_id_impl;
 mod to_text;
 
+pub use to_text::ItemName;
+
 use crate::config;
 use common::basic;
 use fluent::concurrent::FluentBundle;
@@ -247,26 +249,30 @@ pub fn to_txt<T: ToTextId>(a: &T) -> String {
     misc_txt(a.to_textid())
 }
 
+#[macro_export]
 macro_rules! misc_txt_format {
     ($id:expr; $($target:ident = $value:expr),*) => {{
-        let mut table = fluent::FluentArgs::new();
+        let mut table = $crate::fluent::FluentArgs::new();
         $(
-            let value = fluent::FluentValue::String($value.to_text());
+            let value = &$value;
+            let value = $crate::fluent::FluentValue::String(value.to_text());
             table.add(stringify!($target), value);
         )*
 
-        crate::text::misc_txt_with_args($id, Some(&table))
+        $crate::text::misc_txt_with_args($id, Some(&table))
     }}
 }
 
+#[macro_export]
 macro_rules! ui_txt_format {
     ($id:expr; $($target:ident = $value:expr),*) => {{
-        let mut table = fluent::FluentArgs::new();
+        let mut table = $crate::fluent::FluentArgs::new();
         $(
-            let value = fluent::FluentValue::String($value.to_text());
+            let value = &$value;
+            let value = $crate::fluent::FluentValue::String(value.to_text());
             table.add(stringify!($target), value);
         )*
 
-        crate::text::ui_txt_with_args($id, Some(&table))
+        $crate::text::ui_txt_with_args($id, Some(&table))
     }}
 }
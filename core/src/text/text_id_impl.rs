@@ -63,6 +63,11 @@ impl ToTextId for SkillKind {
             Throwing => "skill_kind-throwing",
             MagicDevice => "skill_kind-magic_device",
             Mining => "skill_kind-mining",
+            Lockpicking => "skill_kind-lockpicking",
+            Searching => "skill_kind-searching",
+            Stealth => "skill_kind-stealth",
+            Trading => "skill_kind-trading",
+            Appraisal => "skill_kind-appraisal",
             Weapon(weapon_kind) => weapon_kind.to_textid(),
             Creation(creation_kind) => creation_kind.to_textid(),
         }
@@ -94,6 +99,58 @@ impl ToTextId for ArmorKind {
     }
 }
 
+impl ToTextId for Element {
+    fn to_textid(&self) -> &'static str {
+        use Element::*;
+        match self {
+            None => "element-none",
+            Physical => "element-physical",
+            Fire => "element-fire",
+            Cold => "element-cold",
+            Shock => "element-shock",
+            Poison => "element-poison",
+            Spirit => "element-spirit",
+        }
+    }
+}
+
+impl ToTextId for CharaAttrKind {
+    fn to_textid(&self) -> &'static str {
+        use CharaAttrKind::*;
+        match self {
+            Str => "chara_attr_kind-str",
+            Vit => "chara_attr_kind-vit",
+            Dex => "chara_attr_kind-dex",
+            Int => "chara_attr_kind-int",
+            Wil => "chara_attr_kind-wil",
+            Cha => "chara_attr_kind-cha",
+            Spd => "chara_attr_kind-spd",
+        }
+    }
+}
+
+impl ToTextId for StatusEffect {
+    fn to_textid(&self) -> &'static str {
+        use StatusEffect::*;
+        match self {
+            Asleep => "status_effect-asleep",
+            Poison => "status_effect-poison",
+            Scanned => "status_effect-scanned",
+        }
+    }
+}
+
+impl ToTextId for BlessState {
+    fn to_textid(&self) -> &'static str {
+        use BlessState::*;
+        match self {
+            Cursed => "bless_state-cursed",
+            Normal => "bless_state-normal",
+            Blessed => "bless_state-blessed",
+        }
+    }
+}
+
 impl ToTextId for CreationKind {
     fn to_textid(&self) -> &'static str {
         use CreationKind::*;
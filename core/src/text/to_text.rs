@@ -30,33 +30,66 @@ impl ToText for Site {
     }
 }
 
-impl ToText for Item {
+/// Item name as known by the player.
+/// Items of unidentified kinds are shown by their appearances.
+pub struct ItemName<'a> {
+    item: &'a Item,
+    knowledge: &'a ItemKnowledge,
+}
+
+impl<'a> ItemName<'a> {
+    pub fn new(gd: &'a GameData, item: &'a Item) -> ItemName<'a> {
+        ItemName {
+            item,
+            knowledge: &gd.item_knowledge,
+        }
+    }
+}
+
+impl<'a> ToText for ItemName<'a> {
     fn to_text(&self) -> Cow<str> {
+        use crate::game::item::identify;
         use crate::game::item::ItemEx;
-        let mut text: String = obj_txt(gobj::idx_to_id(self.idx)).into();
+        let item = self.item;
+        let appearance = if identify::is_name_known(self.knowledge, item) {
+            None
+        } else {
+            identify::appearance(self.knowledge, item.idx)
+        };
+        let mut text: String = if let Some(appearance) = appearance {
+            misc_txt(&format!("item_appearance-{}", appearance))
+        } else {
+            obj_txt(gobj::idx_to_id(item.idx))
+        };
+        let identified = identify::is_identified(item);
+
+        if identified && item.bless != BlessState::Normal {
+            text = format!("{} {}", item.bless.to_text(), text);
+        }
 
-        if let Some(n) = self.charge() {
+        if let Some(n) = item.charge().filter(|_| identified) {
             text.push_str(&format!(" ({} : {})", ui_txt("item-charges"), n));
         }
 
-        if let Some(title) = self.title() {
+        if let Some(title) = item.title() {
             if let Some(title) = super::readable::readable_title_txt(title) {
                 text.push_str(&format!(" <{}>", title));
             }
         }
 
-        if let Some((material_name, _)) = self.material() {
+        if let Some((material_name, _)) = item.material() {
             text.push_str(&format!(" ({})", super::prefix::material(material_name)))
         }
 
-        let quality = self.quality.as_int();
+        // Quality of unidentified items is unknown
+        let quality = if identified { item.quality.as_int() } else { 0 };
         if quality > 0 {
             text.push_str(&format!(" +{}", quality));
         } else if quality < 0 {
             text.push_str(&format!(" -{}", -quality));
         }
 
-        for attr in &self.attributes {
+        for attr in &item.attributes {
             match attr {
                 ItemAttribute::SkillLearning(kind) => {
                     text.push_str(&format!(" <{}>", kind.to_text()));
@@ -75,6 +108,12 @@ impl ToText for CharaTemplateIdx {
     }
 }
 
+impl ToText for ItemIdx {
+    fn to_text(&self) -> Cow<str> {
+        obj_txt(gobj::idx_to_id(*self)).into()
+    }
+}
+
 impl ToText for Chara {
     fn to_text(&self) -> Cow<str> {
         use crate::game::chara::CharaEx;
@@ -98,6 +137,7 @@ impl ToText for Command {
             Shoot { .. } => "command-shoot",
             UseTool { .. } => "command-use-tool",
             Enter => "command-enter",
+            Search => "command-search",
             Cancel => "command-cancel",
             RotateWindowRight => "command-rotate_window_right",
             RotateWindowLeft => "command-rotate_window_left",
@@ -108,6 +148,7 @@ impl ToText for Command {
             OpenExitWin => "command-open_exit_win",
             OpenGameInfoWin => "command-open_game_info_win",
             OpenHelpWin => "command-open_help_win",
+            OpenQuestWin => "command-open_quest_win",
             OpenStatusWin => "command-open_status_win",
             OpenItemMenu => "command-open_item_menu",
             PickUpItem => "command-pick_up_item",
@@ -131,11 +172,24 @@ impl ToText for Quest {
     fn to_text(&self) -> Cow<str> {
         match self {
             Quest::SlayMonsters { idx, .. } => {
-                let mut table = fluent::FluentArgs::new();
-                table.add("monster", fluent::FluentValue::String(idx.to_text()));
-                crate::text::misc_txt_with_args("quest-slay_monsters", Some(&table)).into()
+                crate::misc_txt_format!("quest-slay_monsters"; monster=idx)
             }
+            Quest::Deliver { idx, .. } => crate::misc_txt_format!("quest-deliver"; item=idx),
+            Quest::Fetch { idx, .. } => crate::misc_txt_format!("quest-fetch"; item=idx),
+            Quest::Escort { idx, .. } => crate::misc_txt_format!("quest-escort"; chara=idx),
+            Quest::ExploreFloor { floor, .. } => {
+                let floor = floor + 1;
+                crate::misc_txt_format!("quest-explore_floor"; floor=floor)
+            }
+            Quest::Craft { idx, .. } => crate::misc_txt_format!("quest-craft"; item=idx),
         }
+        .into()
+    }
+}
+
+impl ToText for StoryQuest {
+    fn to_text(&self) -> Cow<str> {
+        obj_txt(&self.id).into()
     }
 }
 
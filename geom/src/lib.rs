@@ -4,7 +4,9 @@ extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 
+mod path;
 mod shape;
+pub use path::*;
 pub use shape::*;
 
 use std::fmt;
@@ -14,7 +16,7 @@ use thiserror::Error;
 const OUT_OF_BOUNDS_ERR_MSG: &'static str = "Array2d: index out of bounds";
 
 /// Represents coordinates on a 2D array
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Vec2d(pub i32, pub i32);
 
 impl Vec2d {
@@ -0,0 +1,100 @@
+use crate::{Direction, Vec2d};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Cost of one step. Diagonal steps have the same cost as orthogonal steps.
+const STEP_COST: u32 = 10;
+
+/// Search the shortest path from start to goal by A* algorithm.
+///
+/// `cost` returns the additional cost to enter the given tile, or None if impassable.
+/// The search gives up when the number of expanded tiles exceeds `max_nodes`.
+/// The returned path does not include start, and its last element is goal.
+pub fn find_path<F>(start: Vec2d, goal: Vec2d, max_nodes: usize, mut cost: F) -> Option<Vec<Vec2d>>
+where
+    F: FnMut(Vec2d) -> Option<u32>,
+{
+    if start == goal {
+        return Some(Vec::new());
+    }
+
+    let heuristic = |p: Vec2d| {
+        let dx = (p.0 - goal.0).abs();
+        let dy = (p.1 - goal.1).abs();
+        std::cmp::max(dx, dy) as u32 * STEP_COST
+    };
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Vec2d, Vec2d> = HashMap::new();
+    let mut g_score: HashMap<Vec2d, u32> = HashMap::new();
+    let mut n_expanded = 0;
+
+    g_score.insert(start, 0);
+    open.push(Reverse((heuristic(start), 0, start)));
+
+    while let Some(Reverse((_, g, p))) = open.pop() {
+        if p == goal {
+            let mut path = vec![goal];
+            let mut p = goal;
+            while let Some(&prev) = came_from.get(&p) {
+                if prev == start {
+                    break;
+                }
+                path.push(prev);
+                p = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        if g > *g_score.get(&p).unwrap_or(&u32::MAX) {
+            continue;
+        }
+
+        n_expanded += 1;
+        if n_expanded > max_nodes {
+            return None;
+        }
+
+        for dir in &Direction::EIGHT_DIRS {
+            let next = p + dir.as_vec();
+            let c = if let Some(c) = cost(next) {
+                c
+            } else {
+                continue;
+            };
+            let next_g = g + STEP_COST + c;
+            if next_g < *g_score.get(&next).unwrap_or(&u32::MAX) {
+                g_score.insert(next, next_g);
+                came_from.insert(next, p);
+                open.push(Reverse((next_g + heuristic(next), next_g, next)));
+            }
+        }
+    }
+
+    None
+}
+
+#[test]
+fn find_path_test() {
+    // Wall at x = 2 except y = 4
+    let is_wall = |p: Vec2d| p.0 == 2 && p.1 != 4;
+    let in_range = |p: Vec2d| 0 <= p.0 && p.0 < 5 && 0 <= p.1 && p.1 < 5;
+    let cost = |p: Vec2d| {
+        if in_range(p) && !is_wall(p) {
+            Some(0)
+        } else {
+            None
+        }
+    };
+
+    let path = find_path(Vec2d(0, 0), Vec2d(4, 0), 100, cost).unwrap();
+    assert_eq!(*path.last().unwrap(), Vec2d(4, 0));
+    assert!(path.iter().all(|p| !is_wall(*p)));
+    assert!(path.contains(&Vec2d(2, 4)));
+    assert_eq!(path.len(), 8);
+
+    assert_eq!(find_path(Vec2d(0, 0), Vec2d(0, 0), 100, cost), Some(vec![]));
+    assert_eq!(find_path(Vec2d(0, 0), Vec2d(4, 0), 3, cost), None);
+    assert_eq!(find_path(Vec2d(0, 0), Vec2d(2, 0), 100, cost), None);
+}
@@ -47,6 +47,8 @@ pub fn convert_effect_input(e: Option<EffectInput>) -> Result<Option<Effect>> {
             "skill_learning" => EffectKind::SkillLearning {
                 skills: need_field!(k, skills),
             },
+            "identify" => EffectKind::Identify,
+            "remove_curse" => EffectKind::RemoveCurse,
             _ => bail!("unknown field \"{}\" for effect kind"),
         });
     }
@@ -1,11 +1,12 @@
+use common::gamedata::{FactionId, QuestState};
 use common::script::{Expr, Operator, Value};
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::{char, digit1, multispace0};
-use nom::combinator::complete;
+use nom::combinator::{complete, map_opt, map_res, value};
 use nom::multi::fold_many0;
 use nom::regexp::str::re_find;
-use nom::sequence::{delimited, pair};
+use nom::sequence::{delimited, pair, separated_pair};
 use regex::Regex;
 
 type IResult<I, O> = nom::IResult<I, O, nom::error::VerboseError<I>>;
@@ -44,6 +45,15 @@ pub fn symbol(input: &str) -> IResult<&str, &str> {
     Ok((input, s))
 }
 
+/// Symbol parsed by FromStr, e.g. skill kinds and attribute kinds
+pub fn from_symbol<T: std::str::FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(symbol, str::parse)(input)
+}
+
+pub fn faction_id(input: &str) -> IResult<&str, FactionId> {
+    map_opt(id, |s| FactionId::new(&s))(input)
+}
+
 #[test]
 fn id_test() {
     assert_eq!(id("ab.c"), Ok(("", "ab.c".to_string())));
@@ -76,6 +86,14 @@ fn gvar(input: &str) -> IResult<&str, Expr> {
     Ok((input, Expr::GVar(var_name)))
 }
 
+fn lvar(input: &str) -> IResult<&str, Expr> {
+    let (input, _) = char('@')(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, var_name) = symbol(input)?;
+    let (input, _) = char(')')(input)?;
+    Ok((input, Expr::LVar(var_name.to_owned())))
+}
+
 fn gvar_special(input: &str) -> IResult<&str, Expr> {
     let (input, _) = tag("$?")(input)?;
     Ok((input, Expr::GVar("?".to_owned())))
@@ -120,6 +138,89 @@ fn has_item(input: &str) -> IResult<&str, Expr> {
     Ok((input, Expr::HasItem(s)))
 }
 
+/// Parse `name(arg)`
+fn func<'a, O, F>(name: &'static str, f: F) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    let mut arg = delimited(
+        pair(char('('), multispace0),
+        f,
+        pair(multispace0, char(')')),
+    );
+    move |input: &'a str| {
+        let (input, _) = tag(name)(input)?;
+        let (input, _) = multispace0(input)?;
+        arg(input)
+    }
+}
+
+fn player_attr(input: &str) -> IResult<&str, Expr> {
+    let (input, kind) = func("player_attr", from_symbol)(input)?;
+    Ok((input, Expr::PlayerAttr(kind)))
+}
+
+fn skill_level(input: &str) -> IResult<&str, Expr> {
+    let (input, kind) = func("skill_level", from_symbol)(input)?;
+    Ok((input, Expr::SkillLevel(kind)))
+}
+
+fn faction_relation(input: &str) -> IResult<&str, Expr> {
+    let (input, faction) = func("faction_relation", faction_id)(input)?;
+    Ok((input, Expr::FactionRelation(faction)))
+}
+
+fn quest_state(input: &str) -> IResult<&str, QuestState> {
+    alt((
+        value(QuestState::Active, tag("active")),
+        value(QuestState::Completed, tag("completed")),
+        value(QuestState::RewardReceived, tag("reward_received")),
+        value(QuestState::Failed, tag("failed")),
+    ))(input)
+}
+
+fn quest_count(input: &str) -> IResult<&str, Expr> {
+    let (input, state) = func("quest_count", quest_state)(input)?;
+    Ok((input, Expr::QuestCount(state)))
+}
+
+/// Separator of function arguments
+fn comma(input: &str) -> IResult<&str, char> {
+    delimited(multispace0, char(','), multispace0)(input)
+}
+
+fn story_quest_stage(input: &str) -> IResult<&str, Expr> {
+    let args = separated_pair(id, comma, id);
+    let (input, (quest_id, stage_id)) = func("quest_stage", args)(input)?;
+    Ok((input, Expr::QuestStage(quest_id, stage_id)))
+}
+
+fn story_quest_state(input: &str) -> IResult<&str, Expr> {
+    let args = separated_pair(id, comma, quest_state);
+    let (input, (quest_id, state)) = func("quest_state", args)(input)?;
+    Ok((input, Expr::QuestState(quest_id, state)))
+}
+
+fn in_site(input: &str) -> IResult<&str, Expr> {
+    let (input, site_id) = func("in_site", id)(input)?;
+    Ok((input, Expr::InSite(site_id)))
+}
+
+fn current_floor(input: &str) -> IResult<&str, Expr> {
+    let (input, _) = func("current_floor", multispace0)(input)?;
+    Ok((input, Expr::CurrentFloor))
+}
+
+fn hp(input: &str) -> IResult<&str, Expr> {
+    let (input, chara) = func("hp", from_symbol)(input)?;
+    Ok((input, Expr::Hp(chara)))
+}
+
+fn max_hp(input: &str) -> IResult<&str, Expr> {
+    let (input, chara) = func("max_hp", from_symbol)(input)?;
+    Ok((input, Expr::MaxHp(chara)))
+}
+
 fn factor(input: &str) -> IResult<&str, Expr> {
     let (input, _) = multispace0(input)?;
     let (input, expr) = alt((
@@ -128,10 +229,21 @@ fn factor(input: &str) -> IResult<&str, Expr> {
         complete(integer),
         complete(gvar),
         complete(gvar_special),
+        complete(lvar),
         complete(is_gvar_empty),
         complete(current_time),
         complete(duration_hours),
         complete(has_item),
+        complete(player_attr),
+        complete(skill_level),
+        complete(faction_relation),
+        complete(quest_count),
+        complete(story_quest_stage),
+        complete(story_quest_state),
+        complete(in_site),
+        complete(current_floor),
+        complete(hp),
+        complete(max_hp),
         complete(parens),
     ))(input)?;
     let (input, _) = multispace0(input)?;
@@ -262,16 +374,56 @@ pub fn expr(input: &str) -> IResult<&str, Expr> {
 
 #[test]
 fn expr_test() {
+    use common::gamedata::{CharaAttrKind, SkillKind};
+    use common::script::CharaRef;
     assert_eq!(expr("true"), Ok(("", Expr::Value(Value::Bool(true)))));
     assert_eq!(expr("false"), Ok(("", Expr::Value(Value::Bool(false)))));
     assert_eq!(expr("1234"), Ok(("", Expr::Value(Value::Int(1234)))));
     assert_eq!(expr("$(aa)"), Ok(("", Expr::GVar("aa".to_owned()))));
+    assert_eq!(expr("@(aa)"), Ok(("", Expr::LVar("aa".to_owned()))));
     assert_eq!(
         expr("is_gvar_empty(bb)"),
         Ok(("", Expr::IsGVarEmpty("bb".to_owned())))
     );
     let a = Expr::HasItem("box".to_owned());
     assert_eq!(expr("has_item(box)"), Ok(("", a)));
+    assert_eq!(
+        expr("player_attr(str)"),
+        Ok(("", Expr::PlayerAttr(CharaAttrKind::Str)))
+    );
+    assert_eq!(
+        expr("skill_level( stealth )"),
+        Ok(("", Expr::SkillLevel(SkillKind::Stealth)))
+    );
+    assert_eq!(
+        expr("faction_relation(citizen)"),
+        Ok((
+            "",
+            Expr::FactionRelation(FactionId::new("citizen").unwrap())
+        ))
+    );
+    assert_eq!(
+        expr("quest_count(reward_received)"),
+        Ok(("", Expr::QuestCount(QuestState::RewardReceived)))
+    );
+    assert_eq!(
+        expr("quest_stage(main, cave)"),
+        Ok(("", Expr::QuestStage("main".to_owned(), "cave".to_owned())))
+    );
+    assert_eq!(
+        expr("quest_state( main , completed )"),
+        Ok((
+            "",
+            Expr::QuestState("main".to_owned(), QuestState::Completed)
+        ))
+    );
+    assert_eq!(expr("current_floor()"), Ok(("", Expr::CurrentFloor)));
+    assert_eq!(expr("hp(talker)"), Ok(("", Expr::Hp(CharaRef::Talker))));
+    assert_eq!(
+        expr("max_hp(player)"),
+        Ok(("", Expr::MaxHp(CharaRef::Player)))
+    );
+    assert!(expr("player_attr(foo)").is_err());
     assert_eq!(
         expr("1 * 2 + 3"),
         Ok((
@@ -51,6 +51,7 @@ pub fn build_item_object(tomlinput: Input) -> Result<ItemObject, Error> {
         medical_effect: convert_effect_input(item.medical_effect)?,
         use_effect: convert_effect_input(item.use_effect)?,
         tool_effect: item.tool_effect,
+        light_radius: item.light_radius.unwrap_or(0),
         nutrition: item.nutrition.unwrap_or(0),
         charge: item.charge,
         harvest: item.harvest,
@@ -9,10 +9,10 @@ use self::item::build_item_object;
 use crate::input::Input;
 use anyhow::*;
 use common::gamedata::{CharaBaseAttr, SkillBonus, SkillKind};
+use common::hashmap::HashMap;
 use common::obj::*;
 use geom::Vec2d;
-pub use script_parser::parse as script_parse;
-use std::collections::HashMap;
+pub use script_parser::{parse as script_parse, parse_with_lines, ScriptLines};
 
 pub fn build_object(tomlinput: Input) -> Result<Object, Error> {
     let object_type = tomlinput.object_type.clone();
@@ -53,6 +53,9 @@ pub fn build_object(tomlinput: Input) -> Result<Object, Error> {
         "site_gen" => {
             return build_site_gen_object(tomlinput).map(|o| Object::SiteGen(o));
         }
+        "story_quest" => {
+            return build_story_quest_object(tomlinput).map(|o| Object::StoryQuest(o));
+        }
         _ => {
             bail!("Unknown object_type");
         }
@@ -79,16 +82,26 @@ fn build_effect_object(tomlinput: Input) -> Result<EffectImgObject, Error> {
 
 fn build_special_tile_object(tomlinput: Input) -> Result<SpecialTileObject, Error> {
     let img = get_optional_field!(tomlinput, image);
-    let always_background = if let Some(special_tile) = tomlinput.special_tile {
-        special_tile.always_background.unwrap_or(false)
+    let (always_background, trap) = if let Some(special_tile) = tomlinput.special_tile {
+        let trap = if let Some(trap) = special_tile.trap {
+            Some(TrapParams {
+                effect: effect::convert_effect_input(Some(trap.effect))?.unwrap(),
+                power: trap.power,
+                difficulty: trap.difficulty,
+            })
+        } else {
+            None
+        };
+        (special_tile.always_background.unwrap_or(false), trap)
     } else {
-        false
+        (false, None)
     };
 
     Ok(SpecialTileObject {
         id: tomlinput.id,
         always_background,
         img: build_img(img)?.0,
+        trap,
     })
 }
 
@@ -173,6 +186,7 @@ fn build_chara_template_object(tomlinput: Input) -> Result<CharaTemplateObject,
         default_ai_kind: chara_dep_input.default_ai_kind.unwrap_or_default(),
         skill_bonus,
         base_attr,
+        hooks: chara_dep_input.hooks,
     })
 }
 
@@ -222,5 +236,28 @@ fn build_site_gen_object(tomlinput: Input) -> Result<SiteGenObject, Error> {
         map_template_id: sg.map_template_id,
         unique_citizens: sg.unique_citizens.unwrap_or(vec![]),
         shops: sg.shops.unwrap_or(vec![]),
+        hooks: sg.hooks,
+    })
+}
+
+fn build_story_quest_object(tomlinput: Input) -> Result<StoryQuestObject, Error> {
+    let sq = get_optional_field!(tomlinput, story_quest);
+
+    if sq.stages.is_empty() {
+        bail!("story quest \"{}\" has no stages", tomlinput.id);
+    }
+    for (i, stage) in sq.stages.iter().enumerate() {
+        if sq.stages[..i].iter().any(|s| s.id == stage.id) {
+            bail!(
+                "story quest \"{}\" has duplicate stage \"{}\"",
+                tomlinput.id,
+                stage.id
+            );
+        }
+    }
+
+    Ok(StoryQuestObject {
+        id: tomlinput.id,
+        stages: sq.stages,
     })
 }
@@ -1,10 +1,11 @@
 use common::hashmap::HashMap;
 use nom::branch::alt;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{tag, take_till1};
 use nom::character::complete::{char, line_ending, multispace0, space0};
-use nom::combinator::{cut, map_res};
-use nom::multi::{fold_many0, many1, separated_list0};
-use nom::sequence::{delimited, separated_pair};
+use nom::combinator::{cut, map, map_res, opt};
+use nom::error::context;
+use nom::multi::{many0, many1, separated_list0};
+use nom::sequence::{delimited, preceded, separated_pair};
 use std::str::FromStr;
 
 use super::expr_parser::*;
@@ -56,19 +57,32 @@ where
     ))
 }
 
-fn section_start(input: &str) -> IResult<&str, String> {
+/// Section name and its parameters
+fn section_start(input: &str) -> IResult<&str, (String, Vec<String>)> {
     let (input, _) = tag("---")(input)?;
     let (input, _) = space0(input)?;
     let (input, s) = id(input)?;
+    let (input, params) = opt(delimited(
+        char('('),
+        separated_list0(char(','), ws(map(symbol, str::to_owned))),
+        char(')'),
+    ))(input)?;
     let (input, _) = end_line(input)?;
-    Ok((input, s))
+    Ok((input, (s, params.unwrap_or_default())))
 }
 
 #[test]
 fn section_start_test() {
     assert_eq!(
         section_start("---  section_name \n"),
-        Ok(("", "section_name".to_string()))
+        Ok(("", ("section_name".to_string(), vec![])))
+    );
+    assert_eq!(
+        section_start("--- sub(a, b)\n"),
+        Ok((
+            "",
+            ("sub".to_string(), vec!["a".to_owned(), "b".to_owned()])
+        ))
     );
 }
 
@@ -107,10 +121,13 @@ fn jump_instruction_test() {
 
 fn special_instruction(input: &str) -> IResult<&str, Instruction> {
     let (input, _) = ws(tag("special"))(input)?;
-    let (input, s) = map_res(
-        delimited(char('('), ws(symbol), char(')')),
-        FromStr::from_str,
-    )(input)?;
+    let (input, s) = cut(context(
+        "unknown special instruction",
+        map_res(
+            delimited(char('('), ws(symbol), char(')')),
+            FromStr::from_str,
+        ),
+    ))(input)?;
     let (input, _) = end_line(input)?;
     Ok((input, Instruction::Special(s)))
 }
@@ -125,6 +142,10 @@ fn special_instruction_test() {
         special_instruction("special(shop_sell)\n"),
         Ok(("", Instruction::Special(SpecialInstruction::ShopSell)))
     );
+    assert!(matches!(
+        special_instruction("special(shop_steal)\n"),
+        Err(nom::Err::Failure(_))
+    ));
 }
 
 fn print_instruction(input: &str) -> IResult<&str, Instruction> {
@@ -166,6 +187,60 @@ fn gset_instruction(input: &str) -> IResult<&str, Instruction> {
     Ok((input, Instruction::GSet(var_name, value)))
 }
 
+fn set_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("set"))(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, var_name) = ws(symbol)(input)?;
+    let (input, _) = char(',')(input)?;
+    let (input, value) = ws(expr)(input)?;
+    let (input, _) = char(')')(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::Set(var_name.to_owned(), value)))
+}
+
+fn call_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("call"))(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, section) = ws(id)(input)?;
+    let (input, args) = many0(preceded(char(','), ws(expr)))(input)?;
+    let (input, _) = char(')')(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::Call(section, args)))
+}
+
+fn return_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("return")(input)?;
+    let (input, value) = opt(delimited(char('('), ws(expr), char(')')))(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::Return(value)))
+}
+
+#[test]
+fn call_instruction_test() {
+    assert_eq!(
+        call_instruction("call(sub, 1, @(a))\n"),
+        Ok((
+            "",
+            Instruction::Call(
+                "sub".to_owned(),
+                vec![Expr::Value(Value::Int(1)), Expr::LVar("a".to_owned())]
+            )
+        ))
+    );
+    assert_eq!(
+        return_instruction("return\n"),
+        Ok(("", Instruction::Return(None)))
+    );
+    assert_eq!(
+        set_instruction("set(n, 3)\n"),
+        Ok((
+            "",
+            Instruction::Set("n".to_owned(), Expr::Value(Value::Int(3)))
+        ))
+    );
+}
+
 fn receive_item_instruction(input: &str) -> IResult<&str, Instruction> {
     let (input, _) = ws(tag("receive_item"))(input)?;
     let (input, _) = char('(')(input)?;
@@ -191,6 +266,213 @@ fn remove_item_instruction(input: &str) -> IResult<&str, Instruction> {
     Ok((input, Instruction::RemoveItem(item_id)))
 }
 
+fn change_faction_relation_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("change_faction_relation"))(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, faction) = ws(faction_id)(input)?;
+    let (input, _) = char(',')(input)?;
+    let (input, value) = ws(expr)(input)?;
+    let (input, _) = char(')')(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::ChangeFactionRelation(faction, value)))
+}
+
+fn learn_skill_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("learn_skill"))(input)?;
+    let (input, kind) = delimited(char('('), ws(from_symbol), char(')'))(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::LearnSkill(kind)))
+}
+
+fn gain_skill_exp_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("gain_skill_exp"))(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, kind) = ws(from_symbol)(input)?;
+    let (input, _) = char(',')(input)?;
+    let (input, value) = ws(expr)(input)?;
+    let (input, _) = char(')')(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::GainSkillExp(kind, value)))
+}
+
+fn add_attr_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("add_attr"))(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, kind) = ws(from_symbol)(input)?;
+    let (input, _) = char(',')(input)?;
+    let (input, value) = ws(expr)(input)?;
+    let (input, _) = char(')')(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::AddAttr(kind, value)))
+}
+
+fn add_hp_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("add_hp"))(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, chara) = ws(from_symbol)(input)?;
+    let (input, _) = char(',')(input)?;
+    let (input, value) = ws(expr)(input)?;
+    let (input, _) = char(')')(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::AddHp(chara, value)))
+}
+
+/// Instructions changing the player's state and relations
+fn game_state_instruction(input: &str) -> IResult<&str, Instruction> {
+    alt((
+        change_faction_relation_instruction,
+        learn_skill_instruction,
+        gain_skill_exp_instruction,
+        add_attr_instruction,
+        add_hp_instruction,
+    ))(input)
+}
+
+fn spawn_chara_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("spawn_chara"))(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, chara_id) = ws(id)(input)?;
+    let (input, _) = char(',')(input)?;
+    let (input, faction) = ws(faction_id)(input)?;
+    let (input, _) = char(')')(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::SpawnChara(chara_id, faction)))
+}
+
+/// Map path like "region/site/floor:x,y"
+fn map_path(input: &str) -> IResult<&str, String> {
+    let (input, path) = take_till1(|c: char| c == ')' || c.is_whitespace())(input)?;
+    Ok((input, path.to_owned()))
+}
+
+fn teleport_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("teleport"))(input)?;
+    let (input, path) = delimited(char('('), ws(map_path), char(')'))(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::Teleport(path)))
+}
+
+fn start_combat_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("start_combat"))(input)?;
+    let (input, faction) = delimited(char('('), ws(faction_id), char(')'))(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::StartCombat(faction)))
+}
+
+fn set_quest_stage_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("set_quest_stage"))(input)?;
+    let (input, _) = char('(')(input)?;
+    let (input, quest_id) = ws(id)(input)?;
+    let (input, _) = char(',')(input)?;
+    let (input, stage_id) = ws(id)(input)?;
+    let (input, _) = char(')')(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::SetQuestStage(quest_id, stage_id)))
+}
+
+fn complete_quest_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("complete_quest"))(input)?;
+    let (input, quest_id) = delimited(char('('), ws(id), char(')'))(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::CompleteQuest(quest_id)))
+}
+
+fn fail_quest_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = ws(tag("fail_quest"))(input)?;
+    let (input, quest_id) = delimited(char('('), ws(id), char(')'))(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::FailQuest(quest_id)))
+}
+
+fn fail_quests_instruction(input: &str) -> IResult<&str, Instruction> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("fail_quests")(input)?;
+    let (input, _) = end_line(input)?;
+    Ok((input, Instruction::FailQuests))
+}
+
+/// Instructions for quests
+fn quest_instruction(input: &str) -> IResult<&str, Instruction> {
+    alt((
+        set_quest_stage_instruction,
+        complete_quest_instruction,
+        fail_quests_instruction,
+        fail_quest_instruction,
+    ))(input)
+}
+
+#[test]
+fn quest_instruction_test() {
+    assert_eq!(
+        quest_instruction("set_quest_stage(main-story, find-elder)\n"),
+        Ok((
+            "",
+            Instruction::SetQuestStage("main-story".to_owned(), "find-elder".to_owned())
+        ))
+    );
+    assert_eq!(
+        quest_instruction("complete_quest(main-story)\n"),
+        Ok(("", Instruction::CompleteQuest("main-story".to_owned())))
+    );
+    assert_eq!(
+        quest_instruction("fail_quest( main-story )\n"),
+        Ok(("", Instruction::FailQuest("main-story".to_owned())))
+    );
+    assert!(quest_instruction("set_quest_stage(main-story)\n").is_err());
+    assert_eq!(
+        quest_instruction("fail_quests\n"),
+        Ok(("", Instruction::FailQuests))
+    );
+}
+
+#[test]
+fn game_state_instruction_test() {
+    use common::gamedata::{CharaAttrKind, FactionId, SkillKind};
+    use common::script::CharaRef;
+    let monster = FactionId::new("monster").unwrap();
+    assert_eq!(
+        change_faction_relation_instruction("change_faction_relation(monster, 10)\n"),
+        Ok((
+            "",
+            Instruction::ChangeFactionRelation(monster, Expr::Value(Value::Int(10)))
+        ))
+    );
+    assert_eq!(
+        add_attr_instruction("add_attr(str, 1)\n"),
+        Ok((
+            "",
+            Instruction::AddAttr(CharaAttrKind::Str, Expr::Value(Value::Int(1)))
+        ))
+    );
+    assert_eq!(
+        learn_skill_instruction("learn_skill(stealth)\n"),
+        Ok(("", Instruction::LearnSkill(SkillKind::Stealth)))
+    );
+    assert!(add_attr_instruction("add_attr(foo, 1)\n").is_err());
+    assert_eq!(
+        game_state_instruction("add_hp(talker, 10)\n"),
+        Ok((
+            "",
+            Instruction::AddHp(CharaRef::Talker, Expr::Value(Value::Int(10)))
+        ))
+    );
+    assert_eq!(
+        spawn_chara_instruction("spawn_chara(rat, monster)\n"),
+        Ok(("", Instruction::SpawnChara("rat".to_owned(), monster)))
+    );
+    assert_eq!(
+        teleport_instruction("teleport(east-coast/!home/0:10,5)\n"),
+        Ok((
+            "",
+            Instruction::Teleport("east-coast/!home/0:10,5".to_owned())
+        ))
+    );
+    assert_eq!(
+        start_combat_instruction("start_combat(monster)\n"),
+        Ok(("", Instruction::StartCombat(monster)))
+    );
+}
+
 #[test]
 fn talk_instruction_test() {
     let result = Instruction::Talk(
@@ -213,50 +495,233 @@ fn instruction(input: &str) -> IResult<&str, Instruction> {
         talk_instruction_with_choices,
         talk_instruction,
         gset_instruction,
+        set_instruction,
+        call_instruction,
+        return_instruction,
         receive_item_instruction,
         receive_money_instruction,
         remove_item_instruction,
+        game_state_instruction,
+        spawn_chara_instruction,
+        teleport_instruction,
+        start_combat_instruction,
+        quest_instruction,
         special_instruction,
         print_instruction,
     ))(input)
 }
 
-fn section(input: &str) -> IResult<&str, (String, Vec<Instruction>)> {
-    let (input, section) = section_start(input)?;
-    let (input, instructions) = cut(many1(instruction))(input)?;
-    Ok((input, (section.to_owned(), instructions)))
+/// Instruction or block including instructions.
+/// Positions are the length of the remaining input, and converted to line numbers later.
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Statement {
+    Instruction(Instruction, usize),
+    While(Expr, Vec<Statement>, usize, usize),
+}
+
+fn while_block(input: &str) -> IResult<&str, Statement> {
+    let pos = input.len();
+    let (input, _) = ws(tag("while"))(input)?;
+    let (input, cond) = delimited(char('('), ws(expr), char(')'))(input)?;
+    let (input, _) = end_line(input)?;
+    let (input, body) = cut(many0(statement))(input)?;
+    let (input, _) = multispace0(input)?;
+    let end_pos = input.len();
+    let (input, _) = cut(ws(tag("end")))(input)?;
+    Ok((input, Statement::While(cond, body, pos, end_pos)))
+}
+
+fn statement(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = multispace0(input)?;
+    let pos = input.len();
+    alt((
+        while_block,
+        map(instruction, move |instruction| {
+            Statement::Instruction(instruction, pos)
+        }),
+    ))(input)
+}
+
+/// Convert blocks to instructions with jumps in the section
+fn flatten(
+    statements: Vec<Statement>,
+    instructions: &mut Vec<Instruction>,
+    positions: &mut Vec<usize>,
+) {
+    for statement in statements {
+        match statement {
+            Statement::Instruction(instruction, pos) => {
+                instructions.push(instruction);
+                positions.push(pos);
+            }
+            Statement::While(cond, body, pos, end_pos) => {
+                let start = instructions.len();
+                instructions.push(Instruction::While(cond, 0));
+                positions.push(pos);
+                flatten(body, instructions, positions);
+                instructions.push(Instruction::EndWhile(start));
+                positions.push(end_pos);
+                let end = instructions.len();
+                instructions[start] = match instructions[start].clone() {
+                    Instruction::While(cond, _) => Instruction::While(cond, end),
+                    _ => unreachable!(),
+                };
+            }
+        }
+    }
+}
+
+#[test]
+fn while_block_test() {
+    let (_, statements) =
+        many1(statement)("set(i, 0)\nwhile(@(i) < 3)\n  set(i, @(i) + 1)\nend\nprint(@(i))")
+            .unwrap();
+    let mut instructions = Vec::new();
+    flatten(statements, &mut instructions, &mut Vec::new());
+    let i = || Expr::LVar("i".to_owned());
+    assert_eq!(
+        instructions,
+        vec![
+            Instruction::Set("i".to_owned(), Expr::Value(Value::Int(0))),
+            Instruction::While(
+                Expr::Term(vec![
+                    (Operator::None, i()),
+                    (Operator::Less, Expr::Value(Value::Int(3)))
+                ]),
+                4
+            ),
+            Instruction::Set(
+                "i".to_owned(),
+                Expr::Term(vec![
+                    (Operator::None, i()),
+                    (Operator::Add, Expr::Value(Value::Int(1)))
+                ])
+            ),
+            Instruction::EndWhile(1),
+            Instruction::Print(i()),
+        ]
+    );
+}
+
+/// Parsed section
+struct Section {
+    name: String,
+    params: Vec<String>,
+    instructions: Vec<Instruction>,
+    pos: usize,
+    positions: Vec<usize>,
 }
 
-fn sections(input: &str) -> IResult<&str, HashMap<String, Vec<Instruction>>> {
-    let (input, sections) = fold_many0(
-        section,
-        HashMap::default(),
-        |mut s: HashMap<String, Vec<Instruction>>, section: (String, Vec<Instruction>)| {
-            s.insert(section.0, section.1);
-            s
+fn section(input: &str) -> IResult<&str, Section> {
+    let pos = input.len();
+    let (input, (name, params)) = section_start(input)?;
+    let (input, statements) = cut(many1(statement))(input)?;
+    let mut instructions = Vec::new();
+    let mut positions = Vec::new();
+    flatten(statements, &mut instructions, &mut positions);
+    Ok((
+        input,
+        Section {
+            name,
+            params,
+            instructions,
+            pos,
+            positions,
         },
-    )(input)?;
+    ))
+}
+
+fn sections(input: &str) -> IResult<&str, Vec<Section>> {
+    let (input, sections) = many0(section)(input)?;
     let (input, _) = multispace0(input)?;
     let (input, _) = nom::combinator::eof(input)?;
     Ok((input, sections))
 }
 
+/// Line numbers of sections and instructions in the script source
+#[derive(Clone, Default, Debug)]
+pub struct ScriptLines {
+    sections: HashMap<String, (usize, Vec<usize>)>,
+}
+
+impl ScriptLines {
+    /// Line number of the section header
+    pub fn section(&self, section: &str) -> usize {
+        self.sections.get(section).map(|s| s.0).unwrap_or(0)
+    }
+
+    /// Line number of the i-th instruction in the section
+    pub fn instruction(&self, section: &str, i: usize) -> usize {
+        self.sections
+            .get(section)
+            .and_then(|s| s.1.get(i).copied())
+            .unwrap_or(0)
+    }
+}
+
+fn into_script(input: &str, sections: Vec<Section>) -> (Script, ScriptLines) {
+    // Convert the length of remaining input to line number
+    let line = |pos: usize| input[..input.len() - pos].matches('\n').count() + 1;
+
+    let mut map = HashMap::default();
+    let mut params_list = Vec::new();
+    let mut lines = ScriptLines::default();
+    for section in sections {
+        if !section.params.is_empty() {
+            params_list.push((section.name.clone(), section.params));
+        }
+        lines.sections.insert(
+            section.name.clone(),
+            (
+                line(section.pos),
+                section.positions.into_iter().map(line).collect(),
+            ),
+        );
+        map.insert(section.name, section.instructions);
+    }
+    let mut script = Script::from_map(map);
+    for (section, params) in params_list {
+        script.set_params(&section, params);
+    }
+    (script, lines)
+}
+
 pub fn parse(input: &str) -> Result<Script, PakCompileError> {
+    parse_with_lines(input).map(|(script, _)| script)
+}
+
+/// Parse script, and returns line numbers of sections and instructions together
+pub fn parse_with_lines(input: &str) -> Result<(Script, ScriptLines), PakCompileError> {
     match sections(input) {
-        Ok(o) => Ok(Script::from_map(o.1)),
+        Ok(o) => Ok(into_script(input, o.1)),
         Err(e) => {
             use nom::Err;
-            let description = match e {
-                Err::Incomplete(_) => e.to_string(),
-                Err::Error(e) => nom::error::convert_error(input, e),
-                Err::Failure(e) => nom::error::convert_error(input, e),
+            let (pos, description) = match e {
+                Err::Incomplete(_) => (input.len(), e.to_string()),
+                Err::Error(e) | Err::Failure(e) => {
+                    let pos = e
+                        .errors
+                        .first()
+                        .map_or(0, |(rest, _)| input.len() - rest.len());
+                    (pos, nom::error::convert_error(input, e))
+                }
             };
+            let line = input[..pos].matches('\n').count() + 1;
 
-            Err(PakCompileError::ScriptParseError { description })
+            Err(PakCompileError::ScriptParseError { line, description })
         }
     }
 }
 
+#[test]
+fn parse_error_line_test() {
+    let script = "--- start\ntalk(textid0)\nspecial(shop_steal)\n";
+    assert!(matches!(
+        parse(script),
+        Err(PakCompileError::ScriptParseError { line: 3, .. })
+    ));
+}
+
 #[test]
 fn parse_test() {
     let script = r#"--- test_section0
@@ -288,5 +753,9 @@ talk(textid1,
         )],
     );
 
-    assert_eq!(sections(script), Ok(("", result)))
+    let (script, lines) = parse_with_lines(script).unwrap();
+    assert_eq!(script, Script::from_map(result));
+    assert_eq!(lines.section("test_section1"), 5);
+    assert_eq!(lines.instruction("test_section0", 2), 4);
+    assert_eq!(lines.instruction("test_section1", 0), 6);
 }
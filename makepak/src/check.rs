@@ -0,0 +1,401 @@
+//! Static checks for scripts.
+//! Scripts are checked after all objects are collected, because they refer other objects.
+
+use crate::buildobj::ScriptLines;
+use common::obj::Object;
+use common::script::*;
+use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Source file of a script to report errors
+pub struct ScriptSource {
+    pub path: PathBuf,
+    /// The number of lines before the script text in the file
+    pub line_offset: usize,
+    pub lines: ScriptLines,
+}
+
+/// Ids that scripts can refer
+#[derive(Default)]
+pub struct KnownIds {
+    items: HashSet<String>,
+    charas: HashSet<String>,
+    /// Story quest ids and their stage ids
+    story_quests: HashMap<String, Vec<String>>,
+    /// Fluent message ids. Text ids are not checked if None.
+    texts: Option<HashSet<String>>,
+    /// Objects of other paks are loaded as references.
+    /// If false, unknown object ids are warned instead of errors,
+    /// because they may be defined in other paks.
+    refs_loaded: bool,
+}
+
+impl KnownIds {
+    pub fn add_object(&mut self, obj: &Object) {
+        match obj {
+            Object::Item(o) => {
+                self.items.insert(o.id.clone());
+            }
+            Object::CharaTemplate(o) => {
+                self.charas.insert(o.id.clone());
+            }
+            Object::StoryQuest(o) => {
+                let stages = o.stages.iter().map(|stage| stage.id.clone()).collect();
+                self.story_quests.insert(o.id.clone(), stages);
+            }
+            _ => (),
+        }
+    }
+
+    /// Returns error message if the story quest or the stage is unknown
+    fn check_story_quest(&self, quest_id: &str, stage_id: Option<&str>) -> Option<String> {
+        let stages = if let Some(stages) = self.story_quests.get(quest_id) {
+            stages
+        } else {
+            return Some(format!("unknown story quest id \"{}\"", quest_id));
+        };
+        match stage_id {
+            Some(stage_id) if !stages.iter().any(|s| s == stage_id) => Some(format!(
+                "unknown stage \"{}\" of story quest \"{}\"",
+                stage_id, quest_id
+            )),
+            _ => None,
+        }
+    }
+
+    pub fn set_refs_loaded(&mut self) {
+        self.refs_loaded = true;
+    }
+
+    /// Load message ids from fluent files in the directory recursively
+    pub fn load_texts(&mut self, dir: &Path) -> std::io::Result<()> {
+        load_text_ids(dir, self.texts.get_or_insert_with(HashSet::new))
+    }
+}
+
+fn load_text_ids(dir: &Path, texts: &mut HashSet<String>) -> std::io::Result<()> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new("^([a-zA-Z][a-zA-Z0-9_-]*)\\s*=").unwrap();
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            load_text_ids(&path, texts)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("ftl") {
+            for line in std::fs::read_to_string(&path)?.lines() {
+                if let Some(caps) = RE.captures(line) {
+                    texts.insert(caps[1].to_owned());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Error or warning found in a script
+pub struct CheckError {
+    path: PathBuf,
+    line: usize,
+    msg: String,
+    warning: bool,
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.path.display(),
+            self.line,
+            if self.warning { "warning" } else { "error" },
+            self.msg
+        )
+    }
+}
+
+/// Check all scripts in objects, and print errors and warnings.
+/// Returns false if errors are found.
+pub fn check_scripts(objs: &[(Object, Option<ScriptSource>)], ids: &KnownIds) -> bool {
+    let mut ok = true;
+    for (obj, source) in objs {
+        let (script_obj, source) = match (obj, source) {
+            (Object::Script(o), Some(source)) => (o, source),
+            _ => continue,
+        };
+        for e in check_script(&script_obj.script, source, ids) {
+            if !e.warning {
+                ok = false;
+            }
+            eprintln!("{}", e);
+        }
+    }
+    ok
+}
+
+pub fn check_script(script: &Script, source: &ScriptSource, ids: &KnownIds) -> Vec<CheckError> {
+    let mut errors = Vec::new();
+    let mut push = |line: usize, msg: String, warning: bool| {
+        errors.push(CheckError {
+            path: source.path.clone(),
+            line: source.line_offset + line,
+            msg,
+            warning,
+        });
+    };
+
+    if !script.has_section("start") {
+        push(1, "no start section".to_owned(), false);
+    }
+
+    for (section, instructions) in script.iter_sections() {
+        for (i, instruction) in instructions.iter().enumerate() {
+            let line = source.lines.instruction(section, i);
+            let mut error = |msg: String, warning: bool| push(line, msg, warning);
+            let unknown_obj_warning = !ids.refs_loaded;
+
+            for dest in destinations(instruction) {
+                if dest != QUIT_SECTION && dest != CONTINUE_SECTION && !script.has_section(dest) {
+                    error(format!("unknown section \"{}\"", dest), false);
+                }
+            }
+
+            match instruction {
+                Instruction::Call(dest, args) if script.has_section(dest) => {
+                    let n_params = script.params(dest).len();
+                    if n_params != args.len() {
+                        error(
+                            format!(
+                                "\"{}\" needs {} arguments, but given {}",
+                                dest,
+                                n_params,
+                                args.len()
+                            ),
+                            false,
+                        );
+                    }
+                }
+                Instruction::Talk(text_id, choices) => {
+                    if let Some(texts) = ids.texts.as_ref() {
+                        let text_ids = std::iter::once(text_id).chain(choices.iter().map(|c| &c.0));
+                        for text_id in text_ids {
+                            if !texts.contains(text_id) {
+                                error(format!("unknown text id \"{}\"", text_id), false);
+                            }
+                        }
+                    }
+                }
+                Instruction::ReceiveItem(id, _) | Instruction::RemoveItem(id)
+                    if !ids.items.contains(id) =>
+                {
+                    error(format!("unknown item id \"{}\"", id), unknown_obj_warning);
+                }
+                Instruction::SpawnChara(id, _) if !ids.charas.contains(id) => {
+                    error(
+                        format!("unknown character template id \"{}\"", id),
+                        unknown_obj_warning,
+                    );
+                }
+                Instruction::SetQuestStage(quest_id, stage_id) => {
+                    if let Some(msg) = ids.check_story_quest(quest_id, Some(stage_id)) {
+                        error(msg, unknown_obj_warning);
+                    }
+                }
+                Instruction::CompleteQuest(quest_id) | Instruction::FailQuest(quest_id) => {
+                    if let Some(msg) = ids.check_story_quest(quest_id, None) {
+                        error(msg, unknown_obj_warning);
+                    }
+                }
+                _ => (),
+            }
+
+            let mut sub_exprs = Vec::new();
+            for expr in exprs(instruction) {
+                flatten_expr(expr, &mut sub_exprs);
+            }
+            for expr in sub_exprs {
+                match expr {
+                    Expr::HasItem(id) if !ids.items.contains(id) => {
+                        error(format!("unknown item id \"{}\"", id), unknown_obj_warning);
+                    }
+                    Expr::QuestStage(quest_id, stage_id) => {
+                        if let Some(msg) = ids.check_story_quest(quest_id, Some(stage_id)) {
+                            error(msg, unknown_obj_warning);
+                        }
+                    }
+                    Expr::QuestState(quest_id, _) => {
+                        if let Some(msg) = ids.check_story_quest(quest_id, None) {
+                            error(msg, unknown_obj_warning);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    for section in unreachable_sections(script) {
+        push(
+            source.lines.section(section),
+            format!("unreachable section \"{}\"", section),
+            true,
+        );
+    }
+
+    errors.sort_by_key(|e| e.line);
+    errors
+}
+
+/// Sections the instruction may move to
+fn destinations(instruction: &Instruction) -> Vec<&str> {
+    match instruction {
+        Instruction::Jump(dest) | Instruction::JumpIf(dest, _) | Instruction::Call(dest, _) => {
+            vec![dest]
+        }
+        Instruction::Talk(_, choices) => choices.iter().map(|c| c.1.as_str()).collect(),
+        _ => vec![],
+    }
+}
+
+/// Sections not reachable from the start section
+fn unreachable_sections(script: &Script) -> Vec<&str> {
+    let mut reachable = HashSet::new();
+    let mut queue = VecDeque::new();
+    if script.has_section("start") {
+        queue.push_back("start");
+    }
+    while let Some(section) = queue.pop_front() {
+        if !reachable.insert(section) {
+            continue;
+        }
+        for instruction in script.section(section) {
+            for dest in destinations(instruction) {
+                if script.has_section(dest) {
+                    queue.push_back(dest);
+                }
+            }
+        }
+    }
+    script
+        .iter_sections()
+        .map(|(section, _)| section)
+        .filter(|section| !reachable.contains(section))
+        .collect()
+}
+
+/// Expressions included in the instruction
+fn exprs(instruction: &Instruction) -> Vec<&Expr> {
+    match instruction {
+        Instruction::JumpIf(_, e)
+        | Instruction::GSet(_, e)
+        | Instruction::Set(_, e)
+        | Instruction::Return(Some(e))
+        | Instruction::While(e, _)
+        | Instruction::ReceiveItem(_, e)
+        | Instruction::ReceiveMoney(e)
+        | Instruction::Print(e)
+        | Instruction::ChangeFactionRelation(_, e)
+        | Instruction::GainSkillExp(_, e)
+        | Instruction::AddAttr(_, e)
+        | Instruction::AddHp(_, e) => vec![e],
+        Instruction::Call(_, args) => args.iter().collect(),
+        _ => vec![],
+    }
+}
+
+/// Collect the expression and its sub expressions
+fn flatten_expr<'a>(expr: &'a Expr, exprs: &mut Vec<&'a Expr>) {
+    exprs.push(expr);
+    match expr {
+        Expr::Term(v) => {
+            for (_, e) in v {
+                flatten_expr(e, exprs);
+            }
+        }
+        Expr::DurationHour(a, b) => {
+            flatten_expr(a, exprs);
+            flatten_expr(b, exprs);
+        }
+        _ => (),
+    }
+}
+
+#[test]
+fn check_script_test() {
+    let script = r#"--- start
+jump_if(sub, has_item(apple))
+talk(text0, [(choice0, missing), (choice1, quit)])
+receive_item(orange, 1)
+call(sub, 1, 2)
+--- sub(a)
+spawn_chara(rat, hostile)
+set_quest_stage(main, cave)
+jump_if(quit, quest_stage(main, forest) || quest_state(side, active))
+complete_quest(main)
+--- orphan
+jump(start)
+"#;
+    let (script, lines) = crate::buildobj::parse_with_lines(script).unwrap();
+    let source = ScriptSource {
+        path: PathBuf::from("test.rrscript"),
+        line_offset: 1,
+        lines,
+    };
+    let mut ids = KnownIds::default();
+    ids.items.insert("apple".to_owned());
+    ids.charas.insert("rat".to_owned());
+    ids.story_quests.insert(
+        "main".to_owned(),
+        vec!["start".to_owned(), "cave".to_owned()],
+    );
+    ids.texts = Some(["text0", "choice0"].iter().map(|s| s.to_string()).collect());
+    ids.refs_loaded = true;
+
+    let errors: Vec<String> = check_script(&script, &source, &ids)
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            "test.rrscript:4: error: unknown section \"missing\"",
+            "test.rrscript:4: error: unknown text id \"choice1\"",
+            "test.rrscript:5: error: unknown item id \"orange\"",
+            "test.rrscript:6: error: \"sub\" needs 1 arguments, but given 2",
+            "test.rrscript:10: error: unknown stage \"forest\" of story quest \"main\"",
+            "test.rrscript:10: error: unknown story quest id \"side\"",
+            "test.rrscript:12: warning: unreachable section \"orphan\"",
+        ]
+    );
+}
+
+#[test]
+fn check_script_without_refs_test() {
+    let script = r#"--- start
+jump_if(missing, has_item(apple))
+spawn_chara(rat, hostile)
+complete_quest(main)
+"#;
+    let (script, lines) = crate::buildobj::parse_with_lines(script).unwrap();
+    let source = ScriptSource {
+        path: PathBuf::from("test.rrscript"),
+        line_offset: 1,
+        lines,
+    };
+
+    let errors: Vec<String> = check_script(&script, &source, &KnownIds::default())
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            "test.rrscript:3: error: unknown section \"missing\"",
+            "test.rrscript:3: warning: unknown item id \"apple\"",
+            "test.rrscript:4: warning: unknown character template id \"rat\"",
+            "test.rrscript:5: warning: unknown story quest id \"main\"",
+        ]
+    );
+}
@@ -1,10 +1,12 @@
+use crate::buildobj::parse_with_lines;
+use crate::check::{check_scripts, KnownIds, ScriptSource};
 use crate::dir;
 use crate::error::*;
 use crate::rrscript::read_rrscript;
 use crate::verbose::print_verbose;
 use anyhow::*;
 use common::obj::Object;
-use common::pakutil::write_object;
+use common::pakutil::{load_objs_dir, read_tar, write_object};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::Path;
@@ -13,9 +15,11 @@ use tar;
 use crate::buildobj::build_object;
 use crate::input::Input;
 
-pub fn compile(files: &[&str], output_file: &String) {
-    let out = File::create(output_file).unwrap();
-    let mut builder = tar::Builder::new(out);
+/// Compile given files into a pak file.
+/// Returns false if input files cannot be processed or errors are found in scripts.
+pub fn compile(files: &[&str], output_file: &str, refs: &[&str], text_dir: Option<&str>) -> bool {
+    let mut objs = Vec::new();
+    let mut failed = false;
 
     for f in files {
         let f = Path::new(f);
@@ -26,28 +30,88 @@ pub fn compile(files: &[&str], output_file: &String) {
         }
 
         let read_result = if Some(true) == f.extension().map(|e| e == "rrscript") {
-            read_rrscript(f)
+            read_rrscript(f).map(|(obj, source)| (obj, Some(source)))
         } else {
             read_input_file(f)
         };
 
-        let obj = match read_result {
-            Ok(o) => o,
+        match read_result {
+            Ok(o) => objs.push(o),
             Err(e) => {
+                failed = true;
+                if let Some(PakCompileError::ScriptParseError { line, description }) =
+                    e.downcast_ref()
+                {
+                    eprintln!("{}:{}: error: {}", f.display(), line, description);
+                    continue;
+                }
                 eprintln!("Cannot process \"{}\"", f.to_string_lossy());
                 for e in e.chain() {
                     eprintln!("{}", e);
                 }
                 continue;
             }
-        };
-        let v = write_to_vec(&obj).unwrap();
-        write_data_to_tar(&mut builder, &v, &obj.get_id());
+        }
+    }
+    if failed {
+        eprintln!(
+            "Errors found in input files. \"{}\" is not written",
+            output_file
+        );
+        return false;
+    }
+
+    // Scripts are checked after all objects are collected
+    let mut ids = KnownIds::default();
+    for (obj, _) in &objs {
+        ids.add_object(obj);
+    }
+    for r in refs {
+        load_ref(Path::new(r), &mut ids);
+    }
+    if !refs.is_empty() {
+        ids.set_refs_loaded();
+    }
+    if let Some(text_dir) = text_dir {
+        if let Err(e) = ids.load_texts(Path::new(text_dir)) {
+            eprintln!("Cannot load texts from \"{}\"\n{}", text_dir, e);
+            return false;
+        }
+    }
+    if !check_scripts(&objs, &ids) {
+        eprintln!(
+            "Errors found in scripts. \"{}\" is not written",
+            output_file
+        );
+        return false;
+    }
+
+    let out = File::create(output_file).unwrap();
+    let mut builder = tar::Builder::new(out);
+    for (obj, _) in &objs {
+        let v = write_to_vec(obj).unwrap();
+        write_data_to_tar(&mut builder, &v, obj.get_id());
     }
     builder.finish().unwrap();
+    true
 }
 
-fn read_input_file<P: AsRef<Path>>(path: P) -> Result<Object, Error> {
+/// Load ids of objects from a pak file or a directory that scripts can refer
+fn load_ref(path: &Path, ids: &mut KnownIds) {
+    let mut cb = |obj: Object| ids.add_object(&obj);
+    let errors = if path.is_dir() {
+        load_objs_dir(path, cb)
+    } else {
+        let mut err_stack = Vec::new();
+        read_tar(path, &mut cb, &mut err_stack);
+        err_stack
+    };
+    for e in errors {
+        eprintln!("Cannot load \"{}\"\n{:?}", path.to_string_lossy(), e);
+    }
+}
+
+fn read_input_file<P: AsRef<Path>>(path: P) -> Result<(Object, Option<ScriptSource>), Error> {
     let path = path.as_ref();
     let s = {
         let mut f = File::open(path)?;
@@ -74,9 +138,22 @@ fn read_input_file<P: AsRef<Path>>(path: P) -> Result<Object, Error> {
     };
 
     print_verbose(|| format!("{:?}", input));
+    let source = if let Some(script) = input.script.as_ref().map(|s| &s.script) {
+        let line_offset = s
+            .find(script.as_str())
+            .map_or(0, |pos| s[..pos].matches('\n').count());
+        let (_, lines) = parse_with_lines(script).map_err(|e| e.with_line_offset(line_offset))?;
+        Some(ScriptSource {
+            path: path.to_owned(),
+            line_offset,
+            lines,
+        })
+    } else {
+        None
+    };
     let object = build_object(input)?;
 
-    Ok(object)
+    Ok((object, source))
 }
 
 fn write_to_vec(obj: &Object) -> Result<Vec<u8>, Error> {
@@ -15,6 +15,21 @@ pub enum PakCompileError {
     },
     #[error("object writing error\n{description}")]
     ObjWriteError { description: String },
-    #[error("script parse error\n{description}")]
-    ScriptParseError { description: String },
+    #[error("script parse error at line {line}\n{description}")]
+    ScriptParseError { line: usize, description: String },
+}
+
+impl PakCompileError {
+    /// Shift the line number of script parse errors by the lines before the script
+    pub fn with_line_offset(self, line_offset: usize) -> PakCompileError {
+        match self {
+            PakCompileError::ScriptParseError { line, description } => {
+                PakCompileError::ScriptParseError {
+                    line: line + line_offset,
+                    description,
+                }
+            }
+            e => e,
+        }
+    }
 }
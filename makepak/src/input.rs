@@ -2,7 +2,9 @@ use common::gamedata::{
     self, EffectAnimKind, Element, ElementArray, FactionId, Harvest, SkillKind, StatusEffect,
     TargetMode,
 };
+use common::script::ScriptHook;
 use common::sitegen;
+use common::storyquest::StoryQuestStage;
 use geom::{ShapeKind, Vec2d};
 use std::collections::HashMap;
 
@@ -19,6 +21,7 @@ pub struct Input {
     pub region_gen: Option<RegionGenDepInput>,
     pub script: Option<ScriptDepInput>,
     pub site_gen: Option<SiteGenDepInput>,
+    pub story_quest: Option<StoryQuestDepInput>,
 }
 
 /// If tomlinput has specified optional field, return it. If not, return error.
@@ -70,6 +73,8 @@ pub struct CharaTemplateDepInput {
     pub wil: u16,
     pub cha: u16,
     pub spd: u16,
+    #[serde(default)]
+    pub hooks: Vec<ScriptHook>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -93,6 +98,16 @@ pub struct WallDepInput {
 #[serde(deny_unknown_fields)]
 pub struct SpecialTileDepInput {
     pub always_background: Option<bool>,
+    pub trap: Option<TrapDepInput>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrapDepInput {
+    pub effect: EffectInput,
+    pub power: f32,
+    #[serde(default)]
+    pub difficulty: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -123,6 +138,7 @@ pub struct ItemDepInput {
     pub use_effect: Option<EffectInput>,
     #[serde(default)]
     pub tool_effect: gamedata::ToolEffect,
+    pub light_radius: Option<u16>,
     pub nutrition: Option<u16>,
     #[serde(default)]
     pub charge: [u8; 2],
@@ -160,6 +176,8 @@ pub struct SiteGenDepInput {
     pub default_faction_id: FactionId,
     pub unique_citizens: Option<Vec<sitegen::UniqueCitizenGenData>>,
     pub shops: Option<Vec<sitegen::ShopGenData>>,
+    #[serde(default)]
+    pub hooks: Vec<ScriptHook>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -168,6 +186,12 @@ pub struct ScriptDepInput {
     pub script: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StoryQuestDepInput {
+    pub stages: Vec<StoryQuestStage>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct EffectInput {
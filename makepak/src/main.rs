@@ -10,6 +10,7 @@ mod verbose;
 #[macro_use]
 mod input;
 mod buildobj;
+mod check;
 mod compile;
 mod dir;
 mod error;
@@ -43,7 +44,15 @@ fn main() {
         f
     };
 
-    compile::compile(&files, &output_file);
+    let refs: Vec<&str> = matches
+        .values_of("ref")
+        .map(|v| v.collect())
+        .unwrap_or_default();
+    let text_dir = matches.value_of("text");
+
+    if !compile::compile(&files, &output_file, &refs, text_dir) {
+        std::process::exit(1);
+    }
 }
 
 fn print_info(files: &[&str]) {
@@ -75,6 +84,24 @@ fn create_matches() -> clap::ArgMatches<'static> {
                 .help("Set output pakage file name")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("ref")
+                .short("r")
+                .long("ref")
+                .value_name("PATH")
+                .help("Pak file or directory including objects that scripts refer")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("text")
+                .short("t")
+                .long("text")
+                .value_name("DIR")
+                .help("Check text ids in scripts with fluent files in the directory")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("INPUT")
                 .help("Input toml files")
@@ -1,4 +1,5 @@
-use crate::buildobj::script_parse;
+use crate::buildobj::parse_with_lines;
+use crate::check::ScriptSource;
 use crate::verbose::print_verbose;
 use anyhow::*;
 use common::obj::{Object, ScriptObject};
@@ -6,8 +7,8 @@ use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
 
-/// Read rrscript file
-pub fn read_rrscript<P: AsRef<Path>>(path: P) -> Result<Object> {
+/// Read rrscript file. The first line is the object id.
+pub fn read_rrscript<P: AsRef<Path>>(path: P) -> Result<(Object, ScriptSource)> {
     let mut f = BufReader::new(File::open(path.as_ref())?);
     let mut first_line = String::new();
     f.read_line(&mut first_line)?;
@@ -17,10 +18,16 @@ pub fn read_rrscript<P: AsRef<Path>>(path: P) -> Result<Object> {
     print_verbose(|| format!("Processing \"{:?}\"", path.as_ref()));
 
     let object_id = first_line.trim().to_owned();
-    let script = script_parse(&script_text)?;
+    let (script, lines) = parse_with_lines(&script_text).map_err(|e| e.with_line_offset(1))?;
 
-    Ok(Object::Script(ScriptObject {
+    let obj = Object::Script(ScriptObject {
         id: object_id,
         script,
-    }))
+    });
+    let source = ScriptSource {
+        path: path.as_ref().to_owned(),
+        line_offset: 1,
+        lines,
+    };
+    Ok((obj, source))
 }
@@ -1,10 +1,11 @@
 use arrayvec::ArrayVec;
 use common::basic::N_TILE_IMG_LAYER;
-use common::gamedata::{ItemGen, TileLayers};
+use common::gamedata::{ItemGen, NpcGen, TileLayers};
 use common::gobj;
 use common::maptemplate::*;
 use common::objholder::*;
 use common::piece_pattern::*;
+use common::script::ScriptHook;
 use geom::*;
 
 pub struct EditingMap {
@@ -15,6 +16,10 @@ pub struct EditingMap {
     pub wall: Array2d<WallIdxPP>,
     pub deco: Array2d<Option<DecoIdx>>,
     pub items: Array2d<Vec<ItemGen>>,
+    /// NPC spawns are not editable, but kept when the map is saved
+    pub npcs: Vec<(Vec2d, NpcGen)>,
+    /// Script hooks are not editable, but kept when the map is saved
+    pub hooks: Vec<ScriptHook>,
 }
 
 impl EditingMap {
@@ -32,6 +37,8 @@ impl EditingMap {
             wall,
             deco,
             items,
+            npcs: Vec::new(),
+            hooks: Vec::new(),
         }
     }
 
@@ -230,6 +237,8 @@ impl EditingMap {
             entrance: self.property.entrance.clone(),
             items,
             music: self.property.music.clone(),
+            npcs: self.npcs.clone(),
+            hooks: self.hooks.clone(),
         }
     }
 }
@@ -281,6 +290,8 @@ impl From<MapTemplateObject> for EditingMap {
         map.property.boundary = obj.boundary;
         map.property.entrance = obj.entrance;
         map.property.music = obj.music;
+        map.npcs = obj.npcs;
+        map.hooks = obj.hooks;
 
         map
     }
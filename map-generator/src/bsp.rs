@@ -0,0 +1,156 @@
+use super::connect::dig_corridor;
+use super::{GeneratedMap, TileKind};
+use geom::*;
+use rand::seq::IteratorRandom;
+use rng::{gen_range, gen_range_inclusive, GameRng};
+
+/// Parameters for rooms in BSP-partitioned areas
+pub struct Bsp {
+    pub min_room_size: u32,
+    pub max_room_size: u32,
+    /// Maximum depth of partitioning
+    pub max_depth: u32,
+}
+
+/// Rectangle area including right bottom tile
+#[derive(Clone, Copy, Debug)]
+struct Area {
+    top_left: Vec2d,
+    right_bottom: Vec2d,
+}
+
+impl Area {
+    fn w(&self) -> i32 {
+        self.right_bottom.0 - self.top_left.0 + 1
+    }
+
+    fn h(&self) -> i32 {
+        self.right_bottom.1 - self.top_left.1 + 1
+    }
+
+    fn random_pos(&self) -> Vec2d {
+        Vec2d(
+            gen_range_inclusive(self.top_left.0, self.right_bottom.0),
+            gen_range_inclusive(self.top_left.1, self.right_bottom.1),
+        )
+    }
+}
+
+impl Bsp {
+    pub fn write_to_map(&self, gm: &mut GeneratedMap) {
+        for p in gm.tile.iter_idx() {
+            gm.tile[p] = TileKind::Wall;
+        }
+
+        let area = Area {
+            top_left: Vec2d(1, 1),
+            right_bottom: Vec2d(gm.size.0 - 2, gm.size.1 - 2),
+        };
+        // No space for rooms. The entrance is left on a wall, so this map is rejected.
+        if area.w() < 1 || area.h() < 1 {
+            return;
+        }
+        let mut rooms = Vec::new();
+        self.split(gm, area, 0, &mut rooms);
+
+        let mut rooms_with_stairs: [usize; 2] = [0, 0];
+        let n = (0..rooms.len()).choose_multiple_fill(&mut GameRng, &mut rooms_with_stairs);
+        gm.entrance = rooms[rooms_with_stairs[0]].random_pos();
+        // If there is only one room, the exit is on another tile of the same room.
+        // A single 1x1 room has no exit.
+        let exit_room = rooms[rooms_with_stairs[n - 1]];
+        let entrance = gm.entrance;
+        gm.exit = RectIter::new(exit_room.top_left, exit_room.right_bottom)
+            .filter(|p| *p != entrance)
+            .choose(&mut GameRng);
+    }
+
+    /// Split the area recursively, and create rooms in leaf areas.
+    /// Rooms in the two child areas are linked by a corridor.
+    /// Returns a room in this area that is used to link to other areas.
+    fn split(&self, gm: &mut GeneratedMap, area: Area, depth: u32, rooms: &mut Vec<Area>) -> Area {
+        // Both of child areas need the space for a room and its wall
+        let min_len = self.min_room_size as i32 + 1;
+        let can_split_x = area.w() >= min_len * 2;
+        let can_split_y = area.h() >= min_len * 2;
+
+        let split_x = match (can_split_x, can_split_y) {
+            _ if depth >= self.max_depth => None,
+            (true, true) => Some(if area.w() == area.h() {
+                gen_range(0, 2) == 0
+            } else {
+                area.w() > area.h()
+            }),
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            (false, false) => None,
+        };
+
+        let split_x = if let Some(split_x) = split_x {
+            split_x
+        } else {
+            let room = self.create_room(gm, area);
+            rooms.push(room);
+            return room;
+        };
+
+        let (a, b) = if split_x {
+            let x =
+                gen_range_inclusive(area.top_left.0 + min_len - 1, area.right_bottom.0 - min_len);
+            (
+                Area {
+                    top_left: area.top_left,
+                    right_bottom: Vec2d(x - 1, area.right_bottom.1),
+                },
+                Area {
+                    top_left: Vec2d(x + 1, area.top_left.1),
+                    right_bottom: area.right_bottom,
+                },
+            )
+        } else {
+            let y =
+                gen_range_inclusive(area.top_left.1 + min_len - 1, area.right_bottom.1 - min_len);
+            (
+                Area {
+                    top_left: area.top_left,
+                    right_bottom: Vec2d(area.right_bottom.0, y - 1),
+                },
+                Area {
+                    top_left: Vec2d(area.top_left.0, y + 1),
+                    right_bottom: area.right_bottom,
+                },
+            )
+        };
+
+        let room_a = self.split(gm, a, depth + 1, rooms);
+        let room_b = self.split(gm, b, depth + 1, rooms);
+        dig_corridor(gm, room_a.random_pos(), room_b.random_pos());
+
+        if gen_range(0, 2) == 0 {
+            room_a
+        } else {
+            room_b
+        }
+    }
+
+    fn create_room(&self, gm: &mut GeneratedMap, area: Area) -> Area {
+        let max_w = std::cmp::min(self.max_room_size as i32, area.w());
+        let max_h = std::cmp::min(self.max_room_size as i32, area.h());
+        let min_w = std::cmp::min(self.min_room_size as i32, max_w);
+        let min_h = std::cmp::min(self.min_room_size as i32, max_h);
+        let w = gen_range_inclusive(min_w, max_w);
+        let h = gen_range_inclusive(min_h, max_h);
+        let x = gen_range_inclusive(area.top_left.0, area.right_bottom.0 - w + 1);
+        let y = gen_range_inclusive(area.top_left.1, area.right_bottom.1 - h + 1);
+
+        let room = Area {
+            top_left: Vec2d(x, y),
+            right_bottom: Vec2d(x + w - 1, y + h - 1),
+        };
+        for p in RectIter::new(room.top_left, room.right_bottom) {
+            gm.tile[p] = TileKind::Floor;
+        }
+        gm.rooms.push((room.top_left, room.right_bottom));
+        room
+    }
+}
@@ -0,0 +1,98 @@
+use super::connect::{connect_regions, regions};
+use super::{GeneratedMap, TileKind};
+use geom::*;
+use rng::{gen_bool, gen_range};
+
+/// Regions smaller than this are filled instead of connected
+const MIN_REGION_SIZE: usize = 6;
+const MAX_TRY: usize = 16;
+
+/// Parameters for cellular automata caves
+pub struct Cave {
+    /// Ratio of initial random walls
+    pub wall_ratio: f32,
+    /// The number of automaton steps
+    pub n_step: u32,
+    /// A floor tile becomes wall if the number of adjacent walls is equal or more than this
+    pub birth_limit: u32,
+    /// A wall tile remains if the number of adjacent walls is equal or more than this
+    pub survival_limit: u32,
+}
+
+impl Cave {
+    pub fn write_to_map(&self, gm: &mut GeneratedMap) {
+        for _ in 0..MAX_TRY {
+            self.fill_random(gm);
+            for _ in 0..self.n_step {
+                self.step(gm);
+            }
+            connect_regions(gm, MIN_REGION_SIZE);
+
+            // If floor tiles are too few, create map again
+            let n_floor = gm.tile.iter().filter(|t| t.is_passable()).count() as i32;
+            if n_floor >= gm.size.0 * gm.size.1 / 4 {
+                break;
+            }
+        }
+
+        let floor_tiles = regions(gm).into_iter().next().unwrap_or_default();
+        if floor_tiles.len() < 2 {
+            // Give up to create a cave
+            for p in gm.tile.iter_idx() {
+                gm.tile[p] = TileKind::Floor;
+            }
+            gm.entrance = Vec2d(0, 0);
+            gm.exit = Some(Vec2d(gm.size.0 - 1, gm.size.1 - 1));
+            return;
+        }
+
+        let entrance = floor_tiles[gen_range(0, floor_tiles.len())];
+        let exit = loop {
+            let p = floor_tiles[gen_range(0, floor_tiles.len())];
+            if p != entrance {
+                break p;
+            }
+        };
+        gm.entrance = entrance;
+        gm.exit = Some(exit);
+    }
+
+    fn fill_random(&self, gm: &mut GeneratedMap) {
+        let size = gm.size;
+        for p in gm.tile.iter_idx() {
+            gm.tile[p] = if is_edge(size, p) || gen_bool(self.wall_ratio) {
+                TileKind::Wall
+            } else {
+                TileKind::Floor
+            };
+        }
+    }
+
+    fn step(&self, gm: &mut GeneratedMap) {
+        let size = gm.size;
+        let prev = gm.tile.clone();
+
+        for p in prev.iter_idx() {
+            if is_edge(size, p) {
+                continue;
+            }
+            let n_wall = RectIter::new(p + (-1, -1), p + (1, 1))
+                .filter(|q| *q != p && prev[*q] == TileKind::Wall)
+                .count() as u32;
+            let is_wall = if prev[p] == TileKind::Wall {
+                n_wall >= self.survival_limit
+            } else {
+                n_wall >= self.birth_limit
+            };
+            gm.tile[p] = if is_wall {
+                TileKind::Wall
+            } else {
+                TileKind::Floor
+            };
+        }
+    }
+}
+
+fn is_edge(size: Vec2d, p: Vec2d) -> bool {
+    p.0 == 0 || p.1 == 0 || p.0 == size.0 - 1 || p.1 == size.1 - 1
+}
@@ -0,0 +1,107 @@
+use super::{GeneratedMap, TileKind};
+use geom::*;
+
+const DIRS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Split passable tiles into groups connected orthogonally
+pub fn regions(gm: &GeneratedMap) -> Vec<Vec<Vec2d>> {
+    regions_by(gm.size, |p| gm.tile[p].is_passable())
+}
+
+/// Split passable tiles into groups connected orthogonally by given passability
+pub fn regions_by<F: Fn(Vec2d) -> bool>(size: Vec2d, is_passable: F) -> Vec<Vec<Vec2d>> {
+    let mut visited = Array2d::new(size.0 as u32, size.1 as u32, false);
+    let mut regions = Vec::new();
+
+    for p in size.iter_from_zero() {
+        if visited[p] || !is_passable(p) {
+            continue;
+        }
+
+        let mut region = vec![p];
+        let mut stack = vec![p];
+        visited[p] = true;
+
+        while let Some(p) = stack.pop() {
+            for d in &DIRS {
+                let next = p + *d;
+                if visited.in_range(next) && !visited[next] && is_passable(next) {
+                    visited[next] = true;
+                    region.push(next);
+                    stack.push(next);
+                }
+            }
+        }
+
+        regions.push(region);
+    }
+
+    regions
+}
+
+/// Connect all regions to the largest one by digging corridors.
+/// Regions smaller than `min_region_size` are filled with walls.
+pub fn connect_regions(gm: &mut GeneratedMap, min_region_size: usize) {
+    let mut regions = regions(gm);
+    if regions.is_empty() {
+        return;
+    }
+    regions.sort_by_key(|region| std::cmp::Reverse(region.len()));
+
+    let mut connected = regions.remove(0);
+
+    for region in regions {
+        if region.len() < min_region_size {
+            for p in region {
+                gm.tile[p] = TileKind::Wall;
+            }
+            continue;
+        }
+
+        let (start, end) = nearest_pair(&region, &connected);
+        connected.extend(dig_corridor(gm, start, end));
+        connected.extend(region);
+    }
+}
+
+/// Find the nearest pair of tiles in two regions
+fn nearest_pair(a: &[Vec2d], b: &[Vec2d]) -> (Vec2d, Vec2d) {
+    let mut nearest = (a[0], b[0]);
+    let mut min_distance = a[0].mdistance(b[0]);
+
+    for pa in a {
+        for pb in b {
+            let distance = pa.mdistance(*pb);
+            if distance < min_distance {
+                min_distance = distance;
+                nearest = (*pa, *pb);
+            }
+        }
+    }
+
+    nearest
+}
+
+/// Dig horizontally and then vertically from start to end.
+/// Returns the tiles changed to floor.
+pub fn dig_corridor(gm: &mut GeneratedMap, start: Vec2d, end: Vec2d) -> Vec<Vec2d> {
+    let mut dug = Vec::new();
+    let mut p = start;
+
+    loop {
+        if !gm.tile[p].is_passable() {
+            gm.tile[p] = TileKind::Floor;
+            dug.push(p);
+        }
+        if p == end {
+            break;
+        }
+        if p.0 != end.0 {
+            p.0 += (end.0 - p.0).signum();
+        } else {
+            p.1 += (end.1 - p.1).signum();
+        }
+    }
+
+    dug
+}
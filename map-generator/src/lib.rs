@@ -7,9 +7,18 @@ use geom::*;
 
 pub mod binary;
 
+mod bsp;
+mod cave;
+mod connect;
 mod fractal;
 mod lattice;
+mod prefab;
 mod rooms;
+pub mod validator;
+
+/// Maximum number of generation when repairing generated maps fails.
+/// After that, a single room map is generated instead.
+const MAX_GENERATE_TRY: usize = 16;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum TileKind {
@@ -32,6 +41,8 @@ pub struct GeneratedMap {
     pub tile: Array2d<TileKind>,
     pub entrance: Vec2d,
     pub exit: Option<Vec2d>,
+    /// Top left and bottom right tiles of rooms
+    pub rooms: Vec<(Vec2d, Vec2d)>,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -50,24 +61,61 @@ enum MapGenParam {
         min_room_size: u32,
         n_room: u32,
     },
+    Cave {
+        wall_ratio: f32,
+        n_step: u32,
+        birth_limit: u32,
+        survival_limit: u32,
+    },
+    Bsp {
+        min_room_size: u32,
+        max_room_size: u32,
+        max_depth: u32,
+    },
+}
+
+impl GeneratedMap {
+    fn new(size: Vec2d) -> GeneratedMap {
+        GeneratedMap {
+            size,
+            tile: Array2d::new(size.0 as u32, size.1 as u32, TileKind::Floor),
+            entrance: Vec2d(0, 0),
+            exit: None,
+            rooms: Vec::new(),
+        }
+    }
+
+    /// Single room surrounded by walls, with the entrance and the exit at its corners.
+    /// This is always valid, so used when generation fails.
+    fn single_room(size: Vec2d) -> GeneratedMap {
+        let mut map = GeneratedMap::new(size);
+        if size.0 < 3 || size.1 < 3 {
+            return map;
+        }
+        for p in map.tile.iter_idx() {
+            if p.0 == 0 || p.1 == 0 || p.0 == size.0 - 1 || p.1 == size.1 - 1 {
+                map.tile[p] = TileKind::Wall;
+            }
+        }
+        map.entrance = Vec2d(1, 1);
+        let exit = size + (-2, -2);
+        if exit != map.entrance {
+            map.exit = Some(exit);
+        }
+        map.rooms.push((Vec2d(1, 1), exit));
+        map
+    }
 }
 
 pub struct MapGenerator {
-    map: GeneratedMap,
+    size: Vec2d,
     genparam: Option<MapGenParam>,
 }
 
 impl MapGenerator {
     pub fn new<S: Into<Vec2d>>(size: S) -> MapGenerator {
-        let size = size.into();
-        let map = GeneratedMap {
-            size,
-            tile: Array2d::new(size.0 as u32, size.1 as u32, TileKind::Floor),
-            entrance: Vec2d(0, 0),
-            exit: None,
-        };
         MapGenerator {
-            map,
+            size: size.into(),
             genparam: None,
         }
     }
@@ -115,39 +163,107 @@ impl MapGenerator {
         self
     }
 
-    /// Generate one map
-    pub fn generate(mut self) -> GeneratedMap {
-        match self
+    /// Create cave map by cellular automata. Separated caves are connected by corridors.
+    pub fn cave(
+        mut self,
+        wall_ratio: f32,
+        n_step: u32,
+        birth_limit: u32,
+        survival_limit: u32,
+    ) -> MapGenerator {
+        self.genparam = Some(MapGenParam::Cave {
+            wall_ratio,
+            n_step,
+            birth_limit,
+            survival_limit,
+        });
+        self
+    }
+
+    /// Create rooms in binary space partitioned areas. Rooms are linked by corridors.
+    pub fn bsp(mut self, min_room_size: u32, max_room_size: u32, max_depth: u32) -> MapGenerator {
+        self.genparam = Some(MapGenParam::Bsp {
+            min_room_size,
+            max_room_size,
+            max_depth,
+        });
+        self
+    }
+
+    /// Generate one map.
+    /// If the generated map is invalid, it is repaired or generated again.
+    /// If all tries fail, a single room map is returned.
+    pub fn generate(self) -> GeneratedMap {
+        let genparam = self
             .genparam
-            .expect("Map generate before giving parameters")
-        {
-            MapGenParam::Flat => {
-                return self.map;
-            }
-            MapGenParam::Lattice {
-                nx,
-                ny,
-                step_min,
-                step_max,
-                door_weight,
-            } => {
-                let lattice = lattice::create_lattice(nx, ny, step_min, step_max);
-                lattice.write_to_map(&mut self.map, door_weight);
-                return self.map;
+            .expect("Map generate before giving parameters");
+
+        for _ in 0..MAX_GENERATE_TRY {
+            let mut map = GeneratedMap::new(self.size);
+            write_to_map(&mut map, &genparam);
+
+            if map.validate().is_ok() {
+                return map;
             }
-            MapGenParam::Fractal => {
-                fractal::write_to_map(&mut self.map);
-                return self.map;
+            map.repair();
+            if map.validate().is_ok() {
+                return map;
             }
-            MapGenParam::Rooms {
-                max_room_size,
+        }
+
+        GeneratedMap::single_room(self.size)
+    }
+}
+
+fn write_to_map(map: &mut GeneratedMap, genparam: &MapGenParam) {
+    match *genparam {
+        MapGenParam::Flat => (),
+        MapGenParam::Lattice {
+            nx,
+            ny,
+            step_min,
+            step_max,
+            door_weight,
+        } => {
+            let lattice = lattice::create_lattice(nx, ny, step_min, step_max);
+            lattice.write_to_map(map, door_weight);
+        }
+        MapGenParam::Fractal => {
+            fractal::write_to_map(map);
+        }
+        MapGenParam::Rooms {
+            max_room_size,
+            min_room_size,
+            n_room,
+        } => {
+            let rooms = rooms::Rooms::new(max_room_size, min_room_size, n_room);
+            rooms.write_to_map(map);
+        }
+        MapGenParam::Cave {
+            wall_ratio,
+            n_step,
+            birth_limit,
+            survival_limit,
+        } => {
+            let cave = cave::Cave {
+                wall_ratio,
+                n_step,
+                birth_limit,
+                survival_limit,
+            };
+            cave.write_to_map(map);
+        }
+        MapGenParam::Bsp {
+            min_room_size,
+            max_room_size,
+            max_depth,
+        } => {
+            let bsp = bsp::Bsp {
                 min_room_size,
-                n_room,
-            } => {
-                let rooms = rooms::Rooms::new(max_room_size, min_room_size, n_room);
-                rooms.write_to_map(&mut self.map);
-                return self.map;
-            }
+                max_room_size,
+                max_depth,
+            };
+            bsp.write_to_map(map);
         }
     }
 }
@@ -207,4 +323,132 @@ mod tests {
         let map = MapGenerator::new((35, 35)).rooms(5, 8, 7).generate();
         println!("Rooms map:\n{}", map);
     }
+
+    #[test]
+    fn cave_map() {
+        for _ in 0..10 {
+            let map = MapGenerator::new((40, 40)).cave(0.45, 4, 5, 4).generate();
+            assert_eq!(map.validate(), Ok(()));
+            assert!(map.tile[map.entrance].is_passable());
+            assert!(map.tile[map.exit.unwrap()].is_passable());
+            println!("Cave map:\n{}", map);
+        }
+    }
+
+    #[test]
+    fn bsp_map() {
+        for _ in 0..10 {
+            let map = MapGenerator::new((40, 30)).bsp(4, 8, 4).generate();
+            assert_eq!(map.validate(), Ok(()));
+            assert!(map.tile[map.entrance].is_passable());
+            assert_ne!(Some(map.entrance), map.exit);
+            println!("BSP map:\n{}", map);
+        }
+    }
+
+    #[test]
+    fn bsp_single_room_map() {
+        for size in &[Vec2d(2, 2), Vec2d(3, 3), Vec2d(4, 3), Vec2d(6, 6)] {
+            for _ in 0..10 {
+                let map = MapGenerator::new(*size).bsp(1, 1, 0).generate();
+                assert_eq!(map.validate(), Ok(()), "size {:?}", size);
+                assert!(map.tile[map.entrance].is_passable());
+                assert_ne!(Some(map.entrance), map.exit);
+            }
+        }
+    }
+
+    #[test]
+    fn all_generators_reachability() {
+        type SetParam = fn(MapGenerator) -> MapGenerator;
+        let generators: Vec<(&str, Vec2d, SetParam)> = vec![
+            ("flat", Vec2d(10, 10), |mg| mg.flat()),
+            ("lattice", Vec2d(19, 15), |mg| mg.lattice(5, 4, 3, 7, 0.5)),
+            ("fractal", Vec2d(32, 32), |mg| mg.fractal()),
+            ("rooms", Vec2d(35, 35), |mg| mg.rooms(5, 8, 7)),
+            ("cave", Vec2d(40, 40), |mg| mg.cave(0.45, 4, 5, 4)),
+            ("bsp", Vec2d(40, 30), |mg| mg.bsp(4, 8, 4)),
+        ];
+
+        for (name, size, f) in generators {
+            for seed in 0..100 {
+                rng::reseed(seed);
+                let map = f(MapGenerator::new(size)).generate();
+                assert_eq!(map.validate(), Ok(()), "{} map with seed {}", name, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn stamp_prefab() {
+        // Room with an entrance at the bottom
+        let prefab = Array2d::from_fn(5, 4, |p| {
+            if p.0 == 0 || p.0 == 4 || p.1 == 0 || p.1 == 3 {
+                TileKind::Wall
+            } else {
+                TileKind::Floor
+            }
+        });
+
+        for seed in 0..20 {
+            rng::reseed(seed);
+            let mut map = MapGenerator::new((35, 35)).rooms(5, 8, 7).generate();
+            let pos = if let Some(pos) = map.find_free_space(Vec2d(5, 4)) {
+                pos
+            } else {
+                continue;
+            };
+            map.stamp(pos, &prefab, Vec2d(2, 3));
+            assert_eq!(map.validate(), Ok(()), "seed {}", seed);
+            assert_eq!(map.tile[pos + (1, 1)], TileKind::Floor);
+            assert_eq!(map.tile[pos], TileKind::Wall);
+            println!("Map with prefab:\n{}", map);
+        }
+    }
+
+    #[test]
+    fn single_room_map() {
+        for size in &[Vec2d(2, 2), Vec2d(3, 3), Vec2d(4, 3), Vec2d(20, 10)] {
+            let map = GeneratedMap::single_room(*size);
+            assert_eq!(map.validate(), Ok(()), "size {:?}", size);
+            assert!(map.tile[map.entrance].is_passable());
+            assert_ne!(Some(map.entrance), map.exit);
+        }
+        let map = GeneratedMap::single_room(Vec2d(20, 10));
+        assert_eq!(map.exit, Some(Vec2d(18, 8)));
+        assert_eq!(map.tile[Vec2d(0, 0)], TileKind::Wall);
+        println!("Single room map:\n{}", map);
+    }
+
+    #[test]
+    fn repair_separated_map() {
+        let mut map = GeneratedMap::new(Vec2d(9, 5));
+        for p in map.tile.iter_idx() {
+            if p.0 == 4 || p.1 == 2 {
+                map.tile[p] = TileKind::Wall;
+            }
+        }
+        map.entrance = Vec2d(0, 0);
+        map.exit = Some(Vec2d(8, 4));
+        assert_eq!(
+            map.validate(),
+            Err(validator::ValidationError::ExitUnreachable)
+        );
+
+        map.exit = Some(Vec2d(1, 1));
+        assert_eq!(
+            map.validate(),
+            Err(validator::ValidationError::Disconnected(4))
+        );
+
+        map.exit = Some(Vec2d(4, 2));
+        assert_eq!(
+            map.validate(),
+            Err(validator::ValidationError::ExitNotPassable(Vec2d(4, 2)))
+        );
+
+        map.repair();
+        assert_eq!(map.validate(), Ok(()));
+        println!("Repaired map:\n{}", map);
+    }
 }
@@ -0,0 +1,106 @@
+use super::connect::dig_corridor;
+use super::{GeneratedMap, TileKind};
+use geom::*;
+
+impl GeneratedMap {
+    /// Choose a random position where a prefab of given size can be located.
+    /// The area and its surrounding tiles must be filled with walls.
+    pub fn find_free_space(&self, size: Vec2d) -> Option<Vec2d> {
+        if size.0 + 2 > self.size.0 || size.1 + 2 > self.size.1 {
+            return None;
+        }
+
+        let candidates: Vec<Vec2d> = RectIter::new(
+            Vec2d(1, 1),
+            Vec2d(self.size.0 - size.0 - 1, self.size.1 - size.1 - 1),
+        )
+        .filter(|pos| {
+            RectIter::new(*pos + (-1, -1), *pos + size)
+                .all(|p| self.tile.in_range(p) && self.tile[p] == TileKind::Wall)
+        })
+        .collect();
+
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(candidates[rng::gen_range(0, candidates.len())])
+        }
+    }
+
+    /// Write prefab tiles at given position,
+    /// and dig a corridor from the prefab entrance to the nearest floor outside.
+    /// `entrance` is a relative position in the prefab.
+    /// The corridor goes out from the nearest prefab side, and does not cross the prefab.
+    pub fn stamp(&mut self, pos: Vec2d, tile: &Array2d<TileKind>, entrance: Vec2d) {
+        let size = Vec2d(tile.size().0 as i32, tile.size().1 as i32);
+        let in_prefab =
+            |p: Vec2d| pos.0 <= p.0 && p.0 < pos.0 + size.0 && pos.1 <= p.1 && p.1 < pos.1 + size.1;
+
+        let mut outside_floor: Vec<Vec2d> = self
+            .tile
+            .iter_idx()
+            .filter(|p| self.tile[*p].is_passable() && !in_prefab(*p))
+            .collect();
+
+        for (p, t) in tile.iter_with_idx() {
+            self.tile[pos + p] = *t;
+        }
+
+        // Dig from the entrance to the outside of the prefab
+        let dir = [
+            (entrance.0 + 1, Vec2d(-1, 0)),
+            (size.0 - entrance.0, Vec2d(1, 0)),
+            (entrance.1 + 1, Vec2d(0, -1)),
+            (size.1 - entrance.1, Vec2d(0, 1)),
+        ]
+        .iter()
+        .min_by_key(|(distance, _)| *distance)
+        .unwrap()
+        .1;
+        let mut door = pos + entrance;
+        while in_prefab(door) {
+            self.tile[door] = TileKind::Floor;
+            door = door + dir;
+        }
+
+        outside_floor.sort_by_key(|p| p.mdistance(door));
+        for end in outside_floor {
+            for horizontal_first in &[true, false] {
+                let path = l_path(door, end, *horizontal_first);
+                if path
+                    .iter()
+                    .all(|p| !in_prefab(*p) && self.tile.in_range(*p))
+                {
+                    for p in path {
+                        self.tile[p] = TileKind::Floor;
+                    }
+                    return;
+                }
+            }
+        }
+        // Fallback that may cross the prefab
+        if let Some(end) = self
+            .tile
+            .iter_idx()
+            .filter(|p| self.tile[*p].is_passable() && !in_prefab(*p))
+            .min_by_key(|p| p.mdistance(door))
+        {
+            dig_corridor(self, door, end);
+        }
+    }
+}
+
+/// Tiles on the L-shaped path from start to end
+fn l_path(start: Vec2d, end: Vec2d, horizontal_first: bool) -> Vec<Vec2d> {
+    let mut path = vec![start];
+    let mut p = start;
+    while p != end {
+        if (horizontal_first && p.0 != end.0) || p.1 == end.1 {
+            p.0 += (end.0 - p.0).signum();
+        } else {
+            p.1 += (end.1 - p.1).signum();
+        }
+        path.push(p);
+    }
+    path
+}
@@ -73,6 +73,10 @@ impl Rooms {
             for p in rectiter {
                 gm.tile[p] = TileKind::Floor;
             }
+            gm.rooms.push((
+                Vec2d(room.x, room.y),
+                Vec2d(room.x + room.w as i32, room.y + room.h as i32),
+            ));
             if i == rooms_with_stairs[0] || i == rooms_with_stairs[1] {
                 let dx = gen_range(1, room.w - 1) as i32;
                 let dy = gen_range(1, room.h - 1) as i32;
@@ -0,0 +1,76 @@
+//! Validation for generated maps.
+//! All passable tiles must be connected, and the exit must be reachable from the entrance.
+
+use super::connect::{connect_regions, regions_by};
+use super::{GeneratedMap, TileKind};
+use geom::*;
+use thiserror::Error;
+
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+pub enum ValidationError {
+    #[error("entrance {0:?} is not passable")]
+    EntranceNotPassable(Vec2d),
+    #[error("exit {0:?} is not passable")]
+    ExitNotPassable(Vec2d),
+    #[error("exit is not reachable from entrance")]
+    ExitUnreachable,
+    #[error("passable tiles are separated into {0} regions")]
+    Disconnected(usize),
+}
+
+/// Validate map connectivity by given passability.
+/// This can be used for maps modified after generation.
+pub fn validate_by<F: Fn(Vec2d) -> bool>(
+    size: Vec2d,
+    entrance: Vec2d,
+    exit: Option<Vec2d>,
+    is_passable: F,
+) -> Result<(), ValidationError> {
+    let in_range = |p: Vec2d| 0 <= p.0 && p.0 < size.0 && 0 <= p.1 && p.1 < size.1;
+
+    if !in_range(entrance) || !is_passable(entrance) {
+        return Err(ValidationError::EntranceNotPassable(entrance));
+    }
+    if let Some(exit) = exit {
+        if !in_range(exit) || !is_passable(exit) {
+            return Err(ValidationError::ExitNotPassable(exit));
+        }
+    }
+
+    let regions = regions_by(size, is_passable);
+    if let Some(exit) = exit {
+        let entrance_region = regions
+            .iter()
+            .find(|region| region.contains(&entrance))
+            .expect("entrance must be in a region");
+        if !entrance_region.contains(&exit) {
+            return Err(ValidationError::ExitUnreachable);
+        }
+    }
+    if regions.len() > 1 {
+        return Err(ValidationError::Disconnected(regions.len()));
+    }
+
+    Ok(())
+}
+
+impl GeneratedMap {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_by(self.size, self.entrance, self.exit, |p| {
+            self.tile[p].is_passable()
+        })
+    }
+
+    /// Carve stairs tiles and tunnels between separated regions
+    pub fn repair(&mut self) {
+        if self.tile.in_range(self.entrance) {
+            self.tile[self.entrance] = TileKind::Floor;
+        }
+        if let Some(exit) = self.exit {
+            if self.tile.in_range(exit) {
+                self.tile[exit] = TileKind::Floor;
+            }
+        }
+        connect_regions(self, 1);
+    }
+}
@@ -1,5 +1,8 @@
 //! Helper crate for using thread local and fast random number generator
 
+#[macro_use]
+extern crate serde_derive;
+
 use rand::distributions::uniform::{SampleBorrow, SampleUniform, Uniform};
 pub use rand::seq::SliceRandom;
 pub use rand::thread_rng;
@@ -9,31 +12,102 @@ use rand::SeedableRng;
 use rand_xorshift::XorShiftRng;
 use std::cell::RefCell;
 
+/// Seed used when the state of RNG is fixed
+pub const FIXED_SEED: u64 = 0x7275696e730a;
+
+/// Random number streams.
+/// Each stream has its own generator state, so consuming random numbers in a stream
+/// does not change the results of other streams.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RngStream {
+    Main,
+    MapGen,
+    Combat,
+    Loot,
+}
+
+impl RngStream {
+    pub const ALL: [RngStream; 4] = [
+        RngStream::Main,
+        RngStream::MapGen,
+        RngStream::Combat,
+        RngStream::Loot,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Snapshot of generator states.
+/// Stored in save data to reproduce the game after loading.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RngState {
+    seed: u64,
+    streams: Vec<XorShiftRng>,
+}
+
+impl RngState {
+    pub fn from_seed(seed: u64) -> RngState {
+        let streams = RngStream::ALL
+            .iter()
+            .map(|stream| XorShiftRng::seed_from_u64(seed ^ ((stream.index() as u64) << 56)))
+            .collect();
+        RngState { seed, streams }
+    }
+
+    /// The seed this state was initialized with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for RngState {
+    fn default() -> RngState {
+        RngState::from_seed(FIXED_SEED)
+    }
+}
+
+struct Generators {
+    state: RngState,
+    active: RngStream,
+}
+
+impl Generators {
+    #[inline]
+    fn rng(&mut self) -> &mut XorShiftRng {
+        &mut self.state.streams[self.active.index()]
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct GameRng;
 
-thread_local!(static XORSHIFT_RNG: RefCell<XorShiftRng> = {
-    let xorshift_rng = XorShiftRng::from_seed([0; 16]);
-    RefCell::new(xorshift_rng)
+thread_local!(static GENERATORS: RefCell<Generators> = {
+    RefCell::new(Generators {
+        state: RngState::from_seed(0),
+        active: RngStream::Main,
+    })
 });
 
 impl RngCore for GameRng {
     #[inline]
     fn next_u32(&mut self) -> u32 {
-        XORSHIFT_RNG.with(|xorshift_rng| xorshift_rng.borrow_mut().next_u32())
+        GENERATORS.with(|g| g.borrow_mut().rng().next_u32())
     }
 
     #[inline]
     fn next_u64(&mut self) -> u64 {
-        XORSHIFT_RNG.with(|xorshift_rng| xorshift_rng.borrow_mut().next_u64())
+        GENERATORS.with(|g| g.borrow_mut().rng().next_u64())
     }
 
     fn fill_bytes(&mut self, dest: &mut [u8]) {
-        XORSHIFT_RNG.with(|xorshift_rng| xorshift_rng.borrow_mut().fill_bytes(dest))
+        GENERATORS.with(|g| g.borrow_mut().rng().fill_bytes(dest))
     }
 
     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ::rand::Error> {
-        XORSHIFT_RNG.with(|xorshift_rng| xorshift_rng.borrow_mut().try_fill_bytes(dest))
+        GENERATORS.with(|g| g.borrow_mut().rng().try_fill_bytes(dest))
     }
 }
 
@@ -41,18 +115,42 @@ pub fn get_rng() -> GameRng {
     GameRng
 }
 
-/// Reseed
-pub fn reseed(fixed: bool) {
-    let new_rng = if fixed {
-        XorShiftRng::seed_from_u64(0x7275696e730a)
-    } else {
-        XorShiftRng::from_rng(thread_rng()).expect("reseed from thread rng failed")
-    };
-    XORSHIFT_RNG.with(|xorshift_rng| {
-        xorshift_rng.replace(new_rng);
+/// Reseed all streams by given seed
+pub fn reseed(seed: u64) {
+    set_state(RngState::from_seed(seed));
+}
+
+/// Generator independent of the streams, for values that must be derived from a fixed seed
+pub fn seeded(seed: u64) -> impl Rng {
+    XorShiftRng::seed_from_u64(seed)
+}
+
+/// Generate a new seed from thread rng
+pub fn gen_seed() -> u64 {
+    thread_rng().gen()
+}
+
+/// Get the current generator states
+pub fn get_state() -> RngState {
+    GENERATORS.with(|g| g.borrow().state.clone())
+}
+
+/// Restore generator states
+pub fn set_state(state: RngState) {
+    assert_eq!(state.streams.len(), RngStream::ALL.len());
+    GENERATORS.with(|g| {
+        g.borrow_mut().state = state;
     })
 }
 
+/// Use the given stream for random numbers generated in f
+pub fn with_stream<F: FnOnce() -> R, R>(stream: RngStream, f: F) -> R {
+    let prev = GENERATORS.with(|g| std::mem::replace(&mut g.borrow_mut().active, stream));
+    let result = f();
+    GENERATORS.with(|g| g.borrow_mut().active = prev);
+    result
+}
+
 pub fn next_u32() -> u32 {
     let mut rng = GameRng;
     rng.next_u32()
@@ -132,4 +230,26 @@ mod tests {
         let average = sum / N as f64;
         println!("average is {}", average);
     }
+
+    #[test]
+    fn restore_state() {
+        reseed(12345);
+        let _ = with_stream(RngStream::MapGen, next_u32);
+        let state = get_state();
+        let a: Vec<u32> = (0..10).map(|_| next_u32()).collect();
+        let b: Vec<u32> = (0..10)
+            .map(|_| with_stream(RngStream::Loot, next_u32))
+            .collect();
+
+        set_state(state);
+        let a2: Vec<u32> = (0..10).map(|_| next_u32()).collect();
+        assert_eq!(a, a2);
+
+        // Streams are independent of each other
+        reseed(12345);
+        let b2: Vec<u32> = (0..10)
+            .map(|_| with_stream(RngStream::Loot, next_u32))
+            .collect();
+        assert_eq!(b, b2);
+    }
 }
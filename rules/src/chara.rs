@@ -1,3 +1,5 @@
+use common::gamedata::NpcAIKind;
+
 /// Rules for character parameter calculation
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Chara {
@@ -32,4 +34,8 @@ pub struct Chara {
     pub carrying_capacity_threshold_stressed: f32,
     pub carrying_capacity_threshold_strained: f32,
     pub carrying_capacity_threshold_overloaded: f32,
+    /// Maximum number of party members except the player
+    pub max_party_members: u32,
+    /// AI kind for party members
+    pub party_ai_kind: NpcAIKind,
 }
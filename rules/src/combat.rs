@@ -4,4 +4,6 @@ pub struct Combat {
     pub skill_base: f32,
     pub throw_range_factor: u32,
     pub throw_range_max: u32,
+    /// Range of ranged weapons
+    pub ranged_weapon_range: u32,
 }
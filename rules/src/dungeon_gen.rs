@@ -24,4 +24,25 @@ pub struct DungeonGenParams {
     pub floor_range: [u32; 2],
     /// Default map music
     pub music: String,
+    /// Map template ids stamped into floors as prefabs, and their weight
+    #[serde(default)]
+    pub prefabs: Vec<(String, f32)>,
+    /// The probability of stamping a prefab on each floor
+    #[serde(default)]
+    pub prefab_probability: f32,
+    /// Trap special tile ids and their weight
+    #[serde(default)]
+    pub traps: Vec<(String, f32)>,
+    /// Trap generation probability on each floor tile
+    #[serde(default)]
+    pub trap_probability: f32,
+    /// The probability of locking each door
+    #[serde(default)]
+    pub locked_door_probability: f32,
+    /// The probability of replacing each door with a hidden passage
+    #[serde(default)]
+    pub hidden_passage_probability: f32,
+    /// The probability of lighting each room
+    #[serde(default)]
+    pub lit_room_probability: f32,
 }
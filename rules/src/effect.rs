@@ -5,5 +5,11 @@ pub struct Effect {
     pub item_eat_power_factor: f32,
     pub mining_power_factor: f32,
     pub mining_power_base: f32,
+    /// Added to Lockpicking skill level when trying to pick a lock
+    pub lockpicking_base: f32,
+    /// Added to Searching skill level when searching hidden traps and passages
+    pub searching_base: f32,
+    /// Searching range from the player
+    pub search_range: i32,
     pub recover_hp_factor: f32,
 }
@@ -19,6 +19,16 @@ pub struct Exp {
     pub healing_probability: f32,
     /// Base exp to Mining skill
     pub mining: u32,
+    /// Base exp to Lockpicking skill
+    pub lockpicking: u32,
+    /// Base exp to Searching skill after finding something
+    pub searching: u32,
+    /// Base exp to Stealth skill after sneak attacks
+    pub stealth: u32,
+    /// Base exp to Trading skill after buying or selling
+    pub trading: u32,
+    /// Base exp to Appraisal skill after identifying an item
+    pub appraisal: u32,
     /// Base exp for creation
     pub creation_base_exp: u32,
 }
@@ -12,11 +12,29 @@ pub struct FloorGenParams {
     pub map_gen_kind: MapGenKind,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MapGenKind {
     Flat,
     Fractal,
     Lattice,
     Rooms,
+    /// Caves generated by cellular automata
+    Cave {
+        /// Ratio of initial random walls
+        wall_ratio: f32,
+        /// The number of automaton steps
+        n_step: u32,
+        /// A floor tile becomes wall if the number of adjacent walls is equal or more than this
+        birth_limit: u32,
+        /// A wall tile remains if the number of adjacent walls is equal or more than this
+        survival_limit: u32,
+    },
+    /// Rooms in binary space partitioned areas linked by corridors
+    Bsp {
+        min_room_size: u32,
+        max_room_size: u32,
+        /// Maximum depth of partitioning
+        max_depth: u32,
+    },
 }
@@ -1,4 +1,48 @@
+use common::gamedata::{AffixKind, ItemKindRough};
+use std::collections::HashMap;
+
 #[derive(Serialize, Deserialize)]
 pub struct Item {
     pub quality_level_factor: u32,
+    /// Item id consumed to open a locked door
+    pub key_item_id: String,
+    /// Item kinds identified by kind, and text ids of their random appearances
+    pub appearances: HashMap<ItemKindRough, Vec<String>>,
+    /// Item kinds generated unidentified
+    pub unidentified_kinds: Vec<ItemKindRough>,
+    /// Probability that a generated item is blessed
+    pub blessed_prob: f32,
+    /// Probability that a generated item is cursed
+    pub cursed_prob: f32,
+    /// Effectiveness factor for blessed items
+    pub blessed_eff_factor: f32,
+    /// Effectiveness factor for cursed items
+    pub cursed_eff_factor: f32,
+    /// Added to item generation level when the Appraisal skill is tested
+    pub appraisal_difficulty: u32,
+    /// Probability to add each affix to generated items
+    pub affix_prob: f32,
+    /// The maximum number of affixes for an item
+    pub max_affixes: u32,
+    /// Power of status effects caused by affixes on hit
+    pub on_hit_status_power: f32,
+    /// Affixes that can be added to generated items
+    pub affixes: Vec<AffixRule>,
+}
+
+/// Random affix of generated items
+#[derive(Serialize, Deserialize)]
+pub struct AffixRule {
+    pub kind: AffixKind,
+    /// Item kinds this affix can be added to
+    pub item_kinds: Vec<ItemKindRough>,
+    /// This affix is not generated below this level
+    pub min_level: u32,
+    pub gen_weight: f32,
+    /// The maximum value at min_level
+    pub base_value: f32,
+    /// Increase of the maximum value per generation level above min_level
+    pub value_per_level: f32,
+    /// Upper limit of the value
+    pub max_value: u16,
 }
@@ -24,7 +24,9 @@ pub mod npc_ai;
 pub mod params;
 pub mod quest;
 pub mod race;
+pub mod stealth;
 pub mod town;
+pub mod view;
 
 use lazy_static::lazy_static;
 use serde::de::Deserialize;
@@ -56,7 +58,9 @@ pub struct Rules {
     pub params: params::Params,
     pub quest: quest::Quest,
     pub race: race::Races,
+    pub stealth: stealth::Stealth,
     pub town: town::Town,
+    pub view: view::View,
 }
 
 impl Rules {
@@ -132,7 +136,9 @@ impl Rules {
             params: read_from_json(&rules_dir.join("params.json")),
             quest: read_from_json(&rules_dir.join("quest.json")),
             race: read_from_json(&rules_dir.join("race.json")),
+            stealth: read_from_json(&rules_dir.join("stealth.json")),
             town: read_from_json(&rules_dir.join("town.json")),
+            view: read_from_json(&rules_dir.join("view.json")),
         }
     }
 }
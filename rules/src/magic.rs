@@ -2,4 +2,6 @@
 #[derive(Serialize, Deserialize)]
 pub struct Magic {
     pub magic_device_base_power: f32,
+    /// Power of active skills per INT
+    pub active_skill_base_power: f32,
 }
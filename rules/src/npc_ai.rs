@@ -14,16 +14,71 @@ impl NpcAIs {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NpcAI {
-    pub move_kind: MoveKind,
-    #[serde(default)]
-    pub walk_prob: f32,
+    /// The root node of the behavior tree evaluated at every NPC turn
+    pub tree: AiNode,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Node of NPC behavior tree.
+/// Evaluated nodes succeed or fail. An action node fails if the action is not available,
+/// and evaluation stops when an action is decided.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AiNode {
+    /// Evaluate children in order, and succeed at the first succeeded child
+    Select(Vec<AiNode>),
+    /// Evaluate children in order, and fail at the first failed child
+    Sequence(Vec<AiNode>),
+    /// Succeed if the condition is satisfied
+    Cond(AiCond),
+    /// Succeed if the action is available
+    Action(AiAction),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AiCond {
+    /// Always true
+    True,
+    Not(Box<AiCond>),
+    /// True with given probability
+    Prob(f32),
+    /// HP / max HP is lower than given ratio
+    HpBelow(f32),
+    /// An ally in view, including itself, has lower HP ratio than given value
+    AllyHpBelow(f32),
+    /// A hostile character is in view
+    EnemyInView,
+    /// The distance to the nearest hostile character is equal or less than given value
+    EnemyWithin(u32),
+    /// The character equips a ranged weapon
+    HasRangedWeapon,
+    /// The distance to the leader is larger than given value
+    LeaderFartherThan(u32),
+    /// The distance to the initial position is larger than given value
+    AwayFromInitialPos(u32),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-pub enum MoveKind {
-    NoMove,
-    Melee,
-    Wander,
-    Return,
+pub enum AiAction {
+    /// Do nothing
+    Wait,
+    /// Move to a random direction
+    RandomWalk,
+    /// Move to the nearest hostile character, and attack it if adjacent
+    ApproachEnemy,
+    /// Move away from the nearest hostile character in view
+    FleeFromEnemy,
+    /// Shoot the nearest hostile character in range by the equipped ranged weapon
+    ShootEnemy,
+    /// Throw an item in the inventory that has throw effect
+    ThrowItem,
+    /// Release the magical effect of a charged item in the inventory
+    UseMagicItem,
+    /// Use the active skill of given id
+    ActiveSkill(String),
+    /// Move to the leader
+    FollowLeader,
+    /// Move to the initial position
+    ReturnToInitialPos,
 }
@@ -5,4 +5,51 @@ use std::collections::HashMap;
 pub struct Quest {
     /// The probability of choose npc for monster slaying quest
     pub slay_race_probability: HashMap<String, f32>,
+    /// The probability of choose npc for escort quest
+    pub escort_race_probability: HashMap<String, f32>,
+    /// The number of quests offered in a town
+    pub n_town_quests: u32,
+    /// Templates of generated quests
+    pub templates: HashMap<String, QuestTemplate>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestKind {
+    SlayMonsters,
+    Deliver,
+    Fetch,
+    Escort,
+    ExploreFloor,
+    Craft,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuestTemplate {
+    pub kind: QuestKind,
+    /// Weight to be chosen
+    pub weight: f32,
+    /// The template is available if the player's level is higher than this
+    #[serde(default)]
+    pub min_level: u32,
+    /// Candidates of the target item or character template ids.
+    /// If empty, the target is chosen by the player's level.
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// Range of the number of monsters or items, or the dungeon floor to reach
+    #[serde(default = "default_goal")]
+    pub goal: [u32; 2],
+    /// Reward money is reward_money + reward_money_per_level * level + reward_money_per_goal * goal
+    pub reward_money: i64,
+    #[serde(default)]
+    pub reward_money_per_level: i64,
+    #[serde(default)]
+    pub reward_money_per_goal: i64,
+    /// Days until the deadline. No deadline if None.
+    #[serde(default)]
+    pub deadline_days: Option<u32>,
+}
+
+fn default_goal() -> [u32; 2] {
+    [1, 1]
 }
@@ -0,0 +1,26 @@
+/// Rules for stealth, noise and NPC awareness
+#[derive(Serialize, Deserialize)]
+pub struct Stealth {
+    /// Noise volume (distance in tiles) of walking
+    pub noise_walk: i32,
+    /// Noise volume of attacks
+    pub noise_combat: i32,
+    /// Noise volume of digging walls
+    pub noise_mining: i32,
+    /// Noise volume of passing through or unlocking doors
+    pub noise_door: i32,
+    /// Walking noise is multiplied by stealth_base / (stealth_base + Stealth skill level)
+    pub stealth_base: f32,
+    /// The probability to notice an enemy in sight at distance 0
+    pub notice_probability: f32,
+    /// Notice probability is divided by (1.0 + stealth_factor * Stealth skill level)
+    pub stealth_factor: f32,
+    /// Notice probability is multiplied by this when the character is suspicious
+    pub suspicious_notice_factor: f32,
+    /// Turns to keep being suspicious
+    pub suspicious_turns: u32,
+    /// Turns to keep hunting after losing sight of the target
+    pub hunting_turns: u32,
+    /// Attack power is multiplied by this for attacks to unaware targets
+    pub sneak_attack_factor: f32,
+}
@@ -5,4 +5,35 @@ pub struct Town {
     pub min_shop_items: u32,
     /// The maximum number of shop items
     pub max_shop_items: u32,
+    /// Shops are restocked after this days passed
+    pub restock_interval_days: u32,
+    /// Shopkeeper's money after restocking is shop_money_base + shop_money_per_level * shop level
+    pub shop_money_base: i64,
+    pub shop_money_per_level: i64,
+    /// Buying price is item price * this factor before bonuses
+    pub buy_price_factor: f32,
+    /// Selling price is item price * this factor before bonuses
+    pub sell_price_factor: f32,
+    /// The lower limit of buying price factor after bonuses
+    pub min_buy_price_factor: f32,
+    /// The upper limit of selling price factor after bonuses.
+    /// Must be lower than min_buy_price_factor.
+    pub max_sell_price_factor: f32,
+    /// Price bonus per player's charisma
+    pub price_bonus_per_cha: f32,
+    /// Price bonus per player's Trading skill level
+    pub price_bonus_per_trading: f32,
+    /// Price bonus per relation between the player and shopkeeper's faction
+    pub price_bonus_per_relation: f32,
+    /// Prices decrease by this factor per supplied item
+    pub supply_price_drop: f32,
+    /// The lower limit of price factor by supply
+    pub min_supply_price_factor: f32,
+    /// Supply is multiplied by this value at restocking
+    pub supply_decay: f32,
+    /// Needed development for one shop level
+    pub development_per_shop_level: u32,
+    /// Development added when the player receives quest rewards in the town
+    pub development_per_quest: u32,
+    pub max_shop_level: u32,
 }
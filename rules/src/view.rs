@@ -0,0 +1,12 @@
+/// Rules for character sight and light
+#[derive(Serialize, Deserialize)]
+pub struct View {
+    /// Characters can see tiles in this distance even if it is completely dark
+    pub dark_view_range: i32,
+    /// Ambient light at the first floor of dungeons (0.0 - 1.0)
+    pub dungeon_light_base: f32,
+    /// Decrease of ambient light per dungeon floor
+    pub dungeon_light_per_floor: f32,
+    /// Ambient light of outdoor maps for each hour (0.0 - 1.0)
+    pub outdoor_light: [f32; 24],
+}
@@ -9,6 +9,35 @@ fn get_matches() -> ArgMatches<'static> {
                 .long("fix-rand")
                 .help("Fixes the state of RNG when game start"),
         )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Sets the seed of RNG for a new game")
+                .takes_value(true)
+                .validator(|s| s.parse::<u64>().map(|_| ()).map_err(|e| e.to_string())),
+        )
+        .arg(
+            Arg::with_name("record")
+                .long("record")
+                .value_name("FILE")
+                .help("Records player inputs in a new game to the file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("replay")
+                .long("replay")
+                .value_name("FILE")
+                .help("Replays the recorded file without window, and checks the result")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("upgrade-save")
+                .long("upgrade-save")
+                .value_name("DIR")
+                .help("Upgrades save data in the directory to the current format version")
+                .takes_value(true),
+        )
         .get_matches()
 }
 
@@ -19,5 +48,21 @@ pub fn modify_config_by_args(mut config: Config) -> Config {
         config.fix_rand = true;
     }
 
+    if let Some(seed) = matches.value_of("seed") {
+        config.seed = Some(seed.parse().unwrap());
+    }
+
+    if let Some(path) = matches.value_of("record") {
+        config.record_file = Some(path.into());
+    }
+
+    if let Some(path) = matches.value_of("replay") {
+        config.replay_file = Some(path.into());
+    }
+
+    if let Some(path) = matches.value_of("upgrade-save") {
+        config.upgrade_save_dir = Some(path.into());
+    }
+
     config
 }
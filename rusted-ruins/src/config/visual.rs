@@ -184,6 +184,7 @@ pub struct NewGameDialogConfig {
 pub struct ChooseSaveFileDialogConfig {
     pub rect: CfgRect,
     pub list_size: u32,
+    pub column_pos: Vec<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1,5 +1,6 @@
+use common::gamedata::{Item, ItemAttribute};
 use common::gobj;
-use common::obj::ImgObject;
+use common::obj::{ImgObject, ImgVariationRule};
 use common::objholder::*;
 use sdl2::image::ImageRWops;
 use sdl2::rect::Rect;
@@ -119,3 +120,24 @@ impl_iconidx! {
     {Item, ItemIdx},
     {UIImg, UIImgIdx}
 }
+
+impl From<&Item> for IconIdx {
+    fn from(item: &Item) -> IconIdx {
+        let obj = gobj::get_obj(item.idx);
+
+        if obj.img.variation_rule == ImgVariationRule::RandomOnGen {
+            for attr in &item.attributes {
+                match attr {
+                    ItemAttribute::ImageVariation(n) => {
+                        return IconIdx::Item {
+                            idx: item.idx,
+                            i_pattern: *n,
+                        };
+                    }
+                    _ => (),
+                }
+            }
+        }
+        IconIdx::from(item.idx)
+    }
+}
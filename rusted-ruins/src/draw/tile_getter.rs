@@ -36,8 +36,12 @@ impl<'a> BackgroundDrawInfo<'a> {
         di.tile = tile;
 
         if map.is_inside(pos) {
-            if let Some(special_tile_id) = map.observed_tile[pos].special.obj_id() {
-                let special_tile_obj: &'static SpecialTileObject = gobj::get_by_id(special_tile_id);
+            if let Some(special_tile_obj) = map.observed_tile[pos]
+                .special
+                .obj_id()
+                .and_then(gobj::get_by_id_checked::<SpecialTileObject>)
+            {
+                let special_tile_id = &special_tile_obj.id;
                 if special_tile_obj.always_background {
                     let special_tile_idx: SpecialTileIdx = gobj::id_to_idx(special_tile_id);
                     di.special = Some(special_tile_idx);
@@ -66,8 +70,12 @@ impl<'a> ForegroundDrawInfo<'a> {
         let mut di = ForegroundDrawInfo::default();
 
         if map.is_inside(pos) {
-            if let Some(special_tile_id) = map.observed_tile[pos].special.obj_id() {
-                let special_tile_obj: &'static SpecialTileObject = gobj::get_by_id(special_tile_id);
+            if let Some(special_tile_obj) = map.observed_tile[pos]
+                .special
+                .obj_id()
+                .and_then(gobj::get_by_id_checked::<SpecialTileObject>)
+            {
+                let special_tile_id = &special_tile_obj.id;
                 if !special_tile_obj.always_background {
                     let special_tile_idx: SpecialTileIdx = gobj::id_to_idx(special_tile_id);
                     di.special = Some(special_tile_idx);
@@ -1,8 +1,8 @@
 extern crate rusted_ruins_audio as audio;
 extern crate rusted_ruins_common as common;
+#[macro_use]
+extern crate rusted_ruins_core;
 extern crate rusted_ruins_geom as geom;
-extern crate rusted_ruins_map_generator as map_generator;
-extern crate rusted_ruins_rng as rng;
 extern crate rusted_ruins_rules as rules;
 #[macro_use]
 extern crate lazy_static;
@@ -13,25 +13,33 @@ extern crate log as applog;
 
 #[macro_use]
 mod error;
-#[macro_use]
-mod log;
-#[macro_use]
-mod text;
-mod chara_log;
 mod config;
 mod context;
 mod draw;
 mod eventhandler;
-mod game;
 mod screen;
 mod sdltypeconv;
 mod window;
 
+use rusted_ruins_core::{chara_log, game, log, text};
+use rusted_ruins_core::{init_obj, init_rules};
+
 fn main() {
     setup_logger();
     init_lazy_statics();
     init_obj();
     init_rules();
+    rusted_ruins_core::audio::set_audio_funcs(audio::play_sound, audio::play_music);
+
+    if let Some(path) = crate::config::CONFIG.replay_file.as_ref() {
+        let result = game::replay::replay_file(path);
+        std::process::exit(if result { 0 } else { 1 });
+    }
+
+    if let Some(path) = crate::config::CONFIG.upgrade_save_dir.as_ref() {
+        let result = game::saveload::upgrade_save_dirs(path);
+        std::process::exit(if result { 0 } else { 1 });
+    }
 
     let sdl_context = SdlContext::init();
     let mut screen = screen::Screen::new(&sdl_context.sdl_context);
@@ -67,22 +75,6 @@ fn init_lazy_statics() {
     log::init();
 }
 
-fn init_obj() {
-    let mut data_dirs = crate::config::get_data_dirs();
-    for d in data_dirs.iter_mut() {
-        info!("Loading objects from \"{}\"", d.to_string_lossy());
-        d.push("paks");
-    }
-    common::gobj::init(data_dirs);
-}
-
-fn init_rules() {
-    rules::init(
-        &*crate::config::ASSETS_DIR,
-        crate::config::ADDON_DIR.as_ref(),
-    );
-}
-
 /// Setup logger. It is not game logger. It is for debug and warning infomation.
 fn setup_logger() {
     env_logger::builder().format_timestamp(None).init();
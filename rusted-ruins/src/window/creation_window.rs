@@ -4,7 +4,7 @@ use super::widget::*;
 use crate::config::UI_CFG;
 use crate::draw::border::draw_window_border;
 use crate::game::creation;
-use crate::text::{misc_txt, obj_txt, ui_txt, ToText};
+use crate::text::{misc_txt, obj_txt, ui_txt, ItemName, ToText};
 use common::gamedata::*;
 use common::gobj;
 use common::objholder::*;
@@ -236,7 +236,7 @@ impl CreationDetailDialog {
             format!(
                 "{}: {}",
                 ui_txt("label_text-creation-use-facility"),
-                facility_item.to_text()
+                ItemName::new(gd, facility_item).to_text()
             )
         } else if let Some(facility_name) = recipe.facility.as_ref() {
             let text_id = format!("facility-{}", facility_name);
@@ -15,7 +15,7 @@ pub fn create_dialog_from_request(
     Some(match req {
         DialogOpenRequest::YesNo { mut callback, msg } => {
             let msgdialog = msg_dialog::MsgDialog::with_yesno(&*msg, move |pa, n| {
-                callback(pa, n == 0);
+                pa.answer_yes_no(&mut *callback, n == 0);
                 super::DialogResult::Close
             });
             Box::new(msgdialog)
@@ -31,7 +31,9 @@ pub fn create_dialog_from_request(
         DialogOpenRequest::ShopBuy { cid } => {
             Box::new(ItemWindow::new(ItemWindowMode::ShopBuy { cid }, game))
         }
-        DialogOpenRequest::ShopSell => Box::new(ItemWindow::new(ItemWindowMode::ShopSell, game)),
+        DialogOpenRequest::ShopSell { cid } => {
+            Box::new(ItemWindow::new(ItemWindowMode::ShopSell { cid }, game))
+        }
         DialogOpenRequest::PickUpItem => Box::new(ItemWindow::new(ItemWindowMode::PickUp, game)),
         DialogOpenRequest::Quest => Box::new(super::quest_window::QuestWindow::new(game)),
         DialogOpenRequest::GameOver => Box::new(super::exit_window::GameOverWindow::new()),
@@ -63,7 +63,7 @@ impl DialogWindow for ExitWindow {
                 if let DialogCloseValue::Index(n) = v {
                     match n {
                         0 => {
-                            pa.game().save_file();
+                            pa.save_file();
                             return DialogResult::Close;
                         }
                         1 => return DialogResult::Quit,
@@ -12,11 +12,13 @@ const COMMANDS: &[Command] = &[
     Command::OpenHelpWin,
     Command::OpenStatusWin,
     Command::OpenGameInfoWin,
+    Command::OpenQuestWin,
     Command::OpenItemMenu,
     Command::OpenEquipWin,
     Command::EatItem,
     Command::DrinkItem,
     Command::DropItem,
+    Command::Search,
     Command::OpenExitWin,
     Command::OpenCreationWin,
 ];
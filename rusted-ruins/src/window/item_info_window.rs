@@ -24,7 +24,7 @@ impl ItemInfoWindow {
     pub fn new(il: ItemLocation, game: &Game) -> ItemInfoWindow {
         let c = &UI_CFG.item_info_window;
         let item = game.gd.get_item(il);
-        let info = ItemInfoText::new(item.0);
+        let info = ItemInfoText::new(&game.gd, item.0);
 
         let item_image = ImageWidget::item(c.item_image, game.gd.get_item(il).0);
         let item_name = LabelWidget::new(c.item_name, &info.item_name, FontKind::M);
@@ -7,8 +7,9 @@ use crate::draw::border::draw_window_border;
 use crate::eventhandler::InputMode;
 use crate::game::extrait::*;
 use crate::game::item::filter::*;
+use crate::game::shop::{buy_price, sell_price};
 use crate::game::{DialogOpenRequest, Game, InfoGetter};
-use crate::text::ToText;
+use crate::text::{ItemName, ToText};
 use common::gamedata::*;
 use common::gobj;
 use sdl2::rect::Rect;
@@ -24,7 +25,9 @@ pub enum ItemWindowMode {
     Use,
     Release,
     Read,
-    ShopSell,
+    ShopSell {
+        cid: CharaId,
+    },
     ShopBuy {
         cid: CharaId,
     },
@@ -192,21 +195,21 @@ impl ItemWindow {
         match self.mode {
             ItemWindowMode::List => {
                 let filtered_list = gd.get_filtered_item_list(ill_player, ItemFilter::all());
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::PickUp => {
                 let filtered_list = gd.get_filtered_item_list(ill_ground, ItemFilter::all());
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::Drop => {
                 let filtered_list = gd.get_filtered_item_list(ill_player, ItemFilter::all());
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::Throw => {
                 let player_str = gd.chara.get(CharaId::Player).attr.str;
                 let filter = ItemFilter::new().throwable(Some(player_str));
                 let filtered_list = gd.get_filtered_item_list(ill_player, filter);
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::Drink => {
                 let filtered_list = gd.get_merged_filtered_item_list(
@@ -214,7 +217,7 @@ impl ItemWindow {
                     ill_player,
                     ItemFilter::new().drinkable(true),
                 );
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::Eat => {
                 let filtered_list = gd.get_merged_filtered_item_list(
@@ -222,7 +225,7 @@ impl ItemWindow {
                     ill_player,
                     ItemFilter::new().eatable(true),
                 );
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::Use => {
                 let filtered_list = gd.get_merged_filtered_item_list(
@@ -230,7 +233,7 @@ impl ItemWindow {
                     ill_player,
                     ItemFilter::new().usable(true),
                 );
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::Release => {
                 let filtered_list = gd.get_merged_filtered_item_list(
@@ -238,7 +241,7 @@ impl ItemWindow {
                     ill_player,
                     ItemFilter::new().kind_rough(ItemKindRough::MagicDevice),
                 );
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::Read => {
                 let filtered_list = gd.get_merged_filtered_item_list(
@@ -246,29 +249,29 @@ impl ItemWindow {
                     ill_player,
                     ItemFilter::new().readable(true),
                 );
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::ShopBuy { cid } => {
                 let ill = ItemListLocation::Shop { cid };
                 let filtered_list = gd.get_filtered_item_list(ill, ItemFilter::new());
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
-            ItemWindowMode::ShopSell => {
+            ItemWindowMode::ShopSell { .. } => {
                 let ill = ItemListLocation::Chara {
                     cid: CharaId::Player,
                 };
                 let filtered_list = gd.get_filtered_item_list(ill, ItemFilter::new());
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
             ItemWindowMode::Select { ill, filter, .. } => {
                 let filtered_list = gd.get_filtered_item_list(ill, filter);
-                self.update_list(filtered_list);
+                self.update_list(filtered_list, gd);
             }
         }
         self.update_label(gd);
     }
 
-    fn update_list(&mut self, list: FilteredItemList) {
+    fn update_list(&mut self, list: FilteredItemList, gd: &GameData) {
         self.list.set_n_item(list.clone().count() as u32);
 
         let mode = &self.mode;
@@ -283,12 +286,12 @@ impl ItemWindow {
         self.list.update_rows_by_func(move |i| {
             let (_, ref item, n_item) = list.clone().nth(i as usize).unwrap();
 
-            let item_text = format!("{} x {}", item.to_text(), n_item);
+            let item_text = format!("{} x {}", ItemName::new(gd, item).to_text(), n_item);
 
             // Infomation displayed in the right column
             let additional_info = match mode {
-                ItemWindowMode::ShopBuy { .. } => format!("{}G", item.price()),
-                ItemWindowMode::ShopSell => format!("{}G", item.selling_price()),
+                ItemWindowMode::ShopBuy { cid } => format!("{}G", buy_price(gd, item, *cid)),
+                ItemWindowMode::ShopSell { cid } => format!("{}G", sell_price(gd, item, *cid)),
                 _ => format!("{:.2}kg", item.w() as f32 / 1000.0),
             };
 
@@ -303,7 +306,7 @@ impl ItemWindow {
             )
             .right();
 
-            (item.icon(), t1, t2)
+            (IconIdx::from(*item), t1, t2)
         });
     }
 
@@ -318,9 +321,11 @@ impl ItemWindow {
         ));
 
         match self.mode {
-            ItemWindowMode::ShopBuy { .. } | ItemWindowMode::ShopSell { .. } => {
+            ItemWindowMode::ShopBuy { cid } | ItemWindowMode::ShopSell { cid } => {
+                let player = gd.player.money();
+                let shop = gd.get_shop(cid).money;
                 self.info_label1
-                    .set_text(&format!("{} G", gd.player.money()));
+                    .set_text(&ui_txt_format!("label_text-shop-money"; player=player, shop=shop));
             }
             _ => (),
         }
@@ -373,13 +378,13 @@ impl ItemWindow {
                     DialogResult::CloseAll
                 }
             }
-            ItemWindowMode::ShopBuy { .. } => {
-                pa.buy_item(il);
+            ItemWindowMode::ShopBuy { cid } => {
+                pa.buy_item(il, cid);
                 self.update_by_mode(pa.gd());
                 DialogResult::Continue
             }
-            ItemWindowMode::ShopSell => {
-                pa.sell_item(il);
+            ItemWindowMode::ShopSell { cid } => {
+                pa.sell_item(il, cid);
                 self.update_by_mode(pa.gd());
                 DialogResult::Continue
             }
@@ -136,7 +136,7 @@ impl MainWindow {
                             match mode {
                                 MainWindowMode::Target { callback, .. } => {
                                     let callback: Box<dyn Fn(&mut DoPlayerAction) + 'static> =
-                                        Box::new(move |pa| callback(pa, Target::Tile(pos)));
+                                        Box::new(move |pa| pa.select_target(&*callback, pos));
                                     return ConvertMouseEventResult::DoAction(callback);
                                 }
                                 _ => (),
@@ -39,7 +39,6 @@ use crate::eventhandler::EventHandler;
 use crate::game::{Command, DoPlayerAction, GameState, InfoGetter, UiRequest};
 use crate::SdlContext;
 use common::gamedata::*;
-use geom::*;
 use sdl2::keyboard::TextInputUtil;
 use sdl2::render::TextureCreator;
 use sdl2::video::WindowContext;
@@ -207,6 +206,14 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
             }
         }
 
+        // Continue auto-travel if player did not act in this turn
+        if self.game.get_state() == GameState::PlayerTurn
+            && self.mode.is_on_game()
+            && self.window_stack.is_empty()
+        {
+            DoPlayerAction::new(&mut self.game).step_auto_travel();
+        }
+
         // After advancing turn and processing command, game may start animation.
         self.anim = self.game.pop_animation();
 
@@ -273,7 +280,7 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
         if anim.is_some() {
             self.passed_frame += 1;
         }
-        crate::chara_log::advance_frame();
+        crate::chara_log::advance_frame(UI_CFG.damage.n_frame);
     }
 
     pub fn animation_now(&self) -> bool {
@@ -419,26 +426,13 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
 
         let mut pa = DoPlayerAction::new(&mut self.game);
 
+        if pa.exec_game_command(&command) {
+            return true;
+        }
+        pa.interrupt_auto_travel();
+
         use self::item_window::*;
         match command {
-            Command::Move { dir } => {
-                pa.try_move(dir);
-            }
-            Command::MoveTo { dest } => {
-                pa.move_to(dest);
-            }
-            Command::Shoot { target } => {
-                pa.shoot(target);
-            }
-            Command::UseTool { target } => {
-                pa.use_tool(target);
-            }
-            Command::Enter => {
-                // If player is on stairs, move from this map
-                if pa.gd().on_map_entrance() {
-                    pa.goto_next_floor(Direction::none(), true);
-                }
-            }
             Command::OpenCreationWin => {
                 let dialog = Box::new(creation_window::create_creation_window_group(
                     pa.game(),
@@ -478,6 +472,10 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
                 let dialog = Box::new(game_info_window::GameInfoWindow::new(pa.game()));
                 self.push_dialog_window(dialog);
             }
+            Command::OpenQuestWin => {
+                let dialog = Box::new(quest_window::StoryQuestWindow::new(pa.game()));
+                self.push_dialog_window(dialog);
+            }
             Command::PickUpItem => {
                 if !pa.gd().item_on_player_tile().is_empty() {
                     let item_window = ItemWindow::new(ItemWindowMode::PickUp, pa.game());
@@ -569,6 +567,9 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
                     self.game = game;
                     self.game.update_before_player_turn();
                     self.game.start_new_game();
+                    if let Some(path) = crate::config::CONFIG.record_file.as_ref() {
+                        self.game.start_recording(path);
+                    }
                     game_log_i!("start"; version=env!("CARGO_PKG_VERSION"));
                 }
                 _ => unreachable!(),
@@ -576,6 +577,7 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
             WindowManageMode::OnGame(_) => match result {
                 SpecialDialogResult::ReturnToStartScreen => {
                     info!("Return to start screen");
+                    self.game.finish_recording();
                     crate::log::clear();
                     self.window_stack.clear();
                     self.push_dialog_window(Box::new(start_window::StartDialog::new()));
@@ -588,6 +590,7 @@ impl<'sdl, 't> WindowManager<'sdl, 't> {
 
     fn push_dialog_window(&mut self, w: Box<dyn DialogWindow>) {
         w.sound(true);
+        DoPlayerAction::new(&mut self.game).interrupt_auto_travel();
         if !w.mainwin_cursor() {
             match &mut self.mode {
                 WindowManageMode::OnGame(windows) => {
@@ -4,8 +4,11 @@ use super::widget::*;
 use crate::draw::border::draw_window_border;
 use crate::eventhandler::InputMode;
 use crate::game::quest::available_quests;
-use crate::text::ToText;
-use common::gamedata::Quest;
+use crate::text::{misc_txt, ToText};
+use common::gamedata::{GameData, Quest, QuestState, StoryQuest};
+use common::gobj;
+use common::obj::StoryQuestObject;
+use common::storyquest::stage_text_id;
 
 pub struct QuestWindow {
     rect: Rect,
@@ -61,7 +64,8 @@ impl QuestWindow {
             self.description.set_text("");
         } else {
             let q = &available_quests(&game.gd)[self.list.get_current_choice() as usize];
-            self.description.set_text(&quest_decription_text(q));
+            self.description
+                .set_text(&quest_decription_text(&game.gd, q));
         }
     }
 }
@@ -135,10 +139,173 @@ impl DialogWindow for QuestWindow {
     }
 }
 
-fn quest_decription_text(quest: &Quest) -> String {
-    match quest {
+/// Shows the player's story quests and their stage history
+pub struct StoryQuestWindow {
+    rect: Rect,
+    list: TextListWidget,
+    description: LabelWidget,
+    escape_click: bool,
+}
+
+impl StoryQuestWindow {
+    pub fn new(game: &Game) -> StoryQuestWindow {
+        let rect = UI_CFG.quest_window.rect.into();
+        let mut w = StoryQuestWindow {
+            rect,
+            list: TextListWidget::new(
+                (0i32, 0i32, rect.w as u32, rect.h as u32),
+                vec![6],
+                UI_CFG.quest_window.n_row,
+                true,
+            ),
+            description: LabelWidget::wrapped(
+                (
+                    0i32,
+                    (UI_CFG.quest_window.n_row as i32 + 1)
+                        * UI_CFG.list_widget.h_row_default as i32,
+                    rect.width(),
+                    0,
+                ),
+                "",
+                FontKind::M,
+                rect.width(),
+            ),
+            escape_click: false,
+        };
+        let rows: Vec<TextCache> = game
+            .gd
+            .quest
+            .story_quests()
+            .map(|quest| {
+                let text = story_quest_title(quest);
+                TextCache::one(text, FontKind::M, UI_CFG.color.normal_font.into())
+            })
+            .collect();
+        w.list.set_items(rows);
+        w.update_description(game);
+        w
+    }
+
+    fn update_description(&mut self, game: &Game) {
+        let i = self.list.get_current_choice() as usize;
+        if let Some(quest) = game.gd.quest.story_quests().nth(i) {
+            self.description
+                .set_text(&story_quest_description_text(quest));
+        } else {
+            self.description.set_text("");
+        }
+    }
+}
+
+impl Window for StoryQuestWindow {
+    fn draw(&mut self, context: &mut Context, _game: &Game, _anim: Option<(&Animation, u32)>) {
+        draw_window_border(context, self.rect);
+        self.list.draw(context);
+        self.description.draw(context);
+    }
+}
+
+impl DialogWindow for StoryQuestWindow {
+    fn process_command(&mut self, command: &Command, pa: &mut DoPlayerAction) -> DialogResult {
+        check_escape_click!(self, command, false);
+
+        let command = command.relative_to(self.rect);
+        if let Some(response) = self.list.process_command(&command) {
+            match response {
+                ListWidgetResponse::Select(_)
+                | ListWidgetResponse::SelectionChanged
+                | ListWidgetResponse::Scrolled => {
+                    self.update_description(pa.game());
+                }
+                _ => (),
+            }
+            return DialogResult::Continue;
+        }
+
+        match command {
+            Command::Cancel => DialogResult::Close,
+            _ => DialogResult::Continue,
+        }
+    }
+
+    fn mode(&self) -> InputMode {
+        InputMode::Dialog
+    }
+}
+
+fn story_quest_title(quest: &StoryQuest) -> String {
+    match quest.state {
+        QuestState::Completed => misc_txt_format!("story-quest-completed"; quest=quest),
+        QuestState::Failed => misc_txt_format!("story-quest-failed"; quest=quest),
+        _ => quest.to_text().into(),
+    }
+}
+
+/// Quest log texts of reached stages, and objectives of the current stage
+fn story_quest_description_text(quest: &StoryQuest) -> String {
+    let mut lines: Vec<String> = quest
+        .stages
+        .iter()
+        .map(|(stage, time)| {
+            let date = time.into_date();
+            let text = misc_txt(&stage_text_id(&quest.id, stage));
+            misc_txt_format!(
+                "desc-story-quest-stage"; year=date.year, month=date.month, day=date.day, text=text)
+        })
+        .collect();
+
+    let obj: Option<&StoryQuestObject> = gobj::get_by_id_checked(&quest.id);
+    let stage = quest
+        .current_stage()
+        .and_then(|stage| obj.and_then(|obj| obj.stage(stage)));
+    if let Some(stage) = stage {
+        if quest.state == QuestState::Active && !stage.objectives.is_empty() {
+            lines.push(misc_txt("desc-story-quest-objectives"));
+            for objective in &stage.objectives {
+                lines.push(format!("- {}", misc_txt(objective)));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+fn quest_decription_text(gd: &GameData, quest: &Quest) -> String {
+    let mut text = match quest {
         Quest::SlayMonsters { idx, goal, .. } => {
             misc_txt_format!("desc-quest-slay_monsters"; monster=idx, n=goal)
         }
+        Quest::Deliver {
+            idx, destination, ..
+        } => {
+            let site = gd.region.get_site(*destination);
+            misc_txt_format!("desc-quest-deliver"; item=idx, site=site)
+        }
+        Quest::Fetch { idx, goal, .. } => {
+            misc_txt_format!("desc-quest-fetch"; item=idx, n=goal)
+        }
+        Quest::Escort {
+            idx, destination, ..
+        } => {
+            let site = gd.region.get_site(*destination);
+            misc_txt_format!("desc-quest-escort"; chara=idx, site=site)
+        }
+        Quest::ExploreFloor { sid, floor, .. } => {
+            let site = gd.region.get_site(*sid);
+            let floor = floor + 1;
+            misc_txt_format!("desc-quest-explore_floor"; site=site, floor=floor)
+        }
+        Quest::Craft { idx, goal, .. } => {
+            misc_txt_format!("desc-quest-craft"; item=idx, n=goal)
+        }
+    };
+
+    text.push(' ');
+    text.push_str(&misc_txt_format!("desc-quest-reward"; money=quest.reward().money));
+    if let Some(deadline) = quest.deadline() {
+        let date = deadline.into_date();
+        text.push(' ');
+        text.push_str(&misc_txt_format!(
+            "desc-quest-deadline"; year=date.year, month=date.month, day=date.day));
     }
+    text
 }
@@ -3,9 +3,9 @@ use super::widget::*;
 use super::SpecialDialogResult;
 use crate::config::{SCREEN_CFG, UI_CFG};
 use crate::text;
-use common::gamedata::GameData;
+use common::gamedata::MetaData;
 use std::ffi::OsStr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct StartWindow {
     title_screen: ImageWidget,
@@ -86,7 +86,7 @@ impl DialogWindow for StartDialog {
 
 pub struct ChooseSaveFileDialog {
     rect: Rect,
-    list: TextListWidget,
+    list: ListWidget<(TextCache, TextCache, TextCache)>,
     save_files: Vec<PathBuf>,
 }
 
@@ -94,26 +94,66 @@ impl ChooseSaveFileDialog {
     pub fn new() -> ChooseSaveFileDialog {
         let save_files =
             crate::game::saveload::save_file_list().expect("Error at reading save file directory");
+        let cfg = &UI_CFG.choose_save_file_dialog;
+        let rect: Rect = cfg.rect.into();
 
-        let file_name_list: Vec<String> = save_files
-            .iter()
-            .map(|path| {
-                path.file_stem()
-                    .unwrap_or_else(|| OsStr::new(""))
-                    .to_string_lossy()
-                    .into_owned()
-            })
-            .collect();
-        let rect = UI_CFG.choose_save_file_dialog.rect.into();
+        let mut list = ListWidget::with_scroll_bar(
+            (0, 0, rect.width(), rect.height()),
+            cfg.column_pos.clone(),
+            cfg.list_size,
+            false,
+        );
+        list.set_items(save_files.iter().map(|path| slot_row(path)).collect());
 
         ChooseSaveFileDialog {
             rect,
-            list: TextListWidget::text_choices((0, 0, rect.width(), rect.height()), file_name_list),
+            list,
             save_files,
         }
     }
 }
 
+/// Create a list row from the metadata of the save directory
+fn slot_row(path: &Path) -> (TextCache, TextCache, TextCache) {
+    let color: sdl2::pixels::Color = UI_CFG.color.normal_font.into();
+    let meta = match MetaData::load(path) {
+        Ok(meta) => meta,
+        Err(e) => {
+            warn!(
+                "Cannot read metadata of {:?}: {}",
+                path.to_string_lossy(),
+                e
+            );
+            MetaData::default()
+        }
+    };
+    let summary = meta.summary();
+
+    let mut name = if summary.chara_name.is_empty() {
+        path.file_stem()
+            .unwrap_or_else(|| OsStr::new(""))
+            .to_string_lossy()
+            .into_owned()
+    } else {
+        format!("{} Lv. {}", summary.chara_name, summary.level)
+    };
+    if crate::game::saveload::is_autosave(path) {
+        name = format!("[{}] {}", text::ui_txt("label_text-save-autosave"), name);
+    }
+
+    let play_time = format!(
+        "{}:{:02}",
+        summary.play_time / 3600,
+        summary.play_time / 60 % 60
+    );
+
+    (
+        TextCache::one(name, FontKind::M, color),
+        TextCache::one(&summary.location, FontKind::M, color),
+        TextCache::one(play_time, FontKind::M, color),
+    )
+}
+
 impl Window for ChooseSaveFileDialog {
     fn draw(&mut self, context: &mut Context, _game: &Game, _anim: Option<(&Animation, u32)>) {
         draw_window_border(context, self.rect);
@@ -128,7 +168,7 @@ impl DialogWindow for ChooseSaveFileDialog {
             match response {
                 ListWidgetResponse::Select(i) => {
                     // Any item is selected
-                    match GameData::load(&self.save_files[i as usize]) {
+                    match crate::game::saveload::load_save_file(&self.save_files[i as usize]) {
                         Ok(o) => {
                             return DialogResult::Special(SpecialDialogResult::NewGameStart(
                                 Box::new(o),